@@ -0,0 +1,7 @@
+-- Dimensions of the photos, from which the gallery reserves the space of every photo before it is loaded.
+ALTER TABLE photos
+    ADD COLUMN IF NOT EXISTS width INT CHECK ( width > 0 ),
+    ADD COLUMN IF NOT EXISTS height INT CHECK ( height > 0 );
+
+-- Pages of the gallery, the newest photos first.
+CREATE INDEX IF NOT EXISTS idx_photos_on_created_at_id ON photos( created_at DESC, id DESC );
@@ -0,0 +1,3 @@
+-- Key of the original file of the photos in the storage, independent of the storage backend.
+ALTER TABLE photos
+    ADD COLUMN IF NOT EXISTS storage_key TEXT UNIQUE;
@@ -5,9 +5,11 @@ use backend::{logger, settings, Error};
 use clap::Parser;
 use error_stack::{Report, ResultExt};
 
-fn main() -> Result<(), Report<Error>> {
+#[tokio::main]
+async fn main() -> Result<(), Report<Error>> {
     // Parse the command line arguments.
-    let cli_args = settings::CliArgs::parse();
+    let mut cli_args = settings::CliArgs::parse();
+    let command = cli_args.command.take();
 
     // Get the environment prefix for the settings variables.
     let env_prefix = cli_args
@@ -25,15 +27,59 @@ fn main() -> Result<(), Report<Error>> {
     .change_context( Error::InvalidConfigsDir )?;
 
     // Initialize the settings variables.
-    let configs =
+    let mut configs =
         settings::init( configs_dir.as_path(), &env_prefix, &cli_args ).change_context( Error::SettingsInitFailed )?;
 
-    // Tracing logs.
-    let ( _maybe_stdio_writer_guard, _maybe_file_writer_guard ) =
+    // The config commands run before the logger, so their output is not mixed with the logs.
+    match command {
+        Some( settings::Command::CheckConfig ) => {
+            println!( "The settings in {} are valid.", configs_dir.display() );
+            return Ok( () );
+        }
+        Some( settings::Command::PrintConfig ) => {
+            print!( "{}", configs.report.to_masked_toml() );
+            return Ok( () );
+        }
+        _ => {}
+    }
+
+    // The directories are only created and checked to run, e.g. the logs one, possibly asking the user for new ones.
+    configs.ensure_dirs( cli_args.is_interactive ).change_context( Error::SettingsInitFailed )?;
+
+    // Tracing logs, within the runtime as the traces are exported by a background task.
+    let ( maybe_stdio_writer_guard, maybe_file_writer_guard ) =
         logger::init( &configs.general.app_name, &configs.logger );
 
-    tracing::info!( "Starting {}", configs.general.app_name );
-    backend::init_server( configs.server, configs.database ).change_context( Error::ServerInitFailed )?;
+    match command {
+        Some( settings::Command::Export { output } ) => {
+            backend::export_backup( configs.database, configs.storage, output )
+                .await
+                .change_context( Error::BackupExportFailed )?;
+        }
+        Some( settings::Command::Restore { input } ) => {
+            backend::restore_backup( configs.database, configs.storage, input )
+                .await
+                .change_context( Error::BackupRestoreFailed )?;
+        }
+        // Handled before the logger initialization.
+        Some( settings::Command::CheckConfig | settings::Command::PrintConfig ) => {}
+        None => {
+            tracing::info!( "Starting {}", configs.general.app_name );
+
+            // Reload the settings when the configuration files change.
+            let configs_watcher = settings::watch( &configs_dir, &env_prefix, cli_args, configs.clone() )
+                .change_context( Error::SettingsWatchFailed )?;
+
+            backend::init_server( configs.server, configs.database, configs.storage, configs_watcher.subscribe() )
+                .await
+                .change_context( Error::ServerInitFailed )?;
+        }
+    }
+
+    // Flush the traces not exported yet and the logs still buffered in the non-blocking writers.
+    logger::shutdown();
+    drop( maybe_stdio_writer_guard );
+    drop( maybe_file_writer_guard );
 
     Ok( () )
 }
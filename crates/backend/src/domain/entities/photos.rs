@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Photo {
     pub id:          i32,
     pub created_at:  DateTime<Utc>,
@@ -10,4 +10,15 @@ pub struct Photo {
     pub url:         String,
     pub title:       String,
     pub description: Option<String>,
+
+    /// Dimensions in pixels, missing from the archives of the versions that didn't store them.
+    #[serde( default )]
+    pub width:       Option<i32>,
+    #[serde( default )]
+    pub height:      Option<i32>,
+
+    /// Key of the original file in the storage, missing for the photos only referenced by their url.
+    #[serde( default )]
+    #[sqlx( default )]
+    pub storage_key: Option<String>,
 }
@@ -5,6 +5,9 @@ pub enum Error {
     /// Failed to setup the settings.
     #[error( "Failed to setup the settings." )]
     SettingsInitFailed,
+    /// Failed to watch the configuration files.
+    #[error( "Failed to watch the configuration files." )]
+    SettingsWatchFailed,
     /// The configs directory provided is invalid.
     #[error( "The configs directory provided is invalid." )]
     InvalidConfigsDir,
@@ -14,4 +17,10 @@ pub enum Error {
     /// Failed to initialize the server.
     #[error( "Failed to initialize the server." )]
     ServerInitFailed,
+    /// Failed to export the content archive.
+    #[error( "Failed to export the content archive." )]
+    BackupExportFailed,
+    /// Failed to restore the content archive.
+    #[error( "Failed to restore the content archive." )]
+    BackupRestoreFailed,
 }
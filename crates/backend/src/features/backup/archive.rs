@@ -0,0 +1,189 @@
+//! Streaming of the tar archives, entry by entry, so neither the archive nor its files are ever held in memory.
+//!
+//! Only the regular files are supported, with the GNU long names for the paths that don't fit in a header.
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use chrono::Utc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf, Take};
+
+const BLOCK_SIZE: usize = 512;
+/// The name of the GNU entries holding the long name of the next entry.
+const LONG_NAME_PATH: &[u8] = b"././@LongLink";
+/// Bound of the long names read, which are held in memory.
+const MAX_LONG_NAME_SIZE: u64 = 4096;
+/// The range of the checksum field in a header.
+const CHECKSUM_RANGE: std::ops::Range<usize> = 148..156;
+
+/// Writes the entries of a tar archive to `output`, as their data is read.
+pub struct ArchiveWriter<W> {
+    output: W,
+}
+
+impl<W: AsyncWrite + Unpin> ArchiveWriter<W> {
+    pub const fn new( output: W ) -> Self { Self { output } }
+
+    /// Appends the entry at `path` with the `size` bytes of `data`, failing if `data` is shorter.
+    pub async fn append( &mut self, path: &str, size: u64, data: impl AsyncRead + Unpin ) -> io::Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size( size );
+        header.set_mode( 0o644 );
+        header.set_mtime( u64::try_from( Utc::now().timestamp() ).unwrap_or_default() );
+        header.set_entry_type( tar::EntryType::Regular );
+        if header.set_path( path ).is_err() {
+            self.append_long_name( path ).await?;
+            // Truncated like tar does, the long name taking precedence.
+            let name = &mut header.as_old_mut().name;
+            let len = name.len().min( path.len() );
+            name[.. len].copy_from_slice( &path.as_bytes()[.. len] );
+        }
+        header.set_cksum();
+        self.output.write_all( header.as_bytes() ).await?;
+
+        let copied = tokio::io::copy( &mut data.take( size ), &mut self.output ).await?;
+        if copied != size {
+            return Err( io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!( "the data of the archive entry {path} is shorter than its size" ),
+            ) );
+        }
+
+        self.write_padding( size ).await
+    }
+
+    /// Ends the archive and shuts `output` down, so its reader knows the archive is whole.
+    pub async fn finish( mut self ) -> io::Result<()> {
+        self.output.write_all( &[0; 2 * BLOCK_SIZE] ).await?;
+        self.output.shutdown().await
+    }
+
+    async fn append_long_name( &mut self, path: &str ) -> io::Result<()> {
+        let mut data = path.as_bytes().to_vec();
+        data.push( 0 );
+
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[.. LONG_NAME_PATH.len()].copy_from_slice( LONG_NAME_PATH );
+        header.set_size( data.len() as u64 );
+        header.set_mode( 0o644 );
+        header.set_entry_type( tar::EntryType::GNULongName );
+        header.set_cksum();
+
+        self.output.write_all( header.as_bytes() ).await?;
+        self.output.write_all( &data ).await?;
+        self.write_padding( data.len() as u64 ).await
+    }
+
+    async fn write_padding( &mut self, size: u64 ) -> io::Result<()> {
+        tokio::io::copy( &mut tokio::io::repeat( 0 ).take( padding( size ) ), &mut self.output ).await?;
+        Ok( () )
+    }
+}
+
+/// An entry of an archive, whose data is read from the [`ArchiveReader`].
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Reads the entries of a tar archive from `input`, in order. The data of the current entry is read from the reader
+/// itself, whatever is left of it being skipped when moving to the next entry.
+pub struct ArchiveReader<R> {
+    /// Limited to what is left of the data of the current entry.
+    input:   Take<R>,
+    /// Padding after the data of the current entry.
+    padding: u64,
+}
+
+impl<R: AsyncRead + Unpin> ArchiveReader<R> {
+    pub fn new( input: R ) -> Self {
+        Self {
+            input:   input.take( 0 ),
+            padding: 0,
+        }
+    }
+
+    /// Moves to the next entry, or returns `None` at the end of the archive. An archive without its end blocks is
+    /// truncated, so it fails.
+    pub async fn next_entry( &mut self ) -> io::Result<Option<ArchiveEntry>> {
+        self.skip( self.input.limit() + self.padding ).await?;
+        self.padding = 0;
+
+        let mut long_name = None;
+        loop {
+            let mut block = [0; BLOCK_SIZE];
+            self.input.set_limit( BLOCK_SIZE as u64 );
+            self.input.read_exact( &mut block ).await?;
+            if block.iter().all( |byte| *byte == 0 ) {
+                return Ok( None );
+            }
+
+            let header = tar::Header::from_byte_slice( &block );
+            if header.cksum()? != checksum( &block ) {
+                return Err( invalid_data( "an archive header has an invalid checksum".to_string() ) );
+            }
+
+            let size = header.entry_size()?;
+            let path = match long_name.take() {
+                Some( path ) => path,
+                None => String::from_utf8( header.path_bytes().into_owned() )
+                    .map_err( |_| invalid_data( "an archive entry path is not valid UTF-8".to_string() ) )?,
+            };
+
+            match header.entry_type() {
+                tar::EntryType::GNULongName if size <= MAX_LONG_NAME_SIZE => {
+                    let mut data = Vec::new();
+                    self.input.set_limit( size );
+                    self.input.read_to_end( &mut data ).await?;
+                    self.skip( self.input.limit() + padding( size ) ).await?;
+
+                    let len = data.iter().position( |byte| *byte == 0 ).unwrap_or( data.len() );
+                    data.truncate( len );
+                    long_name = Some(
+                        String::from_utf8( data )
+                            .map_err( |_| invalid_data( "an archive entry path is not valid UTF-8".to_string() ) )?,
+                    );
+                }
+                tar::EntryType::Regular | tar::EntryType::Continuous => {
+                    self.input.set_limit( size );
+                    self.padding = padding( size );
+                    return Ok( Some( ArchiveEntry { path, size } ) );
+                }
+                _ => return Err( invalid_data( format!( "the archive entry {path} is not a regular file" ) ) ),
+            }
+        }
+    }
+
+    async fn skip( &mut self, size: u64 ) -> io::Result<()> {
+        self.input.set_limit( size );
+        let skipped = tokio::io::copy( &mut self.input, &mut tokio::io::sink() ).await?;
+        if skipped != size {
+            return Err( io::Error::new( io::ErrorKind::UnexpectedEof, "the archive is truncated" ) );
+        }
+
+        Ok( () )
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ArchiveReader<R> {
+    fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll<io::Result<()>> {
+        Pin::new( &mut self.input ).poll_read( cx, buf )
+    }
+}
+
+/// The size of the padding of `size` bytes of data up to the next block.
+const fn padding( size: u64 ) -> u64 { ( BLOCK_SIZE as u64 - size % BLOCK_SIZE as u64 ) % BLOCK_SIZE as u64 }
+
+/// The checksum of a header, computed with its own checksum field as spaces.
+fn checksum( block: &[u8; BLOCK_SIZE] ) -> u32 {
+    block
+        .iter()
+        .enumerate()
+        .map( |( index, byte )| if CHECKSUM_RANGE.contains( &index ) { u32::from( b' ' ) } else { u32::from( *byte ) } )
+        .sum()
+}
+
+fn invalid_data( message: String ) -> io::Error { io::Error::new( io::ErrorKind::InvalidData, message ) }
@@ -0,0 +1,87 @@
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::{
+    features::backup::{
+        self,
+        archive::ArchiveWriter,
+        file_path,
+        manifest::ChecksumReader,
+        Manifest,
+        ManifestEntry,
+        Service,
+        MANIFEST_PATH,
+        PHOTOS_PATH,
+    },
+    infrastructure::drivers::storage::Storage,
+};
+
+/// An export ready to be written, its manifest having the checksums of all the entries.
+pub struct Export {
+    storage:      Storage,
+    manifest:     Manifest,
+    photos_data:  Vec<u8>,
+    storage_keys: Vec<String>,
+}
+
+impl Service {
+    /// Prepares the export of all the content, with the original files of the storage.
+    ///
+    /// The manifest comes first in the archive so it can be restored as it is read, so the files are read once here to
+    /// compute their checksums, then once more when the archive is written.
+    pub async fn export( &self ) -> Result<Export, backup::Error> {
+        let photos = self.repo.get_all_photos( &self.db ).await?;
+        let photos_data = serde_json::to_vec_pretty( &photos )?;
+        let storage_keys: Vec<_> = photos.iter().filter_map( |photo| photo.storage_key.clone() ).collect();
+
+        let mut entries = vec![ManifestEntry::new( PHOTOS_PATH, &photos_data )];
+        for storage_key in &storage_keys {
+            let mut data = ChecksumReader::new( self.storage.read( storage_key ).await? );
+            tokio::io::copy( &mut data, &mut tokio::io::sink() )
+                .await
+                .map_err( backup::Error::Archive )?;
+            entries.push( data.into_entry( &file_path( storage_key ) ) );
+        }
+
+        Ok( Export {
+            storage: self.storage.clone(),
+            manifest: Manifest::new( photos.len() as u64, storage_keys.len() as u64, entries ),
+            photos_data,
+            storage_keys,
+        } )
+    }
+}
+
+impl Export {
+    /// Writes the tar archive to `output`, streaming the files from the storage.
+    ///
+    /// A file that changed since the export was prepared fails it, as it wouldn't match its checksum.
+    pub async fn write_to( self, output: impl AsyncWrite + Unpin + Send ) -> Result<(), backup::Error> {
+        let manifest_data = serde_json::to_vec_pretty( &self.manifest )?;
+
+        let mut archive = ArchiveWriter::new( output );
+        append_entry( &mut archive, MANIFEST_PATH, manifest_data.len() as u64, manifest_data.as_slice() ).await?;
+        append_entry( &mut archive, PHOTOS_PATH, self.photos_data.len() as u64, self.photos_data.as_slice() ).await?;
+
+        // The first manifest entry is the photos data, then come the files in the order of their keys.
+        for ( storage_key, declared ) in self.storage_keys.iter().zip( &self.manifest.entries[1 ..] ) {
+            let mut data = ChecksumReader::new( self.storage.read( storage_key ).await? );
+            append_entry( &mut archive, &declared.path, declared.size, &mut data ).await?;
+
+            let entry = data.into_entry( &declared.path );
+            if entry.size != declared.size || entry.sha256 != declared.sha256 {
+                return Err( backup::Error::FileChanged( storage_key.clone() ) );
+            }
+        }
+
+        archive.finish().await.map_err( backup::Error::Archive )
+    }
+}
+
+async fn append_entry<W: AsyncWrite + Unpin>(
+    archive: &mut ArchiveWriter<W>,
+    path: &str,
+    size: u64,
+    data: impl AsyncRead + Unpin,
+) -> Result<(), backup::Error> {
+    archive.append( path, size, data ).await.map_err( backup::Error::Archive )
+}
@@ -0,0 +1,128 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, ReadBuf};
+
+use crate::features::backup;
+
+/// Version of the archive layout. Bump it whenever the layout or the data format changes.
+pub const ARCHIVE_FORMAT_VERSION: u32 = 2;
+
+/// Describes the content of an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub app_version:    String,
+    pub created_at:     DateTime<Utc>,
+    pub photos_count:   u64,
+    /// Number of original files under `files/`, missing from the archives of version 1 which had none.
+    #[serde( default )]
+    pub files_count:    u64,
+    pub entries:        Vec<ManifestEntry>,
+}
+
+/// An archive entry with its checksum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path:   String,
+    pub size:   u64,
+    pub sha256: String,
+}
+
+impl ManifestEntry {
+    pub fn new( path: &str, data: &[u8] ) -> Self {
+        Self {
+            path:   path.to_string(),
+            size:   data.len() as u64,
+            sha256: hex::encode( Sha256::digest( data ) ),
+        }
+    }
+}
+
+/// Computes the checksum of the data read through it.
+pub struct ChecksumReader<R> {
+    inner:  R,
+    hasher: Sha256,
+    size:   u64,
+}
+
+impl<R> ChecksumReader<R> {
+    pub fn new( inner: R ) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    /// The entry at `path` of the data read so far.
+    pub fn into_entry( self, path: &str ) -> ManifestEntry {
+        ManifestEntry {
+            path:   path.to_string(),
+            size:   self.size,
+            sha256: hex::encode( self.hasher.finalize() ),
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ChecksumReader<R> {
+    fn poll_read( mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let result = Pin::new( &mut self.inner ).poll_read( cx, buf );
+        if matches!( result, Poll::Ready( Ok( () ) ) ) {
+            let data = &buf.filled()[filled ..];
+            self.hasher.update( data );
+            self.size += data.len() as u64;
+        }
+
+        result
+    }
+}
+
+impl Manifest {
+    pub fn new( photos_count: u64, files_count: u64, entries: Vec<ManifestEntry> ) -> Self {
+        Self {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            app_version: env!( "CARGO_PKG_VERSION" ).to_string(),
+            created_at: Utc::now(),
+            photos_count,
+            files_count,
+            entries,
+        }
+    }
+
+    /// Checks that the archive format version is supported.
+    pub const fn verify_version( &self ) -> Result<(), backup::Error> {
+        if self.format_version > ARCHIVE_FORMAT_VERSION {
+            return Err( backup::Error::UnsupportedFormatVersion( self.format_version ) );
+        }
+
+        Ok( () )
+    }
+
+    /// Whether the manifest declares an entry at `path`.
+    pub fn declares( &self, path: &str ) -> bool { self.entries.iter().any( |entry| entry.path == path ) }
+
+    /// Checks that `entry` was read as declared in the manifest.
+    ///
+    /// An entry that isn't declared could be anything, so it is rejected rather than restored silently.
+    pub fn verify_entry( &self, entry: &ManifestEntry ) -> Result<(), backup::Error> {
+        let declared = self
+            .entries
+            .iter()
+            .find( |declared| declared.path == entry.path )
+            .ok_or_else( || backup::Error::UnexpectedEntry( entry.path.clone() ) )?;
+
+        if declared.size != entry.size || declared.sha256 != entry.sha256 {
+            return Err( backup::Error::ChecksumMismatch( entry.path.clone() ) );
+        }
+
+        Ok( () )
+    }
+}
@@ -0,0 +1,87 @@
+//! Export and restore of all the content as a portable archive.
+//!
+//! The archive is a tar file with the following layout:
+//!
+//! * `manifest.json` - The versioned [`Manifest`] with the checksum of every other entry.
+//! * `data/photos.json` - All the photos rows.
+//! * `files/<storage key>` - The original files, keyed independently of the storage backend so an archive can be
+//!   restored into any of them.
+//!
+//! Every collection has its own `data/<collection>.json` entry and count in the manifest, the photos being the only
+//! one so far. The entries come in that order, so an archive is streamed entry by entry on both ends.
+mod archive;
+mod export;
+mod manifest;
+mod restore;
+
+pub use export::Export;
+pub use manifest::{Manifest, ManifestEntry, ARCHIVE_FORMAT_VERSION};
+pub use restore::RestoreSummary;
+use thiserror::Error;
+
+use crate::infrastructure::{
+    drivers::{db, storage, storage::Storage},
+    repository,
+    repository::Repository,
+};
+
+const MANIFEST_PATH: &str = "manifest.json";
+const PHOTOS_PATH: &str = "data/photos.json";
+const FILES_DIR: &str = "files/";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    // Archive.
+    #[error( "The archive format version {0} is not supported, the latest supported is {ARCHIVE_FORMAT_VERSION}." )]
+    UnsupportedFormatVersion( u32 ),
+    #[error( "The archive is missing the entry {0}." )]
+    MissingEntry( String ),
+    #[error( "The archive has the entry {0}, which the manifest doesn't declare." )]
+    UnexpectedEntry( String ),
+    #[error( "The archive has the entry {0} out of order, the manifest and the data must come before the files." )]
+    MisplacedEntry( String ),
+    #[error( "The checksum of the archive entry {0} does not match the manifest." )]
+    ChecksumMismatch( String ),
+    #[error( "The archive has {found} {collection} but the manifest declares {expected}." )]
+    CountMismatch {
+        collection: &'static str,
+        expected:   u64,
+        found:      u64,
+    },
+    #[error( "The file {0} changed while it was exported." )]
+    FileChanged( String ),
+    #[error( "The instance is not empty, an archive can only be restored into an empty instance." )]
+    InstanceNotEmpty,
+
+    // Other.
+    #[error( "Failed to read or write the archive due to: {0}." )]
+    Archive( #[source] std::io::Error ),
+    #[error( "Failed to serialize or deserialize the archive data due to: {0}." )]
+    Serialization( #[from] serde_json::Error ),
+    #[error( "Failed to execute the restore transaction due to: {0}." )]
+    Transaction( #[source] sqlx::Error ),
+    #[error( transparent )]
+    Storage( #[from] storage::Error ),
+    #[error( transparent )]
+    Internal( #[from] repository::Error ),
+}
+
+#[derive(Debug, Clone)]
+pub struct Service {
+    db:      db::Pool,
+    storage: Storage,
+    repo:    Repository,
+}
+
+impl Service {
+    pub fn new( db: db::Pool, storage: Storage ) -> Self {
+        Self {
+            db,
+            storage,
+            repo: Repository::new(),
+        }
+    }
+}
+
+/// The path of the archive entry of a file of the storage.
+fn file_path( storage_key: &str ) -> String { [FILES_DIR, storage_key].concat() }
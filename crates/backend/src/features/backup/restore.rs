@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{
+    domain::entities::photos::Photo,
+    features::backup::{
+        self,
+        archive::ArchiveReader,
+        manifest::ChecksumReader,
+        Manifest,
+        Service,
+        FILES_DIR,
+        MANIFEST_PATH,
+        PHOTOS_PATH,
+    },
+};
+
+/// What was restored from an archive.
+#[derive(Debug, Clone)]
+pub struct RestoreSummary {
+    pub format_version: u32,
+    pub photos_count:   u64,
+    pub files_count:    u64,
+}
+
+impl Service {
+    /// Restores all the content of a tar archive into an empty instance, streaming its files into the storage.
+    ///
+    /// The manifest and the data come first in the archive and are checked before anything is written. Every file is
+    /// checked against the manifest checksums as it is written. The rows are inserted in a single transaction, which
+    /// is only committed once all the files are written and checked. If anything fails, the files written so far are
+    /// removed, so the restore can be retried.
+    pub async fn restore( &self, archive: impl AsyncRead + Unpin + Send ) -> Result<RestoreSummary, backup::Error> {
+        let mut archive = ArchiveReader::new( archive );
+
+        let manifest: Manifest = serde_json::from_slice( &read_entry( &mut archive, MANIFEST_PATH, None ).await? )?;
+        manifest.verify_version()?;
+
+        let photos: Vec<Photo> =
+            serde_json::from_slice( &read_entry( &mut archive, PHOTOS_PATH, Some( &manifest ) ).await? )?;
+        if photos.len() as u64 != manifest.photos_count {
+            return Err( backup::Error::CountMismatch {
+                collection: "photos",
+                expected:   manifest.photos_count,
+                found:      photos.len() as u64,
+            } );
+        }
+
+        if self.repo.count_photos( &self.db ).await? > 0 {
+            return Err( backup::Error::InstanceNotEmpty );
+        }
+
+        let mut tx = self.db.begin().await.map_err( backup::Error::Transaction )?;
+
+        for photo in &photos {
+            self.repo.restore_photo( &mut *tx, photo ).await?;
+        }
+        self.repo.sync_photos_id_sequence( &mut *tx ).await?;
+
+        let mut written_keys = Vec::new();
+        let result = async {
+            let mut restored_paths = HashSet::from( [PHOTOS_PATH.to_string()] );
+            while let Some( entry ) = archive.next_entry().await.map_err( backup::Error::Archive )? {
+                let storage_key = entry
+                    .path
+                    .strip_prefix( FILES_DIR )
+                    .filter( |_| manifest.declares( &entry.path ) && !restored_paths.contains( &entry.path ) )
+                    .ok_or_else( || backup::Error::UnexpectedEntry( entry.path.clone() ) )?;
+
+                // Before writing, as a failed write may leave a partial file.
+                written_keys.push( storage_key.to_string() );
+                let mut data = ChecksumReader::new( &mut archive );
+                self.storage.write( storage_key, &mut data, entry.size ).await?;
+                manifest.verify_entry( &data.into_entry( &entry.path ) )?;
+
+                restored_paths.insert( entry.path );
+            }
+
+            if let Some( entry ) = manifest.entries.iter().find( |entry| !restored_paths.contains( &entry.path ) ) {
+                return Err( backup::Error::MissingEntry( entry.path.clone() ) );
+            }
+
+            let files_count = restored_paths.len() as u64 - 1;
+            if files_count != manifest.files_count {
+                return Err( backup::Error::CountMismatch {
+                    collection: "files",
+                    expected:   manifest.files_count,
+                    found:      files_count,
+                } );
+            }
+
+            tx.commit().await.map_err( backup::Error::Transaction )
+        }
+        .await;
+
+        if let Err( err ) = result {
+            self.remove_files( &written_keys ).await;
+            return Err( err );
+        }
+
+        Ok( RestoreSummary {
+            format_version: manifest.format_version,
+            photos_count:   manifest.photos_count,
+            files_count:    manifest.files_count,
+        } )
+    }
+
+    /// Removes the files written by a failed restore. The rows are rolled back with their transaction.
+    async fn remove_files( &self, storage_keys: &[String] ) {
+        for storage_key in storage_keys {
+            if let Err( err ) = self.storage.delete( storage_key ).await {
+                tracing::error!( "Failed to remove the restored file {storage_key}: {err}" );
+            }
+        }
+    }
+}
+
+/// Reads the next entry of the archive, which must be at `path`, checked against the `manifest` if any.
+async fn read_entry<R: AsyncRead + Unpin>(
+    archive: &mut ArchiveReader<R>,
+    path: &str,
+    manifest: Option<&Manifest>,
+) -> Result<Vec<u8>, backup::Error> {
+    let entry = archive
+        .next_entry()
+        .await
+        .map_err( backup::Error::Archive )?
+        .ok_or_else( || backup::Error::MissingEntry( path.to_string() ) )?;
+    if entry.path != path {
+        return Err( backup::Error::MisplacedEntry( entry.path ) );
+    }
+
+    let mut data = Vec::new();
+    let mut reader = ChecksumReader::new( archive );
+    reader.read_to_end( &mut data ).await.map_err( backup::Error::Archive )?;
+    if let Some( manifest ) = manifest {
+        manifest.verify_entry( &reader.into_entry( path ) )?;
+    }
+
+    Ok( data )
+}
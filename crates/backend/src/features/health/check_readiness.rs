@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use common::api::health::Report;
+
+use crate::{
+    features::health::{timed_check, Service},
+    infrastructure::drivers::db,
+};
+
+impl Service {
+    /// Checks that the server can serve traffic: it is not draining, the database answers, its schema is up to date
+    /// and the storage of the files can be reached.
+    pub async fn check_readiness( &self ) -> Report {
+        let ( shutdown, database, migrations, storage ) = tokio::join!(
+            timed_check( async {
+                if self.shutdown.is_draining() {
+                    Err( "The server is draining its connections to shut down.".to_string() )
+                } else {
+                    Ok( () )
+                }
+            } ),
+            timed_check( async { db::ping( &self.db ).await.map_err( |err| err.to_string() ) } ),
+            timed_check( async {
+                match db::count_pending_migrations( &self.db ).await {
+                    Ok( 0 ) => Ok( () ),
+                    Ok( pending ) => Err( format!( "There are {pending} pending migrations." ) ),
+                    Err( err ) => Err( err.to_string() ),
+                }
+            } ),
+            timed_check( async { self.storage.ping().await.map_err( |err| err.to_string() ) } ),
+        );
+
+        Report::new( BTreeMap::from( [
+            ( "shutdown".to_string(), shutdown ),
+            ( "database".to_string(), database ),
+            ( "migrations".to_string(), migrations ),
+            ( "storage".to_string(), storage ),
+        ] ) )
+    }
+}
@@ -0,0 +1,53 @@
+//! Liveness and readiness checks of the server and its dependencies.
+mod check_readiness;
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use common::api::health::{Check, Status};
+
+use crate::{
+    infrastructure::drivers::{db, storage::Storage},
+    shutdown::Shutdown,
+};
+
+/// Maximum time a single dependency check can take before it is considered failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs( 2 );
+
+#[derive(Debug, Clone)]
+pub struct Service {
+    db:       db::Pool,
+    storage:  Storage,
+    shutdown: Shutdown,
+}
+
+impl Service {
+    pub fn new( db: db::Pool, storage: Storage, shutdown: Shutdown ) -> Self { Self { db, storage, shutdown } }
+}
+
+/// Runs a check bounded by [`CHECK_TIMEOUT`] and measures its latency.
+async fn timed_check<F>( check: F ) -> Check
+where
+    F: Future<Output = Result<(), String>>,
+{
+    let start = Instant::now();
+    let result = tokio::time::timeout( CHECK_TIMEOUT, check )
+        .await
+        .unwrap_or_else( |_| Err( format!( "Timed out after {CHECK_TIMEOUT:?}." ) ) );
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    match result {
+        Ok( () ) => Check {
+            status: Status::Pass,
+            latency_ms,
+            message: None,
+        },
+        Err( message ) => Check {
+            status: Status::Fail,
+            latency_ms,
+            message: Some( message ),
+        },
+    }
+}
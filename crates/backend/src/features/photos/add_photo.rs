@@ -12,6 +12,8 @@ impl Service {
             url:         input.url,
             title:       input.title,
             description: input.description,
+            width:       input.width,
+            height:      input.height,
         };
 
         self.repo
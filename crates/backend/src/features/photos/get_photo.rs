@@ -0,0 +1,13 @@
+use common::api::photos::get_photo::Output;
+
+use crate::features::photos::{self, Service};
+
+impl Service {
+    pub async fn get_photo( &self, id: u32 ) -> Result<Option<Output>, photos::Error> {
+        let Some( photo ) = self.repo.get_photo( &self.db, id ).await? else {
+            return Ok( None );
+        };
+
+        photos::to_output( photo ).map( Some )
+    }
+}
@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use common::api::photos::list_photos::{Cursor, Output, Query, DEFAULT_LIMIT, MAX_LIMIT};
+
+use crate::{
+    domain::entities::photos::Photo,
+    features::photos::{self, Service},
+};
+
+impl Service {
+    /// Lists a page of the photos, the newest first.
+    pub async fn list_photos( &self, query: Query ) -> Result<Output, photos::Error> {
+        let limit = query.limit.unwrap_or( DEFAULT_LIMIT ).clamp( 1, MAX_LIMIT );
+        let after = query.cursor.map( from_cursor ).transpose()?;
+
+        // One more photo than the page tells whether there is a next page.
+        let mut photos = self.repo.list_photos( &self.db, after, limit + 1 ).await?;
+        let has_next_page = photos.len() > limit as usize;
+        photos.truncate( limit as usize );
+
+        let next_cursor = photos.last().filter( |_| has_next_page ).map( to_cursor ).transpose()?;
+        let photos = photos.into_iter().map( photos::to_output ).collect::<Result<Vec<_>, _>>()?;
+
+        Ok( Output { photos, next_cursor } )
+    }
+}
+
+/// The cursor of the pages after the `photo`.
+fn to_cursor( photo: &Photo ) -> Result<Cursor, photos::Error> {
+    let id = u32::try_from( photo.id ).map_err( |err| photos::Error::Internal( err.into() ) )?;
+
+    Ok( Cursor::new( photo.created_at.timestamp_micros(), id ) )
+}
+
+/// The creation time and the id of the photo the pages of the `cursor` are after.
+fn from_cursor( cursor: Cursor ) -> Result<( DateTime<Utc>, i32 ), photos::Error> {
+    let created_at =
+        DateTime::from_timestamp_micros( cursor.created_at_micros() ).ok_or( photos::Error::InvalidCursor )?;
+    let id = i32::try_from( cursor.id() ).map_err( |_| photos::Error::InvalidCursor )?;
+
+    Ok( ( created_at, id ) )
+}
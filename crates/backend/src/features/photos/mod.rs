@@ -1,12 +1,20 @@
 mod add_photo;
+mod get_photo;
+mod list_photos;
 
+use common::api::photos::get_photo::Output;
 use thiserror::Error;
 
-use crate::infrastructure::{drivers::db, repository, repository::Repository};
+use crate::{
+    domain::entities::photos::Photo,
+    infrastructure::{drivers::db, repository, repository::Repository},
+};
 
 #[derive(Error, Debug)]
 pub enum Error {
     // Photos.
+    #[error( "The cursor is out of range." )]
+    InvalidCursor,
 
     // Other.
     #[error( transparent )]
@@ -27,3 +35,18 @@ impl Service {
         }
     }
 }
+
+/// The API output of a stored photo.
+fn to_output( photo: Photo ) -> Result<Output, Error> {
+    let to_u32 = |value: i32| u32::try_from( value ).map_err( |err| Error::Internal( err.into() ) );
+
+    Ok( Output {
+        id:          to_u32( photo.id )?,
+        created_at:  photo.created_at.timestamp(),
+        url:         photo.url,
+        title:       photo.title,
+        description: photo.description,
+        width:       photo.width.map( to_u32 ).transpose()?,
+        height:      photo.height.map( to_u32 ).transpose()?,
+    } )
+}
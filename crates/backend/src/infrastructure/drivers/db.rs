@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use sqlx::{Executor, Postgres};
+use sqlx::{
+    migrate::{Migrate, MigrateError, Migrator},
+    Executor, Postgres,
+};
 use thiserror::Error;
 
 pub type Pool = sqlx::Pool<Postgres>;
@@ -8,8 +11,8 @@ type PoolOptions = sqlx::pool::PoolOptions<Postgres>;
 
 pub trait Queryer<'a>: Executor<'a, Database = Postgres> {}
 impl<'a> Queryer<'a> for &Pool {}
+impl<'a> Queryer<'a> for &'a mut sqlx::PgConnection {}
 
-#[allow( unused )]
 pub type Tx = sqlx::Transaction<'static, Postgres>;
 
 #[derive(Error, Debug)]
@@ -19,11 +22,11 @@ pub struct ConnectionError( #[from] sqlx::Error );
 pub async fn connect(
     connection: &str,
     max_connections: u32,
-    max_lifetime_minutes: u32,
+    max_lifetime: Duration,
 ) -> Result<Pool, ConnectionError> {
     let pool = PoolOptions::new()
         .max_connections( max_connections )
-        .max_lifetime( Duration::from_secs( u64::from( max_lifetime_minutes ) * 60 ) )
+        .max_lifetime( max_lifetime )
         .connect( connection )
         .await?;
 
@@ -34,7 +37,33 @@ pub async fn connect(
 #[error( "Sqlx migration failed due to: {0}" )]
 pub struct MigrationError( #[from] sqlx::migrate::MigrateError );
 
+static MIGRATOR: Migrator = sqlx::migrate!( "./migrations/" );
+
 pub async fn migrate( db: &Pool ) -> Result<(), MigrationError> {
-    sqlx::migrate!( "./migrations/" ).run( db ).await?;
+    MIGRATOR.run( db ).await?;
+    Ok( () )
+}
+
+/// Counts the migrations embedded in the binary that were not applied to the database yet.
+pub async fn count_pending_migrations( db: &Pool ) -> Result<usize, MigrationError> {
+    let mut connection = db.acquire().await.map_err( MigrateError::Execute )?;
+    let applied_migrations = connection.list_applied_migrations().await?;
+
+    let pending_migrations = MIGRATOR
+        .iter()
+        .filter( |migration| !migration.migration_type.is_down_migration() )
+        .filter( |migration| !applied_migrations.iter().any( |applied| applied.version == migration.version ) )
+        .count();
+
+    Ok( pending_migrations )
+}
+
+#[derive(Error, Debug)]
+#[error( "Sqlx ping failed due to: {0}" )]
+pub struct PingError( #[from] sqlx::Error );
+
+/// Runs the cheapest possible query to check that the database answers.
+pub async fn ping( db: &Pool ) -> Result<(), PingError> {
+    sqlx::query( "SELECT 1;" ).execute( db ).await?;
     Ok( () )
 }
@@ -0,0 +1,146 @@
+mod s3;
+
+use std::path::{Component, Path, PathBuf};
+
+use server_common::tls;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+use crate::settings;
+
+/// Storage of the original files, addressed by keys that don't depend on the storage backend.
+#[derive(Debug, Clone)]
+pub enum Storage {
+    /// A local directory, with every file at the relative path of its key.
+    Local { root: PathBuf },
+    /// A bucket of an S3-compatible object storage, with every file at the object key of its key.
+    S3( Box<s3::Bucket> ),
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error( "The storage key {0} is invalid, it must be a relative path without `..` components." )]
+    InvalidKey( String ),
+    #[error( "Failed to access the storage key {0} due to: {1}." )]
+    AccessFailed( String, #[source] std::io::Error ),
+    #[error( "The storage is unavailable due to: {0}." )]
+    Unavailable( #[source] std::io::Error ),
+    #[error( "Failed to load the CA certificates of the storage due to: {0}." )]
+    TlsLoadFailed( #[source] tls::Error ),
+}
+
+impl Storage {
+    /// Creates the storage of the settings, the S3 one if it is set.
+    pub fn new( storage_settings: &settings::StorageConfigs ) -> Result<Self, Error> {
+        match &storage_settings.s3 {
+            Some( s3_settings ) => {
+                let bucket = s3::Bucket::new( s3_settings ).map_err( Error::TlsLoadFailed )?;
+                Ok( Self::S3( Box::new( bucket ) ) )
+            }
+            None => Ok( Self::Local {
+                root: storage_settings.dir.as_ref().to_path_buf(),
+            } ),
+        }
+    }
+
+    /// Opens the file of the `key` to be read as a stream.
+    pub async fn read( &self, key: &str ) -> Result<Box<dyn AsyncRead + Send + Unpin>, Error> {
+        match self {
+            Self::Local { root } => {
+                let path = local_path( root, key )?;
+                let file = tokio::fs::File::open( path )
+                    .await
+                    .map_err( |err| Error::AccessFailed( key.to_string(), err ) )?;
+
+                Ok( Box::new( file ) )
+            }
+            Self::S3( bucket ) => {
+                validate_key( key )?;
+                let object = bucket.get( key ).await.map_err( |err| Error::AccessFailed( key.to_string(), err ) )?;
+
+                Ok( Box::new( object ) )
+            }
+        }
+    }
+
+    /// Writes the file of the `key` from the `size` bytes streamed from `data`, replacing it if it exists.
+    pub async fn write( &self, key: &str, mut data: impl AsyncRead + Send + Unpin, size: u64 ) -> Result<(), Error> {
+        match self {
+            Self::Local { root } => {
+                let path = local_path( root, key )?;
+                let access_failed = |err| Error::AccessFailed( key.to_string(), err );
+                if let Some( parent ) = path.parent() {
+                    tokio::fs::create_dir_all( parent ).await.map_err( access_failed )?;
+                }
+
+                let mut file = tokio::fs::File::create( path ).await.map_err( access_failed )?;
+                tokio::io::copy( &mut ( &mut data ).take( size ), &mut file )
+                    .await
+                    .map_err( access_failed )?;
+                file.flush().await.map_err( access_failed )
+            }
+            Self::S3( bucket ) => {
+                validate_key( key )?;
+                bucket.put( key, data, size ).await.map_err( |err| Error::AccessFailed( key.to_string(), err ) )
+            }
+        }
+    }
+
+    /// Removes the file of the `key`, if it exists.
+    pub async fn delete( &self, key: &str ) -> Result<(), Error> {
+        match self {
+            Self::Local { root } => {
+                let path = local_path( root, key )?;
+                match tokio::fs::remove_file( path ).await {
+                    Err( err ) if err.kind() != std::io::ErrorKind::NotFound => {
+                        Err( Error::AccessFailed( key.to_string(), err ) )
+                    }
+                    _ => Ok( () ),
+                }
+            }
+            Self::S3( bucket ) => {
+                validate_key( key )?;
+                bucket.delete( key ).await.map_err( |err| Error::AccessFailed( key.to_string(), err ) )
+            }
+        }
+    }
+
+    /// Checks that the storage can be reached and written, without reading any file.
+    pub async fn ping( &self ) -> Result<(), Error> {
+        match self {
+            Self::Local { root } => {
+                let metadata = tokio::fs::metadata( root ).await.map_err( Error::Unavailable )?;
+                if !metadata.is_dir() || metadata.permissions().readonly() {
+                    return Err( Error::Unavailable( std::io::Error::new(
+                        std::io::ErrorKind::PermissionDenied,
+                        format!( "{} is not a writable directory", root.display() ),
+                    ) ) );
+                }
+
+                Ok( () )
+            }
+            Self::S3( bucket ) => bucket.head().await.map_err( Error::Unavailable ),
+        }
+    }
+}
+
+/// The path of the `key` under `root`, which can't escape it as the archives come from outside.
+fn local_path( root: &Path, key: &str ) -> Result<PathBuf, Error> {
+    validate_key( key )?;
+
+    Ok( root.join( key ) )
+}
+
+/// Checks that the `key` is a relative path without `..` components, so it is stored at the same place whatever the
+/// storage.
+fn validate_key( key: &str ) -> Result<(), Error> {
+    let is_valid = !key.is_empty()
+        && Path::new( key )
+            .components()
+            .all( |component| matches!( component, Component::Normal( _ ) ) );
+    if !is_valid {
+        return Err( Error::InvalidKey( key.to_string() ) );
+    }
+
+    Ok( () )
+}
@@ -0,0 +1,308 @@
+//! Client of an S3-compatible object storage, e.g. AWS S3 or `MinIO`.
+//!
+//! The objects are addressed after the bucket in the path of the endpoint ( path-style ), which every service
+//! supports. The requests are signed with AWS Signature Version 4, leaving the payload unsigned as the files are
+//! streamed. An `https` endpoint is trusted with the CA certificates of the settings.
+
+use std::{
+    error::Error as StdError,
+    fmt::Write,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    http::{header, uri::Scheme, Method, Request, Response, StatusCode, Uri},
+};
+use chrono::Utc;
+use futures::{future::BoxFuture, TryStreamExt};
+use hmac::{Hmac, Mac};
+use hyper::client::{
+    connect::{Connected, Connection},
+    HttpConnector,
+};
+use server_common::tls;
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{client::TlsStream, rustls::ServerName, TlsConnector};
+use tokio_util::io::StreamReader;
+
+use crate::settings::{S3Subconfig, Secret};
+
+const SERVICE: &str = "s3";
+const SIGNING_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+const X_AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
+const X_AMZ_DATE: &str = "x-amz-date";
+
+/// Maximum time to connect to the endpoint.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs( 10 );
+/// Size of the chunks of the files sent in the request bodies.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A bucket of the object storage, with a pool of kept alive connections to its endpoint.
+#[derive(Debug, Clone)]
+pub struct Bucket {
+    client:            hyper::Client<S3Connector, Body>,
+    /// The scheme and the authority of the endpoint, e.g. `https://s3.eu-west-1.amazonaws.com`.
+    origin:            String,
+    /// The authority of the endpoint, signed as the `Host` header.
+    host:              String,
+    /// The path of the bucket, with the path of the endpoint.
+    path:              String,
+    region:            String,
+    access_key_id:     String,
+    secret_access_key: Secret<String>,
+}
+
+impl Bucket {
+    /// Creates the client of the bucket, trusting the CA certificates of the settings if any.
+    ///
+    /// # Errors
+    ///
+    /// If the CA certificates can't be read.
+    pub fn new( s3_settings: &S3Subconfig ) -> Result<Self, tls::Error> {
+        let endpoint = s3_settings.endpoint.as_ref();
+        let host_name = endpoint.host_str().unwrap_or_default();
+        let host = endpoint
+            .port()
+            .map_or_else( || host_name.to_string(), |port| format!( "{host_name}:{port}" ) );
+
+        let mut http = HttpConnector::new();
+        http.enforce_http( false );
+        http.set_connect_timeout( Some( CONNECT_TIMEOUT ) );
+        let tls = s3_settings
+            .ca_path
+            .as_ref()
+            .map( |ca_path| tls::create_connector( ca_path.as_ref() ) )
+            .transpose()?;
+
+        Ok( Self {
+            client: hyper::Client::builder().build( S3Connector { http, tls } ),
+            origin: format!( "{}://{host}", endpoint.scheme() ),
+            host,
+            path: format!( "{}/{}", endpoint.path().trim_end_matches( '/' ), encode_path( &s3_settings.bucket ) ),
+            region: s3_settings.region.clone(),
+            access_key_id: s3_settings.access_key_id.clone(),
+            secret_access_key: s3_settings.secret_access_key.clone(),
+        } )
+    }
+
+    /// Gets the object of the `key`, whose content is streamed as it is read.
+    pub async fn get( &self, key: &str ) -> io::Result<impl AsyncRead + Send + Unpin> {
+        let response = self.send( Method::GET, Some( key ), Body::empty(), None ).await?;
+
+        Ok( StreamReader::new( response.into_body().map_err( other_error ) ) )
+    }
+
+    /// Puts the object of the `key` from the `size` bytes streamed from `data`, replacing it if it exists.
+    pub async fn put( &self, key: &str, data: impl AsyncRead + Send + Unpin, size: u64 ) -> io::Result<()> {
+        let ( mut sender, body ) = Body::channel();
+
+        // The body is sent as the request goes, the data being borrowed rather than moved into a task.
+        let send_data = async move {
+            let mut data = data.take( size );
+            loop {
+                let mut chunk = Vec::with_capacity( CHUNK_SIZE );
+                if data.read_buf( &mut chunk ).await? == 0 {
+                    return io::Result::Ok( () );
+                }
+
+                sender.send_data( chunk.into() ).await.map_err( other_error )?;
+            }
+        };
+
+        let ( response, sent ) = tokio::join!( self.send( Method::PUT, Some( key ), body, Some( size ) ), send_data );
+        sent?;
+        response.map( drop )
+    }
+
+    /// Deletes the object of the `key`, which succeeds if it doesn't exist.
+    pub async fn delete( &self, key: &str ) -> io::Result<()> {
+        self.send( Method::DELETE, Some( key ), Body::empty(), None ).await.map( drop )
+    }
+
+    /// Checks that the bucket exists and can be accessed.
+    pub async fn head( &self ) -> io::Result<()> {
+        self.send( Method::HEAD, None, Body::empty(), None ).await.map( drop )
+    }
+
+    /// Sends a signed request for the object of the `key`, or for the bucket itself. A response without a success
+    /// status is an error, whose kind is the closest to its status.
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        body: Body,
+        content_length: Option<u64>,
+    ) -> io::Result<Response<Body>> {
+        let path = key.map_or_else( || self.path.clone(), |key| format!( "{}/{}", self.path, encode_path( key ) ) );
+        let amz_date = Utc::now().format( "%Y%m%dT%H%M%SZ" ).to_string();
+        let authorization = self.authorization( &method, &path, &amz_date );
+
+        let mut request = Request::builder()
+            .method( method )
+            .uri( format!( "{}{path}", self.origin ) )
+            .header( header::HOST, &self.host )
+            .header( X_AMZ_CONTENT_SHA256, UNSIGNED_PAYLOAD )
+            .header( X_AMZ_DATE, amz_date )
+            .header( header::AUTHORIZATION, authorization );
+        if let Some( content_length ) = content_length {
+            request = request.header( header::CONTENT_LENGTH, content_length );
+        }
+
+        let request = request.body( body ).map_err( other_error )?;
+        let response = self.client.request( request ).await.map_err( other_error )?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let kind = match status {
+                StatusCode::NOT_FOUND => io::ErrorKind::NotFound,
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => io::ErrorKind::PermissionDenied,
+                _ => io::ErrorKind::Other,
+            };
+            return Err( io::Error::new( kind, format!( "the S3 storage answered {status}" ) ) );
+        }
+
+        Ok( response )
+    }
+
+    /// The `Authorization` header of a request at `path` sent at `amz_date`, see
+    /// <https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html>.
+    fn authorization( &self, method: &Method, path: &str, amz_date: &str ) -> String {
+        let date = &amz_date[.. 8];
+        let scope = format!( "{date}/{}/{SERVICE}/aws4_request", self.region );
+
+        let canonical_headers =
+            format!( "host:{}\n{X_AMZ_CONTENT_SHA256}:{UNSIGNED_PAYLOAD}\n{X_AMZ_DATE}:{amz_date}\n", self.host );
+        let canonical_request =
+            format!( "{method}\n{path}\n\n{canonical_headers}\n{SIGNED_HEADERS}\n{UNSIGNED_PAYLOAD}" );
+        let string_to_sign = format!(
+            "{SIGNING_ALGORITHM}\n{amz_date}\n{scope}\n{}",
+            hex::encode( Sha256::digest( canonical_request ) )
+        );
+
+        let secret = format!( "AWS4{}", self.secret_access_key.expose_secret() );
+        let signing_key = [self.region.as_str(), SERVICE, "aws4_request"]
+            .into_iter()
+            .fold( hmac_sha256( secret.as_bytes(), date ), |key, part| hmac_sha256( &key, part ) );
+        let signature = hex::encode( hmac_sha256( &signing_key, &string_to_sign ) );
+
+        format!(
+            "{SIGNING_ALGORITHM} Credential={}/{scope}, SignedHeaders={SIGNED_HEADERS}, Signature={signature}",
+            self.access_key_id
+        )
+    }
+}
+
+fn hmac_sha256( key: &[u8], data: &str ) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice( key ).expect( "HMAC takes keys of any size" );
+    mac.update( data.as_bytes() );
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes a path as signed, every byte but the unreserved characters and the `/` separators.
+fn encode_path( path: &str ) -> String {
+    let mut encoded = String::with_capacity( path.len() );
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!( byte, b'-' | b'.' | b'_' | b'~' | b'/' ) {
+            encoded.push( char::from( byte ) );
+        } else {
+            let _ = write!( encoded, "%{byte:02X}" );
+        }
+    }
+
+    encoded
+}
+
+fn other_error( err: impl Into<Box<dyn StdError + Send + Sync>> ) -> io::Error {
+    io::Error::other( err )
+}
+
+/// Connects to the endpoint over TCP, with TLS for an `https` one.
+#[derive(Clone)]
+pub struct S3Connector {
+    http: HttpConnector,
+    tls:  Option<TlsConnector>,
+}
+
+impl tower::Service<Uri> for S3Connector {
+    type Error = Box<dyn StdError + Send + Sync>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = S3Stream;
+
+    fn poll_ready( &mut self, cx: &mut Context<'_> ) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready( cx ).map_err( Into::into )
+    }
+
+    fn call( &mut self, uri: Uri ) -> Self::Future {
+        let connecting = self.http.call( uri.clone() );
+        let tls = self.tls.clone();
+
+        Box::pin( async move {
+            let stream = connecting.await?;
+            if uri.scheme() != Some( &Scheme::HTTPS ) {
+                return Ok( S3Stream::Tcp( stream ) );
+            }
+
+            // The settings require the CA certificates of an `https` endpoint.
+            let tls = tls.ok_or( "no CA certificates to trust the S3 endpoint" )?;
+            let server_name = ServerName::try_from( uri.host().unwrap_or_default() )?;
+            Ok( S3Stream::Tls( Box::new( tls.connect( server_name, stream ).await? ) ) )
+        } )
+    }
+}
+
+/// A connection to the endpoint, see [`S3Connector`].
+pub enum S3Stream {
+    Tcp( TcpStream ),
+    Tls( Box<TlsStream<TcpStream>> ),
+}
+
+impl Connection for S3Stream {
+    fn connected( &self ) -> Connected {
+        match self {
+            Self::Tcp( stream ) => stream.connected(),
+            Self::Tls( stream ) => stream.get_ref().0.connected(),
+        }
+    }
+}
+
+impl AsyncRead for S3Stream {
+    fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_read( cx, buf ),
+            Self::Tls( stream ) => Pin::new( stream ).poll_read( cx, buf ),
+        }
+    }
+}
+
+impl AsyncWrite for S3Stream {
+    fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_write( cx, buf ),
+            Self::Tls( stream ) => Pin::new( stream ).poll_write( cx, buf ),
+        }
+    }
+
+    fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_flush( cx ),
+            Self::Tls( stream ) => Pin::new( stream ).poll_flush( cx ),
+        }
+    }
+
+    fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_shutdown( cx ),
+            Self::Tls( stream ) => Pin::new( stream ).poll_shutdown( cx ),
+        }
+    }
+}
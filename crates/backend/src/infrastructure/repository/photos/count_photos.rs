@@ -0,0 +1,23 @@
+use tracing::instrument;
+
+use crate::infrastructure::{
+    drivers::db,
+    repository::{self, Repository},
+};
+
+impl Repository {
+    #[instrument( name = "db.photos.count_photos", skip_all, err )]
+    pub async fn count_photos<'a, T: db::Queryer<'a>>( &self, db: T ) -> Result<u64, repository::Error> {
+        let query = r#"
+            SELECT COUNT(*)
+            FROM photos;
+        "#;
+
+        let count: i64 = sqlx::query_scalar( query )
+            .fetch_one( db )
+            .await
+            .map_err( |err| repository::Error::QueryFailed( "photos.count_photos", err ) )?;
+
+        u64::try_from( count ).map_err( repository::Error::IntConversionFailed )
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use tracing::instrument;
 
 use crate::infrastructure::{
     drivers::db,
@@ -11,6 +12,8 @@ pub struct Input {
     pub url:         String,
     pub title:       String,
     pub description: Option<String>,
+    pub width:       Option<u32>,
+    pub height:      Option<u32>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -19,10 +22,11 @@ struct Output {
 }
 
 impl Repository {
+    #[instrument( name = "db.photos.create_photo", skip_all, err )]
     pub async fn create_photo<'a, T: db::Queryer<'a>>( &self, db: T, input: Input ) -> Result<u32, repository::Error> {
         let query = r#"
-            INSERT INTO photos ( created_at, url, title, description )
-            VALUES ( $1, $2, $3, $4 )
+            INSERT INTO photos ( created_at, url, title, description, width, height )
+            VALUES ( $1, $2, $3, $4, $5, $6 )
             RETURNING id;
         "#;
 
@@ -31,6 +35,8 @@ impl Repository {
             .bind( &input.url )
             .bind( &input.title )
             .bind( &input.description )
+            .bind( input.width.map( i32::try_from ).transpose()? )
+            .bind( input.height.map( i32::try_from ).transpose()? )
             .fetch_one( db )
             .await
             .map_err( |err| repository::Error::QueryFailed( "photos.create_photo", err ) )?;
@@ -0,0 +1,25 @@
+use tracing::instrument;
+
+use crate::{
+    domain::entities::photos::Photo,
+    infrastructure::{
+        drivers::db,
+        repository::{self, Repository},
+    },
+};
+
+impl Repository {
+    #[instrument( name = "db.photos.get_all_photos", skip_all, err )]
+    pub async fn get_all_photos<'a, T: db::Queryer<'a>>( &self, db: T ) -> Result<Vec<Photo>, repository::Error> {
+        let query = r#"
+            SELECT id, created_at, uploaded_at, url, title, description, width, height, storage_key
+            FROM photos
+            ORDER BY id ASC;
+        "#;
+
+        sqlx::query_as::<_, Photo>( query )
+            .fetch_all( db )
+            .await
+            .map_err( |err| repository::Error::QueryFailed( "photos.get_all_photos", err ) )
+    }
+}
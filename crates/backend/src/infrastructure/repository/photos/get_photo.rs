@@ -0,0 +1,30 @@
+use tracing::instrument;
+
+use crate::{
+    domain::entities::photos::Photo,
+    infrastructure::{
+        drivers::db,
+        repository::{self, Repository},
+    },
+};
+
+impl Repository {
+    #[instrument( name = "db.photos.get_photo", skip_all, err )]
+    pub async fn get_photo<'a, T: db::Queryer<'a>>(
+        &self,
+        db: T,
+        id: u32,
+    ) -> Result<Option<Photo>, repository::Error> {
+        let query = r#"
+            SELECT id, created_at, uploaded_at, url, title, description, width, height
+            FROM photos
+            WHERE id = $1;
+        "#;
+
+        sqlx::query_as::<_, Photo>( query )
+            .bind( i32::try_from( id )? )
+            .fetch_optional( db )
+            .await
+            .map_err( |err| repository::Error::QueryFailed( "photos.get_photo", err ) )
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+
+use crate::{
+    domain::entities::photos::Photo,
+    infrastructure::{
+        drivers::db,
+        repository::{self, Repository},
+    },
+};
+
+impl Repository {
+    /// Lists a page of the photos, the newest first, after the photo of the `after` creation time and id.
+    ///
+    /// The page is found from the position of that photo rather than an offset, so it stays consistent while photos
+    /// are added, and while that photo is removed.
+    #[instrument( name = "db.photos.list_photos", skip_all, err )]
+    pub async fn list_photos<'a, T: db::Queryer<'a>>(
+        &self,
+        db: T,
+        after: Option<( DateTime<Utc>, i32 )>,
+        limit: u32,
+    ) -> Result<Vec<Photo>, repository::Error> {
+        let query = r#"
+            SELECT id, created_at, uploaded_at, url, title, description, width, height
+            FROM photos
+            WHERE $1::TIMESTAMPTZ IS NULL OR ( created_at, id ) < ( $1, $2 )
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3;
+        "#;
+
+        sqlx::query_as::<_, Photo>( query )
+            .bind( after.map( |( created_at, _ )| created_at ) )
+            .bind( after.map( |( _, id )| id ) )
+            .bind( i64::from( limit ) )
+            .fetch_all( db )
+            .await
+            .map_err( |err| repository::Error::QueryFailed( "photos.list_photos", err ) )
+    }
+}
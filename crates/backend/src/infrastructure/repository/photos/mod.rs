@@ -1 +1,6 @@
+pub mod count_photos;
 pub mod create_photo;
+pub mod get_all_photos;
+pub mod get_photo;
+pub mod list_photos;
+pub mod restore_photo;
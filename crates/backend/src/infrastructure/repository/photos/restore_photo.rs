@@ -0,0 +1,52 @@
+use tracing::instrument;
+
+use crate::{
+    domain::entities::photos::Photo,
+    infrastructure::{
+        drivers::db,
+        repository::{self, Repository},
+    },
+};
+
+impl Repository {
+    /// Inserts a photo keeping all of its original columns, including the id and the upload date.
+    #[instrument( name = "db.photos.restore_photo", skip_all, err )]
+    pub async fn restore_photo<'a, T: db::Queryer<'a>>( &self, db: T, photo: &Photo ) -> Result<(), repository::Error> {
+        let query = r#"
+            INSERT INTO photos ( id, created_at, uploaded_at, url, title, description, width, height, storage_key )
+            VALUES ( $1, $2, $3, $4, $5, $6, $7, $8, $9 );
+        "#;
+
+        sqlx::query( query )
+            .bind( photo.id )
+            .bind( photo.created_at )
+            .bind( photo.uploaded_at )
+            .bind( &photo.url )
+            .bind( &photo.title )
+            .bind( &photo.description )
+            .bind( photo.width )
+            .bind( photo.height )
+            .bind( &photo.storage_key )
+            .execute( db )
+            .await
+            .map_err( |err| repository::Error::QueryFailed( "photos.restore_photo", err ) )?;
+
+        Ok( () )
+    }
+
+    /// Moves the photos id sequence past the highest restored id, so new photos do not collide with restored ones.
+    #[instrument( name = "db.photos.sync_photos_id_sequence", skip_all, err )]
+    pub async fn sync_photos_id_sequence<'a, T: db::Queryer<'a>>( &self, db: T ) -> Result<(), repository::Error> {
+        let query = r#"
+            SELECT setval( pg_get_serial_sequence( 'photos', 'id' ), COALESCE( MAX( id ), 1 ) )
+            FROM photos;
+        "#;
+
+        sqlx::query( query )
+            .execute( db )
+            .await
+            .map_err( |err| repository::Error::QueryFailed( "photos.sync_photos_id_sequence", err ) )?;
+
+        Ok( () )
+    }
+}
@@ -7,7 +7,10 @@
 // Expose app Router creation to facilitate e2e tests.
 pub use error::Error;
 use error_stack::{Report, ResultExt};
+pub use features::backup::RestoreSummary;
 pub use presentation::app;
+use server_common::{cors, listener, tls};
+pub use server_common::shutdown;
 use thiserror::Error;
 use tracing::instrument;
 mod domain;
@@ -23,7 +26,7 @@ mod utils;
 pub enum InitServerError {
     /// Failed to bind on the provided address.
     #[error( "Failed to bind on the address: {0}" )]
-    AddressBindFailed( std::net::SocketAddrV4 ),
+    AddressBindFailed( ::settings::validators::ListenAddr ),
     /// Failed to connect to the database.
     #[error( "Failed to connect to the database." )]
     DatabaseConnectionFailed,
@@ -36,28 +39,53 @@ pub enum InitServerError {
     /// Failed to serve the server.
     #[error( "Failed to serve the server." )]
     ServerServeFailed,
+    /// Failed to create the storage of the files.
+    #[error( "Failed to create the storage of the files." )]
+    StorageInitFailed,
+    /// Failed to load the TLS certificate.
+    #[error( "Failed to load the TLS certificate." )]
+    TlsLoadFailed,
 }
 
-#[tokio::main]
-#[instrument( name = "APP", err, skip( server_settings, database_settings ) )]
+#[derive(Error, Debug)]
+pub enum BackupError {
+    /// Failed to connect to the database.
+    #[error( "Failed to connect to the database." )]
+    DatabaseConnectionFailed,
+    /// Failed to create the archive.
+    #[error( "Failed to create the archive." )]
+    ExportFailed,
+    /// Failed to restore the archive.
+    #[error( "Failed to restore the archive." )]
+    RestoreFailed,
+    /// Failed to read or write the archive file.
+    #[error( "Failed to read or write the archive file: {0}" )]
+    ArchiveFileAccessFailed( std::path::PathBuf ),
+    /// Failed to create the storage of the files.
+    #[error( "Failed to create the storage of the files." )]
+    StorageInitFailed,
+}
+
+#[instrument( name = "APP", err, skip( server_settings, database_settings, storage_settings, configs_receiver ) )]
 pub async fn init_server(
     server_settings: settings::ServerConfigs,
     database_settings: settings::DatabaseConfigs,
+    storage_settings: settings::StorageConfigs,
+    configs_receiver: settings::ConfigsReceiver,
 ) -> Result<(), Report<InitServerError>> {
     tracing::info!(
-        "Initializing server the with settings: [ sock_addr_v4={}, frontend_url={}, database_pool_size={}, \
-         database_max_lifetime_minutes={} ].",
-        server_settings.sock_addr_v4,
-        server_settings.frontend_url.as_str(),
+        "Initializing server the with settings: [ listen_addrs={}, database_pool_size={}, \
+         database_max_lifetime={} ].",
+        server_settings.listen_addrs,
         database_settings.pool_size,
-        database_settings.max_lifetime_minutes,
+        database_settings.max_lifetime,
     );
 
     // Database connection.
     let db = infrastructure::drivers::db::connect(
-        &database_settings.url,
+        database_settings.url.expose_secret(),
         database_settings.pool_size,
-        database_settings.max_lifetime_minutes,
+        database_settings.max_lifetime.as_std(),
     )
     .await
     .change_context( InitServerError::DatabaseConnectionFailed )?;
@@ -71,34 +99,242 @@ pub async fn init_server(
         tracing::info!( "Database migrations executed successfully." );
     }
 
+    // Stop accepting connections on SIGTERM/SIGINT after the pre-stop delay, and drain the in-flight ones until the
+    // drain timeout.
+    let shutdown = shutdown::Shutdown::listen( server_settings.pre_stop_delay.as_std() );
+    let drain_timeout = server_settings.drain_timeout.as_std();
+
+    // Apply the reloaded log level.
+    tokio::spawn( logger::reload_log_level( configs_receiver.clone() ) );
+
     // Create app router.
-    let mut app = presentation::app::create( db );
+    let mut app = presentation::app::create( db.clone(), &storage_settings, &server_settings, shutdown.clone() )?;
+
+    // Cors, allowing the origins of the latest settings.
+    app = app.layer( cors::create_layer( configs_receiver, |configs| &configs.cors ) );
+
+    // Servers, binding all the listen addresses before serving any so a wrong one fails the start.
+    let mut listeners = Vec::new();
+    for listen_addr in &server_settings.listen_addrs {
+        let listener = listener::Listener::bind( listen_addr )
+            .change_context_lazy( || InitServerError::AddressBindFailed( listen_addr.clone() ) )?;
+        listeners.push( listener );
+
+        tracing::info!( "Server bound to {listen_addr} successfully." );
+    }
+
+    // The certificate is reloaded as long as the TLS acceptor is kept.
+    let _tls = server_settings
+        .tls
+        .as_ref()
+        .map( |tls_settings| bind_https( tls_settings, &shutdown, &mut listeners ) )
+        .transpose()?;
+
+    // Dedicated prometheus metrics listener.
+    let metrics_settings = &server_settings.metrics;
+    if let Some( metrics_sock_addr ) = metrics_settings.sock_addr.filter( |_| metrics_settings.is_exposed ) {
+        let metrics_server = axum::Server::try_bind( &metrics_sock_addr )
+            .change_context( InitServerError::AddressBindFailed( metrics_sock_addr.into() ) )?;
+        let metrics_shutdown = shutdown.clone();
+
+        tokio::spawn( async move {
+            let metrics_app = monitoring::prometheus::create_metrics_route::<()>();
+
+            if let Err( err ) = metrics_server
+                .serve( metrics_app.into_make_service() )
+                .with_graceful_shutdown( metrics_shutdown.stop_accepting() )
+                .await
+            {
+                tracing::error!( "Metrics server failed: {err}" );
+            }
+        } );
 
-    // Cors.
-    if cfg!( debug_assertions ) {
-        app = app.layer( tower_http::cors::CorsLayer::permissive() );
+        tracing::info!( "Metrics server bound to http://{metrics_sock_addr} successfully." );
+    }
+
+    let is_h2c_enabled = server_settings.is_h2c_enabled;
+    let servers =
+        listeners.into_iter().map( |listener| listener.serve( app.clone(), shutdown.clone(), is_h2c_enabled ) );
+    let server = futures::future::try_join_all( servers );
+
+    let is_drained = tokio::select! {
+        result = server => {
+            result.change_context( InitServerError::ServerServeFailed )?;
+            true
+        }
+        () = shutdown.drain_deadline( drain_timeout ) => false,
+    };
+
+    db.close().await;
+    if is_drained {
+        tracing::info!( "Server shut down gracefully." );
     } else {
-        app = app.layer(
-            tower_http::cors::CorsLayer::new().allow_origin(
-                server_settings
-                    .frontend_url
-                    .as_str()
-                    .parse::<axum::http::HeaderValue>()
-                    .unwrap(),
-            ),
+        tracing::warn!(
+            "Server shut down after the drain timeout of {drain_timeout:?}, dropping the remaining connections."
         );
     }
 
-    // Server.
-    let server = axum::Server::try_bind( &server_settings.sock_addr_v4.into() )
-        .change_context( InitServerError::AddressBindFailed( server_settings.sock_addr_v4 ) )?;
+    Ok( () )
+}
+
+/// Binds the HTTPS listeners, and the plain HTTP listener redirecting to them if any.
+fn bind_https(
+    tls_settings: &settings::TlsSubconfig,
+    shutdown: &shutdown::Shutdown,
+    listeners: &mut Vec<listener::Listener>,
+) -> Result<tls::Tls, Report<InitServerError>> {
+    let tls = tls::Tls::new( tls_settings ).change_context( InitServerError::TlsLoadFailed )?;
+
+    // The port the first HTTPS listener was bound to, the system choosing it for a port 0.
+    let mut https_port = None;
+    for sock_addr in &tls_settings.sock_addrs {
+        let listener = listener::Listener::bind_tls( sock_addr, tls.acceptor() )
+            .change_context_lazy( || InitServerError::AddressBindFailed( ( *sock_addr ).into() ) )?;
+        https_port = https_port.or_else( || listener.local_addr().map( |local_addr| local_addr.port() ) );
+        listeners.push( listener );
+
+        tracing::info!( "Server bound to https://{sock_addr} successfully." );
+    }
+
+    if let ( Some( redirect_sock_addr ), Some( https_port ) ) = ( tls_settings.redirect_sock_addr, https_port ) {
+        let redirect_server = axum::Server::try_bind( &redirect_sock_addr )
+            .change_context( InitServerError::AddressBindFailed( redirect_sock_addr.into() ) )?;
+        let redirect_app = tls::create_redirect_route( https_port );
+        let redirect_shutdown = shutdown.clone();
+
+        tokio::spawn( async move {
+            if let Err( err ) = redirect_server
+                .serve( redirect_app.into_make_service() )
+                .with_graceful_shutdown( redirect_shutdown.stop_accepting() )
+                .await
+            {
+                tracing::error!( "HTTPS redirect server failed: {err}" );
+            }
+        } );
 
-    tracing::info!( "Server bound to http://{} successfully.", server_settings.sock_addr_v4 );
+        tracing::info!( "HTTPS redirect server bound to http://{redirect_sock_addr} successfully." );
+    }
+
+    Ok( tls )
+}
 
-    server
-        .serve( app.into_make_service() )
+/// Export all the content into a portable archive file.
+///
+/// # Arguments
+///
+/// * `database_settings` - The database settings [`DatabaseConfigs`](settings::DatabaseConfigs).
+/// * `storage_settings` - The storage of the original files [`StorageConfigs`](settings::StorageConfigs).
+/// * `output` - The path of the archive file to write.
+///
+/// # Errors
+///
+/// If the database can't be reached, the content can't be exported or the archive file can't be written.
+#[instrument( name = "EXPORT", err, skip( database_settings, storage_settings ) )]
+pub async fn export_backup(
+    database_settings: settings::DatabaseConfigs,
+    storage_settings: settings::StorageConfigs,
+    output: std::path::PathBuf,
+) -> Result<(), Report<BackupError>> {
+    let db = infrastructure::drivers::db::connect(
+        database_settings.url.expose_secret(),
+        database_settings.pool_size,
+        database_settings.max_lifetime.as_std(),
+    )
+    .await
+    .change_context( BackupError::DatabaseConnectionFailed )?;
+
+    let storage = infrastructure::drivers::storage::Storage::new( &storage_settings )
+        .change_context( BackupError::StorageInitFailed )?;
+    let export = features::backup::Service::new( db, storage )
+        .export()
+        .await
+        .change_context( BackupError::ExportFailed )?;
+
+    let file = tokio::fs::File::create( &output )
         .await
-        .change_context( InitServerError::ServerServeFailed )?;
+        .change_context_lazy( || BackupError::ArchiveFileAccessFailed( output.clone() ) )?;
+    if let Err( err ) = export.write_to( tokio::io::BufWriter::new( file ) ).await {
+        // A partial archive would only fail its restore.
+        if let Err( err ) = tokio::fs::remove_file( &output ).await {
+            tracing::error!( "Failed to remove the partial archive {}: {err}", output.display() );
+        }
+
+        return Err( Report::new( err ).change_context( BackupError::ExportFailed ) );
+    }
+
+    tracing::info!( "Exported archive to {} successfully.", output.display() );
 
     Ok( () )
 }
+
+/// Restore all the content from a portable archive file into an empty instance.
+///
+/// # Arguments
+///
+/// * `database_settings` - The database settings [`DatabaseConfigs`](settings::DatabaseConfigs).
+/// * `storage_settings` - The storage to write the original files into [`StorageConfigs`](settings::StorageConfigs).
+/// * `input` - The path of the archive file to read.
+///
+/// # Errors
+///
+/// If the archive file can't be read, the database can't be reached or the content can't be restored.
+#[instrument( name = "RESTORE", err, skip( database_settings, storage_settings ) )]
+pub async fn restore_backup(
+    database_settings: settings::DatabaseConfigs,
+    storage_settings: settings::StorageConfigs,
+    input: std::path::PathBuf,
+) -> Result<(), Report<BackupError>> {
+    let archive = tokio::fs::File::open( &input )
+        .await
+        .change_context_lazy( || BackupError::ArchiveFileAccessFailed( input.clone() ) )?;
+
+    let db = infrastructure::drivers::db::connect(
+        database_settings.url.expose_secret(),
+        database_settings.pool_size,
+        database_settings.max_lifetime.as_std(),
+    )
+    .await
+    .change_context( BackupError::DatabaseConnectionFailed )?;
+
+    // The archive is restored into the current schema, so it must exist before restoring.
+    infrastructure::drivers::db::migrate( &db )
+        .await
+        .change_context( BackupError::RestoreFailed )?;
+
+    let summary = restore_archive( db, &storage_settings, tokio::io::BufReader::new( archive ) ).await?;
+
+    tracing::info!(
+        "Restored archive {} (format version {}) successfully with {} photos and {} files.",
+        input.display(),
+        summary.format_version,
+        summary.photos_count,
+        summary.files_count
+    );
+
+    Ok( () )
+}
+
+/// Restore all the content of a portable archive into the empty instance of `db`.
+///
+/// # Arguments
+///
+/// * `db` - The database, with the current schema.
+/// * `storage_settings` - The storage to write the original files into [`StorageConfigs`](settings::StorageConfigs).
+/// * `archive` - The archive, read as it is restored.
+///
+/// # Errors
+///
+/// If the archive is invalid, the instance is not empty or the content can't be written.
+pub async fn restore_archive(
+    db: sqlx::PgPool,
+    storage_settings: &settings::StorageConfigs,
+    archive: impl tokio::io::AsyncRead + Unpin + Send,
+) -> Result<features::backup::RestoreSummary, Report<BackupError>> {
+    let storage = infrastructure::drivers::storage::Storage::new( storage_settings )
+        .change_context( BackupError::StorageInitFailed )?;
+
+    features::backup::Service::new( db, storage )
+        .restore( archive )
+        .await
+        .change_context( BackupError::RestoreFailed )
+}
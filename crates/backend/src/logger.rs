@@ -2,7 +2,7 @@
 //! The logger is based on the `tracing` crate.
 
 pub use logger::middleware_http_tracing;
-use monitoring::logger;
+use monitoring::{log_filter, logger, rolling_file::RollingOptions};
 
 use crate::settings;
 
@@ -30,7 +30,7 @@ pub fn init(
 
     // Emit logs to stdout if the setting is enabled.
     if logger_settings.is_stdout_emitted {
-        tracing_layers.push( logger::EnableLayer::Stdout );
+        tracing_layers.push( logger::EnableLayer::Stdout( logger_settings.stdout_format ) );
     }
 
     // Enable tokio-console logs.
@@ -46,10 +46,53 @@ pub fn init(
             app_name,
             directory: files_emitted_config.dir.as_ref(),
             prefix: &files_emitted_config.files_prefix,
+            rolling: RollingOptions {
+                rotation:      files_emitted_config.rotation,
+                max_file_size: files_emitted_config.max_file_size.as_u64(),
+                max_files:     files_emitted_config.max_files,
+                max_age:       files_emitted_config.max_age.map( ::settings::validators::Duration::as_std ),
+                is_compressed: files_emitted_config.is_compressed,
+            },
         } );
     }
 
+    let traces_exported_config = &logger_settings.traces_exported;
+
+    // Export traces to Jaeger if the setting is enabled.
+    if traces_exported_config.is_exported {
+        let jaeger_export_options = logger::JaegerExportOptions {
+            service_name:   &traces_exported_config.service_name,
+            agent_endpoint: &traces_exported_config.agent_endpoint,
+            sampling_ratio: traces_exported_config.sampling_ratio.as_f64(),
+        };
+
+        match logger::init_jaeger_tracer( &jaeger_export_options ) {
+            Ok( tracer ) => tracing_layers.push( logger::EnableLayer::Jaeger( tracer ) ),
+            Err( err ) => eprintln!( "Failed to create the Jaeger tracer, traces will not be exported: {err}" ),
+        }
+    }
+
     tracing_layers.push( logger::EnableLayer::SpanTraces );
 
     logger::init( &logger_settings.log_level, &tracing_layers )
 }
+
+/// Applies the log level of the reloaded settings to the log filter, until the settings stop being watched.
+pub async fn reload_log_level( mut configs: settings::ConfigsReceiver ) {
+    let mut log_level = configs.borrow_and_update().logger.log_level;
+
+    while configs.changed().await.is_ok() {
+        let reloaded_log_level = configs.borrow_and_update().logger.log_level;
+        if reloaded_log_level == log_level {
+            continue;
+        }
+
+        log_level = reloaded_log_level;
+        if let Err( err ) = log_filter::set_initial( &log_level.as_directive() ) {
+            tracing::error!( "Failed to apply the reloaded log level: {err}" );
+        }
+    }
+}
+
+/// Flushes the traces not exported yet. Must be called before the program exits.
+pub fn shutdown() { logger::shutdown_tracer(); }
@@ -1,24 +1,58 @@
 use std::sync::Arc;
 
 use axum::{Extension, Router};
-use tower_http::compression::CompressionLayer;
-
-use crate::{features, infrastructure, logger, presentation::routes};
-
-pub fn create( db: infrastructure::drivers::db::Pool ) -> Router {
+use error_stack::{Report, ResultExt};
+use monitoring::prometheus;
+use server_common::{admin::AdminToken, compression};
+
+use crate::{
+    features, infrastructure, logger,
+    presentation::routes,
+    settings,
+    shutdown::Shutdown,
+    InitServerError,
+};
+
+/// Creates the router of the app, with its services.
+///
+/// # Errors
+///
+/// If the storage of the files can't be created.
+pub fn create(
+    db: infrastructure::drivers::db::Pool,
+    storage_settings: &settings::StorageConfigs,
+    server_settings: &settings::ServerConfigs,
+    shutdown: Shutdown,
+) -> Result<Router, Report<InitServerError>> {
     // Main router.
-    let mut app = Router::new().nest( "/api/v1", routes::api::create_route() );
+    let mut app = Router::new()
+        .nest( "/api/v1", routes::api::create_route() )
+        .nest( "/health", routes::health::create_route() );
 
     // Services.
-    let photos_service = Arc::new( features::photos::Service::new( db ) );
-
-    app = app.layer( Extension( photos_service ) );
+    let storage = infrastructure::drivers::storage::Storage::new( storage_settings )
+        .change_context( InitServerError::StorageInitFailed )?;
+    let photos_service = Arc::new( features::photos::Service::new( db.clone() ) );
+    let backup_service = Arc::new( features::backup::Service::new( db.clone(), storage.clone() ) );
+    let health_service = Arc::new( features::health::Service::new( db, storage, shutdown ) );
+
+    app = app
+        .layer( Extension( photos_service ) )
+        .layer( Extension( backup_service ) )
+        .layer( Extension( health_service ) )
+        .layer( Extension( AdminToken( server_settings.admin_token.clone() ) ) );
+
+    // Prometheus metrics, on the main router unless a dedicated listener is configured.
+    if server_settings.metrics.is_exposed && server_settings.metrics.sock_addr.is_none() {
+        app = app.merge( prometheus::create_metrics_route() );
+    }
+    app = prometheus::middleware_http_metrics( app );
 
     // Http tracing logs middleware layer.
     app = logger::middleware_http_tracing( app );
 
-    // Compression.
-    app = app.layer( CompressionLayer::new().br( true ).no_gzip().no_deflate() );
+    // Compression, negotiated with each request.
+    app = compression::middleware_compression( app, &server_settings.compression );
 
-    app
+    Ok( app )
 }
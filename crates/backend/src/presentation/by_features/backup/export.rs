@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::{body::Body, http::header, response::IntoResponse, Extension};
+use chrono::Utc;
+use hyper::body::{Bytes, Sender};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::Error;
+use crate::features;
+
+/// Size of the chunks of the archive sent in the response body.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[axum::debug_handler]
+pub async fn export_backup(
+    Extension( backup_service ): Extension<Arc<features::backup::Service>>,
+) -> Result<impl IntoResponse, Error> {
+    let export = backup_service.export().await?;
+
+    // The archive is streamed as it is written, so it is never held in memory.
+    let ( sender, body ) = Body::channel();
+    tokio::spawn( stream_archive( export, sender ) );
+
+    let file_name = format!( "photo-story-backup-{}.tar", Utc::now().format( "%Y%m%dT%H%M%SZ" ) );
+    let headers = [
+        ( header::CONTENT_TYPE, "application/x-tar".to_string() ),
+        ( header::CONTENT_DISPOSITION, format!( "attachment; filename=\"{file_name}\"" ) ),
+    ];
+
+    Ok( ( headers, axum::body::boxed( body ) ) )
+}
+
+/// Writes the archive into the response body. The response has started, so a failure aborts the body for the client
+/// not to take a truncated archive for a whole one.
+async fn stream_archive( export: features::backup::Export, mut sender: Sender ) {
+    let ( writer, reader ) = tokio::io::duplex( CHUNK_SIZE );
+
+    let ( write_result, send_result ) = tokio::join!( export.write_to( writer ), send_chunks( reader, &mut sender ) );
+    if let Err( err ) = write_result {
+        tracing::error!( "Failed to export the archive due to: {err}" );
+        sender.abort();
+    } else if let Err( err ) = send_result {
+        tracing::error!( "Failed to send the archive due to: {err}" );
+    }
+}
+
+async fn send_chunks(
+    mut reader: impl AsyncRead + Unpin,
+    sender: &mut Sender,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    loop {
+        let mut chunk = Vec::with_capacity( CHUNK_SIZE );
+        if reader.read_buf( &mut chunk ).await? == 0 {
+            return Ok( () );
+        }
+
+        sender.send_data( Bytes::from( chunk ) ).await?;
+    }
+}
@@ -0,0 +1,32 @@
+use axum::{
+    http,
+    response::{IntoResponse, Response},
+    Json,
+};
+use thiserror::Error;
+
+use crate::features;
+
+pub mod export;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error( "Internal error due to: {0}" )]
+    Internal( #[from] features::backup::Error ),
+}
+
+impl IntoResponse for Error {
+    fn into_response( self ) -> Response {
+        tracing::error!( "{self}" );
+
+        let status = match self {
+            Self::Internal( _ ) => http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let response_body = common::api::ErrorResponseBody {
+            message: status.canonical_reason().unwrap_or_default().to_string(),
+        };
+
+        ( status, Json( response_body ) ).into_response()
+    }
+}
@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use axum::{response::IntoResponse, Extension};
+use server_common::health::{record_readiness, report_response};
+
+use crate::features;
+
+#[axum::debug_handler]
+pub async fn check_readiness(
+    Extension( health_service ): Extension<Arc<features::health::Service>>,
+) -> impl IntoResponse {
+    let report = health_service.check_readiness().await;
+    record_readiness( &report );
+
+    report_response( report )
+}
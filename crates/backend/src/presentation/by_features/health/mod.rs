@@ -0,0 +1,2 @@
+pub mod check_liveness;
+pub mod check_readiness;
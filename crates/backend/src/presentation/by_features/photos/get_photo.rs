@@ -0,0 +1,18 @@
+use std::sync::Arc;
+
+use axum::{extract::Path, http::header, response::IntoResponse, Extension};
+
+use super::Error;
+use crate::features;
+
+/// Gets a photo, encoded with rkyv.
+#[axum::debug_handler]
+pub async fn get_photo(
+    Path( id ): Path<u32>,
+    Extension( photos_service ): Extension<Arc<features::photos::Service>>,
+) -> Result<impl IntoResponse, Error> {
+    let photo = photos_service.get_photo( id ).await?.ok_or( Error::NotFound )?;
+    let body = rkyv::to_bytes::<_, 1024>( &photo ).map_err( |err| Error::Internal( err.to_string() ) )?;
+
+    Ok( ( [( header::CONTENT_TYPE, "application/octet-stream" )], body.into_vec() ) )
+}
@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use axum::{extract::Query, http::header, response::IntoResponse, Extension};
+use common::api::photos::list_photos;
+
+use super::Error;
+use crate::features;
+
+/// Lists a page of the photos, the newest first, encoded with rkyv.
+#[axum::debug_handler]
+pub async fn list_photos(
+    Query( query ): Query<list_photos::Query>,
+    Extension( photos_service ): Extension<Arc<features::photos::Service>>,
+) -> Result<impl IntoResponse, Error> {
+    let page = photos_service.list_photos( query ).await?;
+    let body = rkyv::to_bytes::<_, 4096>( &page ).map_err( |err| Error::Internal( err.to_string() ) )?;
+
+    Ok( ( [( header::CONTENT_TYPE, "application/octet-stream" )], body.into_vec() ) )
+}
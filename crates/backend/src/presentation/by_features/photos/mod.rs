@@ -1,15 +1,24 @@
 use axum::{
-    body::{Body, Bytes, Full},
     http,
-    http::header,
     response::{IntoResponse, Response},
+    Json,
 };
 use thiserror::Error;
 
+use crate::features;
+
 pub mod add_photo;
+pub mod get_photo;
+pub mod list_photos;
 
 #[derive(Error, Debug)]
 pub enum Error {
+    #[error( "Photo not found." )]
+    NotFound,
+
+    #[error( "Invalid cursor." )]
+    InvalidCursor,
+
     #[error( "Unknown internal error." )]
     InternalUnknown,
 
@@ -17,17 +26,31 @@ pub enum Error {
     Internal( String ),
 }
 
+impl From<features::photos::Error> for Error {
+    fn from( err: features::photos::Error ) -> Self {
+        match err {
+            features::photos::Error::InvalidCursor => Self::InvalidCursor,
+            features::photos::Error::Internal( _ ) => Self::Internal( err.to_string() ),
+        }
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response( self ) -> Response {
-        unimplemented!();
-        //let ( status, error_message ) = match self {
-        //    Error::InternalUnknown => ( http::StatusCode::INTERNAL_SERVER_ERROR, self.to_string() ),
-        //    Error::Internal( _ ) => ( http::StatusCode::INTERNAL_SERVER_ERROR, self.to_string() ),
-        //};
+        let status = match self {
+            Self::NotFound => http::StatusCode::NOT_FOUND,
+            Self::InvalidCursor => http::StatusCode::BAD_REQUEST,
+            Self::InternalUnknown | Self::Internal( _ ) => http::StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        if status.is_server_error() {
+            tracing::error!( "{self}" );
+        }
 
-        //let response_body: Vec<u8> = serialize( &common::api::ErrorResponseBody { message: error_message } )
-        //    .expect( "Failed to deserialize error struct into bytes. This should never fail." );
+        let response_body = common::api::ErrorResponseBody {
+            message: status.canonical_reason().unwrap_or_default().to_string(),
+        };
 
-        //( status, response_body ).into_response()
+        ( status, Json( response_body ) ).into_response()
     }
 }
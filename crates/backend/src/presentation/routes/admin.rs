@@ -0,0 +1,12 @@
+use axum::{middleware, routing::get, Router};
+use monitoring::log_filter;
+use server_common::admin;
+
+use crate::presentation::by_features::backup::export;
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route( "/backup", get( export::export_backup ) )
+        .merge( log_filter::create_log_filter_route() )
+        .route_layer( middleware::from_fn( admin::require_admin_token ) )
+}
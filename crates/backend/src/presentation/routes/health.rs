@@ -0,0 +1,9 @@
+use axum::{routing::get, Router};
+
+use crate::presentation::by_features::health::{check_liveness, check_readiness};
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route( "/live", get( check_liveness::check_liveness ) )
+        .route( "/ready", get( check_readiness::check_readiness ) )
+}
@@ -1,9 +1,15 @@
+mod admin;
+pub mod health;
 mod photos;
 
 pub mod api {
     use axum::Router;
 
-    use super::photos;
+    use super::{admin, photos};
 
-    pub fn create_route() -> Router { Router::new().nest( "/photos", photos::create_route() ) }
+    pub fn create_route() -> Router {
+        Router::new()
+            .nest( "/photos", photos::create_route() )
+            .nest( "/admin", admin::create_route() )
+    }
 }
@@ -1,5 +1,9 @@
 use axum::{routing::get, Router};
 
-use crate::presentation::by_features::photos::add_photo;
+use crate::presentation::by_features::photos::{add_photo, get_photo, list_photos};
 
-pub fn create_route() -> Router { Router::new().route( "/", get( add_photo::add_photo ) ) }
+pub fn create_route() -> Router {
+    Router::new()
+        .route( "/", get( list_photos::list_photos ).post( add_photo::add_photo ) )
+        .route( "/:id", get( get_photo::get_photo ) )
+}
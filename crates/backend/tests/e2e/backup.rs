@@ -0,0 +1,363 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http,
+    http::{header, HeaderMap, Method, Request, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use backend::{app, settings, shutdown::Shutdown};
+use sqlx::PgPool;
+use tower::util::ServiceExt;
+
+use super::create_storage_settings;
+
+fn storage_path( storage_settings: &settings::StorageConfigs, key: &str ) -> PathBuf {
+    storage_settings.dir.as_ref().join( key )
+}
+
+/// Inserts a photo with its original file in the storage, at `photos/<name>.jpg`.
+async fn insert_stored_photo( db: &PgPool, storage_settings: &settings::StorageConfigs, title: &str ) {
+    let name = title.to_lowercase();
+    let storage_key = format!( "photos/{name}.jpg" );
+    let path = storage_path( storage_settings, &storage_key );
+    fs::create_dir_all( path.parent().unwrap() ).unwrap();
+    fs::write( path, format!( "{name} original" ) ).unwrap();
+
+    let query = r#"
+        INSERT INTO photos ( created_at, url, title, storage_key )
+        VALUES ( TO_TIMESTAMP( 1700000000 ), $1, $2, $3 );
+    "#;
+    sqlx::query( query )
+        .bind( format!( "https://photos.example.com/{name}.jpg" ) )
+        .bind( title )
+        .bind( storage_key )
+        .execute( db )
+        .await
+        .unwrap();
+}
+
+/// Objects of the stub S3 storage, by their path.
+type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+/// Serves a stub of an S3-compatible storage on a random local port, with a `photo-story` bucket.
+fn spawn_stub_s3( objects: Objects ) -> SocketAddr {
+    let stub = Router::new().fallback( stub_s3_request ).with_state( objects );
+
+    let server = axum::Server::bind( &"127.0.0.1:0".parse().unwrap() ).serve( stub.into_make_service() );
+    let addr = server.local_addr();
+    tokio::spawn( server );
+
+    addr
+}
+
+/// Answers the requests signed with the test credentials, the files being sent with their size.
+async fn stub_s3_request(
+    State( objects ): State<Objects>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let authorization = headers.get( header::AUTHORIZATION ).and_then( |value| value.to_str().ok() );
+    let is_signed = authorization.is_some_and( |authorization| {
+        authorization.starts_with( "AWS4-HMAC-SHA256 Credential=test-access-key/" )
+            && authorization.contains( "/us-east-1/s3/aws4_request, SignedHeaders=" )
+    } );
+    if !is_signed || !headers.contains_key( "x-amz-date" ) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    let path = uri.path().to_string();
+    let mut objects = objects.lock().unwrap();
+    match method {
+        Method::HEAD if path == "/photo-story" => StatusCode::OK.into_response(),
+        Method::GET => match objects.get( &path ) {
+            Some( data ) => data.clone().into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        },
+        Method::PUT if headers.contains_key( header::CONTENT_LENGTH ) => {
+            objects.insert( path, body.to_vec() );
+            StatusCode::OK.into_response()
+        }
+        Method::DELETE => {
+            objects.remove( &path );
+            StatusCode::NO_CONTENT.into_response()
+        }
+        _ => StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
+fn create_s3_storage_settings( addr: SocketAddr ) -> settings::StorageConfigs {
+    settings::StorageConfigs {
+        // Unused along the S3 storage.
+        dir: ::settings::validators::DirectoryPath::new_unchecked( "./storage" ),
+        s3:  Some( settings::S3Subconfig {
+            endpoint:          format!( "http://{addr}" ).parse().unwrap(),
+            bucket:            "photo-story".to_string(),
+            region:            "us-east-1".to_string(),
+            access_key_id:     "test-access-key".to_string(),
+            secret_access_key: settings::Secret::new( "test-secret-key".to_string() ),
+            ca_path:           None,
+        } ),
+    }
+}
+
+async fn export_archive( db: PgPool, storage_settings: &settings::StorageConfigs ) -> Vec<u8> {
+    let app = app::create( db, storage_settings, &server_settings_with_admin_token(), Shutdown::default() ).unwrap();
+
+    let request = Request::builder()
+        .uri( "/api/v1/admin/backup" )
+        .header( http::header::AUTHORIZATION, "Bearer test-admin-token" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::OK );
+
+    hyper::body::to_bytes( response.into_body() ).await.unwrap().to_vec()
+}
+
+fn server_settings_with_admin_token() -> settings::ServerConfigs {
+    settings::ServerConfigs {
+        admin_token: Some( settings::Secret::new( "test-admin-token".to_string() ) ),
+        ..settings::ServerConfigs::default()
+    }
+}
+
+#[sqlx::test]
+fn backup_export_is_disabled_without_admin_token( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder()
+        .uri( "/api/v1/admin/backup" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::NOT_FOUND );
+}
+
+#[sqlx::test]
+fn backup_export_rejects_invalid_token( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &server_settings_with_admin_token(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder()
+        .uri( "/api/v1/admin/backup" )
+        .header( http::header::AUTHORIZATION, "Bearer wrong-token" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::UNAUTHORIZED );
+}
+
+#[sqlx::test]
+fn backup_export_returns_archive_with_manifest( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &server_settings_with_admin_token(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder()
+        .uri( "/api/v1/admin/backup" )
+        .header( http::header::AUTHORIZATION, "Bearer test-admin-token" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    assert_eq!( response.headers()[http::header::CONTENT_TYPE], "application/x-tar" );
+
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let mut archive = tar::Archive::new( response_body.as_ref() );
+
+    let mut manifest = String::new();
+    archive
+        .entries()
+        .unwrap()
+        .map( Result::unwrap )
+        .find( |entry| entry.path().unwrap().to_str() == Some( "manifest.json" ) )
+        .expect( "The archive has no manifest." )
+        .read_to_string( &mut manifest )
+        .unwrap();
+
+    let manifest: serde_json::Value = serde_json::from_str( &manifest ).unwrap();
+    assert_eq!( manifest["format_version"], 2 );
+    assert_eq!( manifest["photos_count"], 0 );
+    assert_eq!( manifest["files_count"], 0 );
+}
+
+#[sqlx::test]
+fn backup_restore_round_trips_the_rows_and_the_files( db: PgPool ) {
+    let export_storage_settings = create_storage_settings( "round-trip-export" );
+    insert_stored_photo( &db, &export_storage_settings, "Lisbon" ).await;
+    let archive = export_archive( db.clone(), &export_storage_settings ).await;
+
+    // Restores into an empty instance with another storage.
+    sqlx::query( "DELETE FROM photos;" ).execute( &db ).await.unwrap();
+    let restore_storage_settings = create_storage_settings( "round-trip-restore" );
+
+    let summary = backend::restore_archive( db.clone(), &restore_storage_settings, archive.as_slice() ).await.unwrap();
+
+    assert_eq!( summary.photos_count, 1 );
+    assert_eq!( summary.files_count, 1 );
+    let ( title, storage_key ): ( String, String ) = sqlx::query_as( "SELECT title, storage_key FROM photos;" )
+        .fetch_one( &db )
+        .await
+        .unwrap();
+    assert_eq!( title, "Lisbon" );
+    assert_eq!( storage_key, "photos/lisbon.jpg" );
+    assert_eq!(
+        fs::read( storage_path( &restore_storage_settings, "photos/lisbon.jpg" ) ).unwrap(),
+        b"lisbon original"
+    );
+}
+
+#[sqlx::test]
+fn backup_restore_round_trips_the_storage_keys_longer_than_a_tar_header( db: PgPool ) {
+    let export_storage_settings = create_storage_settings( "long-key-export" );
+    let title = "Lisbon ".repeat( 20 );
+    insert_stored_photo( &db, &export_storage_settings, &title ).await;
+    let archive = export_archive( db.clone(), &export_storage_settings ).await;
+    sqlx::query( "DELETE FROM photos;" ).execute( &db ).await.unwrap();
+    let restore_storage_settings = create_storage_settings( "long-key-restore" );
+
+    backend::restore_archive( db.clone(), &restore_storage_settings, archive.as_slice() ).await.unwrap();
+
+    let name = title.to_lowercase();
+    assert_eq!(
+        fs::read( storage_path( &restore_storage_settings, &format!( "photos/{name}.jpg" ) ) ).unwrap(),
+        format!( "{name} original" ).as_bytes()
+    );
+}
+
+#[sqlx::test]
+fn backup_restore_rejects_truncated_archives( db: PgPool ) {
+    let export_storage_settings = create_storage_settings( "truncated-export" );
+    insert_stored_photo( &db, &export_storage_settings, "Lisbon" ).await;
+    let mut archive = export_archive( db.clone(), &export_storage_settings ).await;
+    sqlx::query( "DELETE FROM photos;" ).execute( &db ).await.unwrap();
+    let restore_storage_settings = create_storage_settings( "truncated-restore" );
+
+    // Every entry is whole, only the end of the archive is missing.
+    archive.truncate( archive.len() - 1024 );
+    let result = backend::restore_archive( db.clone(), &restore_storage_settings, archive.as_slice() ).await;
+
+    assert!( result.is_err() );
+    assert!( !storage_path( &restore_storage_settings, "photos/lisbon.jpg" ).exists() );
+    let photos_count: i64 = sqlx::query_scalar( "SELECT COUNT(*) FROM photos;" ).fetch_one( &db ).await.unwrap();
+    assert_eq!( photos_count, 0 );
+}
+
+#[sqlx::test]
+fn backup_restore_moves_the_files_between_the_local_and_the_s3_storages( db: PgPool ) {
+    let local_storage_settings = create_storage_settings( "s3-export" );
+    insert_stored_photo( &db, &local_storage_settings, "Lisbon" ).await;
+    let archive = export_archive( db.clone(), &local_storage_settings ).await;
+    sqlx::query( "DELETE FROM photos;" ).execute( &db ).await.unwrap();
+
+    let objects = Objects::default();
+    let s3_storage_settings = create_s3_storage_settings( spawn_stub_s3( objects.clone() ) );
+    backend::restore_archive( db.clone(), &s3_storage_settings, archive.as_slice() ).await.unwrap();
+
+    assert_eq!( objects.lock().unwrap()["/photo-story/photos/lisbon.jpg"], b"lisbon original" );
+
+    // And back from the S3 storage.
+    let archive = export_archive( db.clone(), &s3_storage_settings ).await;
+    sqlx::query( "DELETE FROM photos;" ).execute( &db ).await.unwrap();
+    let restore_storage_settings = create_storage_settings( "s3-restore" );
+    backend::restore_archive( db.clone(), &restore_storage_settings, archive.as_slice() ).await.unwrap();
+
+    assert_eq!(
+        fs::read( storage_path( &restore_storage_settings, "photos/lisbon.jpg" ) ).unwrap(),
+        b"lisbon original"
+    );
+}
+
+#[sqlx::test]
+fn backup_restore_failing_partway_leaves_nothing_behind( db: PgPool ) {
+    let export_storage_settings = create_storage_settings( "partway-export" );
+    insert_stored_photo( &db, &export_storage_settings, "Lisbon" ).await;
+    insert_stored_photo( &db, &export_storage_settings, "Porto" ).await;
+    let archive = export_archive( db.clone(), &export_storage_settings ).await;
+    sqlx::query( "DELETE FROM photos;" ).execute( &db ).await.unwrap();
+
+    // The file of the second photo can't be written, as a directory is in its way.
+    let restore_storage_settings = create_storage_settings( "partway-restore" );
+    let blocking_dir = storage_path( &restore_storage_settings, "photos/porto.jpg" );
+    fs::create_dir_all( &blocking_dir ).unwrap();
+
+    let result = backend::restore_archive( db.clone(), &restore_storage_settings, archive.as_slice() ).await;
+
+    assert!( result.is_err() );
+    assert!( !storage_path( &restore_storage_settings, "photos/lisbon.jpg" ).exists() );
+    let photos_count: i64 = sqlx::query_scalar( "SELECT COUNT(*) FROM photos;" ).fetch_one( &db ).await.unwrap();
+    assert_eq!( photos_count, 0 );
+
+    // Nothing is left behind to refuse a retry.
+    fs::remove_dir( &blocking_dir ).unwrap();
+    let summary = backend::restore_archive( db.clone(), &restore_storage_settings, archive.as_slice() ).await.unwrap();
+
+    assert_eq!( summary.photos_count, 2 );
+    assert_eq!(
+        fs::read( storage_path( &restore_storage_settings, "photos/lisbon.jpg" ) ).unwrap(),
+        b"lisbon original"
+    );
+}
+
+#[sqlx::test]
+fn backup_restore_rejects_entries_missing_from_the_manifest( db: PgPool ) {
+    let storage_settings = create_storage_settings( "undeclared-entry" );
+    let archive = export_archive( db.clone(), &storage_settings ).await;
+
+    // Copies the archive with an extra entry.
+    let mut tampered_archive = tar::Builder::new( Vec::new() );
+    for entry in tar::Archive::new( archive.as_slice() ).entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let header = entry.header().clone();
+        let mut data = Vec::new();
+        entry.read_to_end( &mut data ).unwrap();
+        tampered_archive.append( &header, data.as_slice() ).unwrap();
+    }
+    let mut header = tar::Header::new_gnu();
+    header.set_size( 7 );
+    header.set_mode( 0o644 );
+    tampered_archive.append_data( &mut header, "files/extra.jpg", b"unknown".as_slice() ).unwrap();
+    let tampered_archive = tampered_archive.into_inner().unwrap();
+
+    let result = backend::restore_archive( db.clone(), &storage_settings, tampered_archive.as_slice() ).await;
+
+    let report = format!( "{:?}", result.unwrap_err() );
+    assert!( report.contains( "files/extra.jpg" ), "{report}" );
+    assert!( !storage_path( &storage_settings, "extra.jpg" ).exists() );
+    let photos_count: i64 = sqlx::query_scalar( "SELECT COUNT(*) FROM photos;" ).fetch_one( &db ).await.unwrap();
+    assert_eq!( photos_count, 0 );
+}
@@ -0,0 +1,53 @@
+use axum::{
+    body::Body,
+    http::{header, Request},
+};
+use backend::{app, settings, shutdown::Shutdown};
+use sqlx::PgPool;
+use tower::util::ServiceExt;
+
+fn create_server_settings( min_bytes: u64 ) -> settings::ServerConfigs {
+    settings::ServerConfigs {
+        compression: settings::CompressionSubconfig {
+            min_bytes: ::settings::validators::ByteSize::new( min_bytes ),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[sqlx::test]
+fn responses_are_compressed_with_the_negotiated_algorithm( db: PgPool ) {
+    let app = app::create( db, &settings::StorageConfigs::default(), &create_server_settings( 0 ), Shutdown::default() )
+        .unwrap();
+
+    let request = Request::builder()
+        .uri( "/health/live" )
+        .header( header::ACCEPT_ENCODING, "gzip, zstd;q=0.5" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.headers()[header::CONTENT_ENCODING], "gzip" );
+    assert_eq!( response.headers()[header::VARY], "accept-encoding" );
+}
+
+#[sqlx::test]
+fn responses_below_the_minimum_size_are_not_compressed( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &create_server_settings( 1024 ),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request =
+        Request::builder().uri( "/health/live" ).header( header::ACCEPT_ENCODING, "br" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert!( !response.headers().contains_key( header::CONTENT_ENCODING ) );
+    assert_eq!( response.headers()[header::VARY], "accept-encoding" );
+}
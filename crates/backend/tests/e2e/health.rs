@@ -0,0 +1,87 @@
+use axum::{body::Body, http, http::Request};
+use backend::{app, settings, shutdown::Shutdown};
+use sqlx::PgPool;
+use tower::util::ServiceExt;
+
+use super::create_storage_settings;
+
+#[sqlx::test]
+fn health_live( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder().uri( "/health/live" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+}
+
+#[sqlx::test]
+fn health_ready( db: PgPool ) {
+    let app = app::create(
+        db,
+        &create_storage_settings( "health-ready" ),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder().uri( "/health/ready" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let report: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( report["status"], "pass" );
+    assert_eq!( report["checks"]["database"]["status"], "pass" );
+    assert_eq!( report["checks"]["migrations"]["status"], "pass" );
+    assert_eq!( report["checks"]["storage"]["status"], "pass" );
+}
+
+#[sqlx::test]
+fn health_ready_fails_without_storage( db: PgPool ) {
+    let storage_settings = settings::StorageConfigs {
+        dir: ::settings::validators::DirectoryPath::new_unchecked( "./missing-storage" ),
+        s3:  None,
+    };
+    let app = app::create( db, &storage_settings, &settings::ServerConfigs::default(), Shutdown::default() ).unwrap();
+
+    let request = Request::builder().uri( "/health/ready" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::SERVICE_UNAVAILABLE );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let report: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( report["checks"]["database"]["status"], "pass" );
+    assert_eq!( report["checks"]["storage"]["status"], "fail" );
+}
+
+#[sqlx::test]
+fn health_ready_fails_while_draining( db: PgPool ) {
+    let shutdown = Shutdown::default();
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        shutdown.clone(),
+    )
+    .unwrap();
+    shutdown.trigger();
+
+    let request = Request::builder().uri( "/health/ready" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::SERVICE_UNAVAILABLE );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let report: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( report["checks"]["shutdown"]["status"], "fail" );
+}
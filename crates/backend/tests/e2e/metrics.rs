@@ -0,0 +1,44 @@
+use axum::{body::Body, http, http::Request};
+use backend::{app, settings, shutdown::Shutdown};
+use sqlx::PgPool;
+use tower::util::ServiceExt;
+
+#[sqlx::test]
+fn metrics_are_labelled_by_route_template( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder().uri( "/health/live" ).body( Body::empty() ).unwrap();
+    app.clone().oneshot( request ).await.unwrap();
+
+    let request = Request::builder().uri( "/metrics" ).body( Body::empty() ).unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let metrics = String::from_utf8( response_body.to_vec() ).unwrap();
+    assert!( metrics.contains( r#"incoming_requests{method="GET",route="/health/live"}"# ) );
+    assert!( metrics.contains( r#"route="/health/live",statuscode="200",type="2xx""# ) );
+}
+
+#[sqlx::test]
+fn metrics_are_not_served_on_main_router_with_dedicated_listener( db: PgPool ) {
+    let server_settings = settings::ServerConfigs {
+        metrics: settings::MetricsSubconfig {
+            is_exposed: true,
+            sock_addr:  Some( "127.0.0.1:9100".parse().unwrap() ),
+        },
+        ..settings::ServerConfigs::default()
+    };
+    let app = app::create( db, &settings::StorageConfigs::default(), &server_settings, Shutdown::default() ).unwrap();
+
+    let request = Request::builder().uri( "/metrics" ).body( Body::empty() ).unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::NOT_FOUND );
+}
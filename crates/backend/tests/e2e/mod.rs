@@ -1 +1,22 @@
+use std::fs;
+
+use backend::settings;
+
+mod backup;
+mod compression;
+mod health;
+mod metrics;
 mod photos;
+mod request_id;
+
+/// Settings of a storage in a new directory of the temporary directory.
+fn create_storage_settings( name: &str ) -> settings::StorageConfigs {
+    let dir = std::env::temp_dir().join( format!( "backend-tests-storage-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+
+    settings::StorageConfigs {
+        dir: ::settings::validators::DirectoryPath::new_unchecked( dir ),
+        s3:  None,
+    }
+}
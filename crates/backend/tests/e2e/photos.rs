@@ -1,12 +1,19 @@
-use axum::{body::Body, http, http::Request};
-use backend::app;
+use axum::{body::Body, http, http::Request, Router};
+use backend::{app, settings, shutdown::Shutdown};
+use common::api::photos::list_photos;
 use serde_json::json;
 use sqlx::PgPool;
 use tower::util::ServiceExt;
 
 #[sqlx::test]
 fn photo_add( db: PgPool ) {
-    let app = app::create( db );
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
 
     let body = json!( {} );
 
@@ -22,3 +29,160 @@ fn photo_add( db: PgPool ) {
     assert_eq!( response.status(), http::StatusCode::OK );
     let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
 }
+
+#[sqlx::test]
+fn photo_get( db: PgPool ) {
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO photos ( created_at, url, title ) VALUES ( TO_TIMESTAMP( 1700000000 ), $1, $2 ) RETURNING id;",
+    )
+    .bind( "https://photos.example.com/lisbon.jpg" )
+    .bind( "Lisbon" )
+    .fetch_one( &db )
+    .await
+    .unwrap();
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder().uri( format!( "/api/v1/photos/{id}" ) ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let mut bytes = rkyv::AlignedVec::new();
+    bytes.extend_from_slice( &response_body );
+    let photo = rkyv::from_bytes::<common::api::photos::get_photo::Output>( &bytes ).unwrap();
+    assert_eq!( photo.id, u32::try_from( id ).unwrap() );
+    assert_eq!( photo.created_at, 1_700_000_000 );
+    assert_eq!( photo.title, "Lisbon" );
+    assert_eq!( photo.description, None );
+}
+
+#[sqlx::test]
+fn photo_get_not_found( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder().uri( "/api/v1/photos/404" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::NOT_FOUND );
+}
+
+/// Inserts photos taken 5 minutes apart, Lisbon first, and returns their ids.
+async fn insert_photos( db: &PgPool, titles: &[&str] ) -> Vec<i32> {
+    let query = r#"
+        INSERT INTO photos ( created_at, url, title, width, height )
+        VALUES ( TO_TIMESTAMP( $1 ), $2, $3, 3000, 2000 )
+        RETURNING id;
+    "#;
+
+    let mut ids = Vec::new();
+    for ( created_at, title ) in ( 1_700_000_000.. ).step_by( 300 ).zip( titles ) {
+        let id = sqlx::query_scalar( query )
+            .bind( f64::from( created_at ) )
+            .bind( format!( "https://photos.example.com/{title}.jpg" ) )
+            .bind( *title )
+            .fetch_one( db )
+            .await
+            .unwrap();
+        ids.push( id );
+    }
+
+    ids
+}
+
+async fn list_photos( app: &Router, uri: &str ) -> ( http::StatusCode, Option<list_photos::Output> ) {
+    let request = Request::builder().uri( uri ).body( Body::empty() ).unwrap();
+    let response = app.clone().oneshot( request ).await.unwrap();
+    let status = response.status();
+    if status != http::StatusCode::OK {
+        return ( status, None );
+    }
+
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let mut bytes = rkyv::AlignedVec::new();
+    bytes.extend_from_slice( &response_body );
+
+    ( status, Some( rkyv::from_bytes::<list_photos::Output>( &bytes ).unwrap() ) )
+}
+
+fn titles( page: &list_photos::Output ) -> Vec<&str> {
+    page.photos.iter().map( |photo| photo.title.as_str() ).collect()
+}
+
+#[sqlx::test]
+fn photo_list_pages( db: PgPool ) {
+    insert_photos( &db, &["Lisbon", "Porto", "Braga"] ).await;
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let ( _, first_page ) = list_photos( &app, "/api/v1/photos?limit=2" ).await;
+    let first_page = first_page.unwrap();
+    assert_eq!( titles( &first_page ), ["Braga", "Porto"] );
+    assert_eq!( first_page.photos[0].width, Some( 3000 ) );
+    assert_eq!( first_page.photos[0].height, Some( 2000 ) );
+    assert_eq!( first_page.next_cursor.map( |cursor| cursor.id() ), Some( first_page.photos[1].id ) );
+
+    let uri = format!( "/api/v1/photos?limit=2&cursor={}", first_page.next_cursor.unwrap() );
+    let ( _, last_page ) = list_photos( &app, &uri ).await;
+    let last_page = last_page.unwrap();
+    assert_eq!( titles( &last_page ), ["Lisbon"] );
+    assert_eq!( last_page.next_cursor, None );
+}
+
+#[sqlx::test]
+fn photo_list_pages_after_the_cursor_photo_is_removed( db: PgPool ) {
+    insert_photos( &db, &["Lisbon", "Porto", "Braga"] ).await;
+    let app = app::create(
+        db.clone(),
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let ( _, first_page ) = list_photos( &app, "/api/v1/photos?limit=2" ).await;
+    let cursor = first_page.unwrap().next_cursor.unwrap();
+    sqlx::query( "DELETE FROM photos WHERE id = $1;" )
+        .bind( i32::try_from( cursor.id() ).unwrap() )
+        .execute( &db )
+        .await
+        .unwrap();
+
+    let ( _, last_page ) = list_photos( &app, &format!( "/api/v1/photos?limit=2&cursor={cursor}" ) ).await;
+    assert_eq!( titles( &last_page.unwrap() ), ["Lisbon"] );
+}
+
+#[sqlx::test]
+fn photo_list_rejects_invalid_cursors( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    // Not a cursor, then the id of a photo, and a cursor whose id doesn't fit the database.
+    for cursor in ["photo", "7", "0000000000000000ffffffff"] {
+        let ( status, _ ) = list_photos( &app, &format!( "/api/v1/photos?cursor={cursor}" ) ).await;
+        assert_eq!( status, http::StatusCode::BAD_REQUEST, "{cursor}" );
+    }
+}
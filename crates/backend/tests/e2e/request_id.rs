@@ -0,0 +1,43 @@
+use axum::{body::Body, http::Request};
+use backend::{app, settings, shutdown::Shutdown};
+use sqlx::PgPool;
+use tower::util::ServiceExt;
+
+#[sqlx::test]
+fn request_id_is_echoed( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder()
+        .uri( "/health/live" )
+        .header( "x-request-id", "frontend-request-id" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.headers()["x-request-id"], "frontend-request-id" );
+}
+
+#[sqlx::test]
+fn request_id_is_generated_when_missing( db: PgPool ) {
+    let app = app::create(
+        db,
+        &settings::StorageConfigs::default(),
+        &settings::ServerConfigs::default(),
+        Shutdown::default(),
+    )
+    .unwrap();
+
+    let request = Request::builder().uri( "/health/live" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    let request_id = response.headers()["x-request-id"].to_str().unwrap();
+    assert!( uuid::Uuid::parse_str( request_id ).is_ok() );
+}
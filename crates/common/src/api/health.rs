@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde( rename_all = "lowercase" )]
+pub enum Status {
+    Pass,
+    Fail,
+}
+
+/// Result of a single dependency check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Check {
+    pub status:     Status,
+    pub latency_ms: f64,
+    #[serde( default, skip_serializing_if = "Option::is_none" )]
+    pub message:    Option<String>,
+}
+
+/// Response body of the health endpoints, with the breakdown of every check by name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub status: Status,
+    pub checks: BTreeMap<String, Check>,
+}
+
+impl Report {
+    /// Creates a report that only passes if every check passed.
+    #[must_use]
+    pub fn new( checks: BTreeMap<String, Check> ) -> Self {
+        let status = if checks.values().all( |check| check.status == Status::Pass ) {
+            Status::Pass
+        } else {
+            Status::Fail
+        };
+
+        Self { status, checks }
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+pub mod health;
 pub mod photos;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
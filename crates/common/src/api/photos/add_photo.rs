@@ -5,4 +5,6 @@ pub struct Input {
     pub url:         String,
     pub title:       String,
     pub description: Option<String>,
+    pub width:       Option<u32>,
+    pub height:      Option<u32>,
 }
@@ -0,0 +1,16 @@
+use rkyv::{Archive, Deserialize, Serialize};
+
+/// A photo, encoded with rkyv by the backend API and with serde by the server functions of the frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize, serde::Serialize, serde::Deserialize)]
+#[archive( check_bytes )]
+pub struct Output {
+    pub id:          u32,
+    /// When the photo was taken, in seconds since the Unix epoch.
+    pub created_at:  i64,
+    pub url:         String,
+    pub title:       String,
+    pub description: Option<String>,
+    /// Dimensions in pixels, unknown for the photos added before they were stored.
+    pub width:       Option<u32>,
+    pub height:      Option<u32>,
+}
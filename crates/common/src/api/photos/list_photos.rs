@@ -0,0 +1,97 @@
+use std::{fmt, str::FromStr};
+
+use rkyv::{Archive, Deserialize, Serialize};
+use thiserror::Error;
+
+use super::get_photo;
+
+/// Number of photos of a page, when the query doesn't set it.
+pub const DEFAULT_LIMIT: u32 = 24;
+
+/// Maximum number of photos of a page.
+pub const MAX_LIMIT: u32 = 100;
+
+/// The query of a page of the photos, the newest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Query {
+    /// The `next_cursor` of the previous page, or none for the first page.
+    pub cursor: Option<Cursor>,
+    pub limit:  Option<u32>,
+}
+
+/// A page of the photos, encoded with rkyv by the backend API and with serde by the server functions of the frontend.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, Serialize, Deserialize, serde::Serialize, serde::Deserialize)]
+#[archive( check_bytes )]
+pub struct Output {
+    pub photos:      Vec<get_photo::Output>,
+    /// The cursor of the next page, or none if it is the last one.
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Error type of the parsing of a [`Cursor`].
+#[derive(Error, Debug)]
+#[error( "Invalid cursor: {0}" )]
+pub struct InvalidCursor( String );
+
+/// The position of a page in the listing: the creation time and the id of the last photo of the previous page, so the
+/// next page starts at the same place even if that photo is removed in the meantime.
+///
+/// It is opaque to the clients, which pass back the `next_cursor` of a page as the `cursor` of the next one.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Archive, Serialize, Deserialize, serde::Serialize, serde::Deserialize,
+)]
+#[archive( check_bytes )]
+#[serde( try_from = "String", into = "String" )]
+pub struct Cursor {
+    /// Creation time of the photo, in microseconds since the Unix epoch.
+    created_at_micros: i64,
+    id:                u32,
+}
+
+impl Cursor {
+    /// Length of the cursor, as hexadecimal digits of the creation time and of the id.
+    const LEN: usize = 16 + 8;
+
+    #[must_use]
+    pub const fn new( created_at_micros: i64, id: u32 ) -> Self { Self { created_at_micros, id } }
+
+    #[must_use]
+    pub const fn created_at_micros( &self ) -> i64 { self.created_at_micros }
+
+    #[must_use]
+    pub const fn id( &self ) -> u32 { self.id }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result {
+        let created_at = u64::from_be_bytes( self.created_at_micros.to_be_bytes() );
+        write!( f, "{created_at:016x}{:08x}", self.id )
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = InvalidCursor;
+
+    fn from_str( cursor: &str ) -> Result<Self, Self::Err> {
+        if cursor.len() != Self::LEN || !cursor.bytes().all( |byte| byte.is_ascii_hexdigit() ) {
+            return Err( InvalidCursor( cursor.to_string() ) );
+        }
+
+        let ( created_at, id ) = cursor.split_at( 16 );
+        let invalid = |_| InvalidCursor( cursor.to_string() );
+        let created_at = u64::from_str_radix( created_at, 16 ).map_err( invalid )?;
+        let id = u32::from_str_radix( id, 16 ).map_err( invalid )?;
+
+        Ok( Self::new( i64::from_be_bytes( created_at.to_be_bytes() ), id ) )
+    }
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = InvalidCursor;
+
+    fn try_from( cursor: String ) -> Result<Self, Self::Error> { cursor.parse() }
+}
+
+impl From<Cursor> for String {
+    fn from( cursor: Cursor ) -> Self { cursor.to_string() }
+}
@@ -1 +1,3 @@
 pub mod add_photo;
+pub mod get_photo;
+pub mod list_photos;
@@ -0,0 +1,39 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// The fingerprinted URLs of the static files, whose names include a hash of their content.
+#[derive(Debug, Clone, Default)]
+pub struct AssetManifest {
+    urls:          Arc<HashMap<String, String>>,
+    fingerprinted: Arc<HashSet<String>>,
+}
+
+impl AssetManifest {
+    /// Creates the manifest from the fingerprinted names of the `files`, served under `base_url`.
+    #[must_use]
+    pub fn new( base_url: &str, files: HashMap<String, String> ) -> Self {
+        let base_url = base_url.trim_end_matches( '/' );
+        let urls: HashMap<String, String> = files
+            .into_iter()
+            .map( |( file, fingerprinted_file )| {
+                ( format!( "{base_url}/{file}" ), format!( "{base_url}/{fingerprinted_file}" ) )
+            } )
+            .collect();
+        let fingerprinted = urls.values().cloned().collect();
+
+        Self {
+            urls: Arc::new( urls ),
+            fingerprinted: Arc::new( fingerprinted ),
+        }
+    }
+
+    /// The fingerprinted URL of a file, or its `url` if it is not fingerprinted.
+    #[must_use]
+    pub fn url<'a>( &'a self, url: &'a str ) -> &'a str { self.urls.get( url ).map_or( url, String::as_str ) }
+
+    /// Whether the `url` is a fingerprinted one, whose content never changes.
+    #[must_use]
+    pub fn is_fingerprinted( &self, url: &str ) -> bool { self.fingerprinted.contains( url ) }
+}
@@ -5,4 +5,5 @@
 #![warn( clippy::perf )]
 
 pub mod api;
+pub mod assets;
 pub mod entities;
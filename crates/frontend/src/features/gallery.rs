@@ -0,0 +1,50 @@
+//! The justified layout of the gallery.
+//!
+//! Every photo is a flex item of the height of the rows, as wide as its image at that height, which the `width` and
+//! `height` attributes of the image give from its aspect ratio. The items of a row grow to fill its width, and their
+//! images are cropped to cover them. The attributes reserve the space of a photo before it is loaded, so the page
+//! doesn't shift while the photos load, and the layout holds in the rendered page before it is hydrated.
+//!
+//! The layout only uses classes and attributes, as the Content-Security-Policy blocks the inline styles.
+
+/// Height of the rows, in pixels, set by the `h-60` class of the tiles.
+pub const ROW_HEIGHT: u32 = 240;
+
+/// Aspect ratio of the photos without dimensions.
+const DEFAULT_ASPECT_RATIO: f64 = 3.0 / 2.0;
+
+/// Bounds of the aspect ratio, so a panorama doesn't take a whole row and a tall photo doesn't shrink to a sliver.
+const MIN_ASPECT_RATIO: f64 = 1.0 / 2.0;
+const MAX_ASPECT_RATIO: f64 = 3.0;
+
+/// The aspect ratio of a photo from its dimensions, within the bounds of the layout.
+#[must_use]
+pub fn aspect_ratio( width: Option<u32>, height: Option<u32> ) -> f64 {
+    match ( width, height ) {
+        ( Some( width ), Some( height ) ) if width > 0 && height > 0 => {
+            ( f64::from( width ) / f64::from( height ) ).clamp( MIN_ASPECT_RATIO, MAX_ASPECT_RATIO )
+        }
+        _ => DEFAULT_ASPECT_RATIO,
+    }
+}
+
+/// The `width` and `height` attributes of the image of a photo, at the height of the rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub width:  u32,
+    pub height: u32,
+}
+
+impl Tile {
+    #[must_use]
+    pub fn new( aspect_ratio: f64 ) -> Self {
+        // The aspect ratios of the layout are bounded, so the width is a few hundred pixels.
+        #[allow( clippy::cast_possible_truncation, clippy::cast_sign_loss )]
+        let width = ( aspect_ratio * f64::from( ROW_HEIGHT ) ).round() as u32;
+
+        Self {
+            width,
+            height: ROW_HEIGHT,
+        }
+    }
+}
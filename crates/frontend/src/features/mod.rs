@@ -1 +1 @@
-
+pub mod gallery;
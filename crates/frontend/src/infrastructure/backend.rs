@@ -0,0 +1,96 @@
+//! The backend API of the server functions, when they run in the web server.
+//!
+//! The web server provides the [`BackendApi`] as context to the pages it renders and to the server functions it
+//! handles. It sends their requests to the backend over HTTP with the pool of connections of its reverse proxy, without
+//! going through its own public routes. The id of the request being handled is sent along, so the logs of the backend
+//! can be correlated with the ones of the web server.
+//!
+//! Leptos 0.5 has no rkyv encoding for the server functions, so they are encoded with CBOR between the browsers and the
+//! web server, and only the backend API answers with rkyv.
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use http::{Response, StatusCode};
+use leptos::{use_context, ServerFnError};
+use rkyv::{
+    de::deserializers::SharedDeserializeMap, validation::validators::DefaultValidator, AlignedVec, Archive, CheckBytes,
+    Deserialize,
+};
+
+/// Sends a GET request to the backend, with the path and query of the resource, e.g. `/api/v1/photos/1`, and the id
+/// of the request being handled if any.
+type Get = dyn Fn( String, Option<String> ) -> BoxFuture<'static, Result<Response<Vec<u8>>, String>> + Send + Sync;
+
+/// The backend API, provided as context by the web server.
+#[derive(Clone)]
+pub struct BackendApi {
+    get:        Arc<Get>,
+    request_id: Option<String>,
+}
+
+impl BackendApi {
+    #[must_use]
+    pub fn new<F>( get: F ) -> Self
+    where
+        F: Fn( String, Option<String> ) -> BoxFuture<'static, Result<Response<Vec<u8>>, String>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Self {
+            get:        Arc::new( get ),
+            request_id: None,
+        }
+    }
+
+    /// The backend API of the request with the `request_id`, which is sent with the requests to the backend.
+    #[must_use]
+    pub fn with_request_id( &self, request_id: Option<String> ) -> Self {
+        Self {
+            get: self.get.clone(),
+            request_id,
+        }
+    }
+
+    /// Gets a resource encoded with rkyv, or [`None`] if the backend doesn't find it.
+    ///
+    /// # Errors
+    ///
+    /// If the backend can't be reached, answers with an error, or answers with a resource that can't be decoded.
+    pub async fn get<T>( &self, path_query: &str ) -> Result<Option<T>, ServerFnError>
+    where
+        T: Archive,
+        T::Archived: for<'a> CheckBytes<DefaultValidator<'a>> + Deserialize<T, SharedDeserializeMap>,
+    {
+        let response = ( self.get )( path_query.to_string(), self.request_id.clone() )
+            .await
+            .map_err( ServerFnError::ServerError )?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok( None ),
+            status if status.is_success() => {
+                // The archived resource is read in place, which needs the alignment of its fields.
+                let mut bytes = AlignedVec::with_capacity( response.body().len() );
+                bytes.extend_from_slice( response.body() );
+
+                rkyv::from_bytes::<T>( &bytes )
+                    .map( Some )
+                    .map_err( |err| ServerFnError::Deserialization( err.to_string() ) )
+            }
+            status => {
+                Err( ServerFnError::ServerError( format!( "The backend answered {path_query} with {status}." ) ) )
+            }
+        }
+    }
+}
+
+/// The backend API provided by the web server.
+///
+/// # Errors
+///
+/// If it is not provided, e.g. outside of the web server.
+pub fn use_backend_api() -> Result<BackendApi, ServerFnError> {
+    use_context::<BackendApi>()
+        .ok_or_else( || ServerFnError::ServerError( "The backend API is not provided.".to_string() ) )
+}
@@ -1 +1,4 @@
+//! The server functions of the features, backed by the backend API. The backend has no story domain yet, so only the
+//! photos have server functions.
 
+pub mod photos;
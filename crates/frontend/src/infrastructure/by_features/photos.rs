@@ -0,0 +1,32 @@
+use common::api::photos::{
+    get_photo::Output as Photo,
+    list_photos::{Cursor, Output as PhotosPage},
+};
+use leptos::{server, ServerFnError};
+
+/// Gets a photo, or [`None`] if it doesn't exist.
+///
+/// The pages rendered by the web server call it directly, without a request to `/leptos`, and their resources are
+/// serialized into the page, so the hydrated page reuses them instead of calling it again.
+#[server( GetPhoto, "/leptos", "Cbor" )]
+pub async fn get_photo( id: u32 ) -> Result<Option<Photo>, ServerFnError> {
+    use crate::infrastructure::backend;
+
+    backend::use_backend_api()?.get( &format!( "/api/v1/photos/{id}" ) ).await
+}
+
+/// Gets a page of the photos, the newest first, after the `cursor` of the previous page.
+#[server( GetPhotos, "/leptos", "Cbor" )]
+pub async fn get_photos( cursor: Option<Cursor> ) -> Result<PhotosPage, ServerFnError> {
+    use crate::infrastructure::backend;
+
+    let path_query = cursor.map_or_else(
+        || "/api/v1/photos".to_string(),
+        |cursor| format!( "/api/v1/photos?cursor={cursor}" ),
+    );
+
+    backend::use_backend_api()?
+        .get( &path_query )
+        .await?
+        .ok_or_else( || ServerFnError::ServerError( "The backend doesn't list the photos.".to_string() ) )
+}
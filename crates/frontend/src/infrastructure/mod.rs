@@ -1,2 +1,4 @@
 // Modules.
+#[cfg( feature = "ssr" )]
+pub mod backend;
 pub mod by_features;
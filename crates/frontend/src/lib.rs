@@ -4,7 +4,6 @@
 #![warn( clippy::complexity )]
 #![warn( clippy::perf )]
 #![feature( async_closure )]
-#![feature( lazy_cell )]
 
 #[cfg( target_arch = "wasm32" )]
 use lol_alloc::{FreeListAllocator, LockedAllocator};
@@ -13,11 +12,11 @@ use lol_alloc::{FreeListAllocator, LockedAllocator};
 #[global_allocator]
 static ALLOCATOR: LockedAllocator<FreeListAllocator> = LockedAllocator::new( FreeListAllocator::new() );
 
+pub mod features;
+pub mod infrastructure;
 pub mod presentation;
 
 mod domain;
-mod features;
-mod infrastructure;
 mod utils;
 use wasm_bindgen::prelude::wasm_bindgen;
 
@@ -1 +1 @@
-
+pub mod photos;
@@ -0,0 +1,193 @@
+use common::api::photos::{get_photo::Output as Photo, list_photos::Cursor};
+use leptos::{
+    component, create_effect, create_node_ref, create_resource, create_rw_signal, create_signal, document, ev,
+    html::Div, on_cleanup, provide_context, request_animation_frame, store_value, use_context, view, window,
+    window_event_listener, CollectView, For, IntoView, RwSignal, SignalGet, SignalSet, SignalUpdate, SignalWith,
+    SignalWithUntracked, StoredValue, Transition,
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{IntersectionObserver, IntersectionObserverEntry};
+
+use crate::{
+    features::gallery::{self, Tile},
+    infrastructure::by_features::photos::get_photos,
+};
+
+/// The state of the gallery that outlives its page, so it is restored when the user navigates back from a photo.
+#[derive(Debug, Clone, Copy)]
+pub struct GalleryState {
+    /// The cursor of every loaded page, none for the first one.
+    cursors:            RwSignal<Vec<Option<Cursor>>>,
+    /// The scroll position when a photo was opened.
+    scroll_y:           StoredValue<Option<f64>>,
+    /// Whether the current navigation goes back or forward in the history, rather than to a new entry.
+    is_navigating_back: StoredValue<bool>,
+}
+
+impl GalleryState {
+    fn new() -> Self {
+        Self {
+            cursors:            create_rw_signal( vec![None] ),
+            scroll_y:           store_value( None ),
+            is_navigating_back: store_value( false ),
+        }
+    }
+}
+
+/// Provides the [`GalleryState`] to the routes.
+///
+/// It must be called before the router is created, so the gallery knows whether the user navigates back when the
+/// router handles the `popstate` event and renders it. The flag lasts until the next frame, as for the router.
+pub fn provide_gallery_state() {
+    let state = GalleryState::new();
+    provide_context( state );
+
+    // Kept for the lifetime of the app.
+    window_event_listener( ev::popstate, move |_| {
+        state.is_navigating_back.set_value( true );
+        request_animation_frame( move || state.is_navigating_back.set_value( false ) );
+    } );
+}
+
+/// The gallery of the photos, the newest first, which loads the next page as the user scrolls to its end.
+#[must_use]
+#[component]
+pub fn ComponentPhotoGallery() -> impl IntoView {
+    let state = use_context::<GalleryState>().unwrap_or_else( GalleryState::new );
+    if !state.is_navigating_back.get_value() || state.scroll_y.get_value().is_none() {
+        // Not navigating back from a photo, starts again from the newest photos, without the scroll position of a
+        // photo opened before.
+        state.scroll_y.set_value( None );
+        state.cursors.set( vec![None] );
+    }
+
+    let next_cursor = create_rw_signal( None::<Cursor> );
+    let ( is_loading, set_is_loading ) = create_signal( false );
+    let is_end_near = create_rw_signal( false );
+    let end = create_node_ref::<Div>();
+
+    // Loads the next page when the end of the gallery is near, and again while it stays near.
+    create_effect( move |_| {
+        if !is_end_near.get() {
+            return;
+        }
+        let Some( cursor ) = next_cursor.get() else {
+            return;
+        };
+
+        if !state.cursors.with_untracked( |cursors| cursors.contains( &Some( cursor ) ) ) {
+            state.cursors.update( |cursors| cursors.push( Some( cursor ) ) );
+        }
+    } );
+
+    create_effect( move |_| {
+        let Some( end ) = end.get() else {
+            return;
+        };
+
+        let on_intersection = Closure::<dyn Fn( js_sys::Array )>::new( move |entries: js_sys::Array| {
+            // The last entry is the latest change of the intersection.
+            let entry = entries.get( entries.length().saturating_sub( 1 ) );
+            if let Ok( entry ) = entry.dyn_into::<IntersectionObserverEntry>() {
+                is_end_near.set( entry.is_intersecting() );
+            }
+        } );
+        let Ok( observer ) = IntersectionObserver::new( on_intersection.as_ref().unchecked_ref() ) else {
+            return;
+        };
+        observer.observe( &end );
+
+        on_cleanup( move || {
+            observer.disconnect();
+            drop( on_intersection );
+        } );
+    } );
+
+    // Restores the scroll position once the pages it was in are loaded again.
+    create_effect( move |_| {
+        if is_loading.get() {
+            return;
+        }
+
+        request_animation_frame( move || {
+            let Some( scroll_y ) = state.scroll_y.get_value() else {
+                return;
+            };
+            let scroll_height = document().document_element().map_or( 0, |element| element.scroll_height() );
+
+            if f64::from( scroll_height ) >= scroll_y {
+                state.scroll_y.set_value( None );
+                window().scroll_to_with_x_and_y( 0.0, scroll_y );
+            }
+        } );
+    } );
+
+    view! {
+        <section class="relative">
+            <div class="flex flex-wrap gap-1 p-1">
+                <Transition fallback=|| () set_pending=set_is_loading>
+                    <For
+                        each=move || state.cursors.get()
+                        key=|cursor| *cursor
+                        children=move |cursor| view! { <GalleryPage cursor next_cursor state/> }
+                    />
+                </Transition>
+                // Takes the free space of the last row, so its photos are not stretched.
+                <div class="h-0 grow-[1000000]"></div>
+            </div>
+
+            // Near the end when it is less than a screen away.
+            <div node_ref=end class="pointer-events-none absolute inset-x-0 bottom-0 h-screen"></div>
+        </section>
+    }
+}
+
+#[component]
+fn GalleryPage( cursor: Option<Cursor>, next_cursor: RwSignal<Option<Cursor>>, state: GalleryState ) -> impl IntoView {
+    let page = create_resource( move || cursor, get_photos );
+
+    // The next page is the one after the last loaded page.
+    create_effect( move |_| {
+        let is_last_page = state.cursors.with( |cursors| cursors.last() == Some( &cursor ) );
+        page.with( |page| {
+            if let Some( Ok( page ) ) = page.as_ref().filter( |_| is_last_page ) {
+                next_cursor.set( page.next_cursor );
+            }
+        } );
+    } );
+
+    view! {
+        {move || page.with( |page| match page {
+            None => ().into_view(),
+            Some( Ok( page ) ) => {
+                page.photos.iter().map( |photo| view! { <GalleryTile photo=photo.clone() state/> } ).collect_view()
+            }
+            Some( Err( err ) ) => view! {
+                <p class="text-error w-full p-4">{format!( "The photos can't be loaded: {err}" )}</p>
+            }
+            .into_view(),
+        } )}
+    }
+}
+
+#[component]
+fn GalleryTile( photo: Photo, state: GalleryState ) -> impl IntoView {
+    let tile = Tile::new( gallery::aspect_ratio( photo.width, photo.height ) );
+    let on_click = move |_| state.scroll_y.set_value( Some( window().scroll_y().unwrap_or_default() ) );
+
+    // The tile is as wide as its image at the height of the rows, and grows with the others to fill its row.
+    view! {
+        <a class="bg-base-200 block h-60 grow overflow-hidden" href=format!( "/photos/{}", photo.id ) on:click=on_click>
+            <img
+                class="block h-full min-w-full object-cover"
+                width=tile.width
+                height=tile.height
+                src=photo.url
+                alt=photo.title.clone()
+                title=photo.title
+                loading="lazy"
+                decoding="async"
+            />
+        </a>
+    }
+}
@@ -0,0 +1,5 @@
+pub use gallery::{provide_gallery_state, ComponentPhotoGallery};
+pub use photo::ComponentPhoto;
+
+pub mod gallery;
+pub mod photo;
@@ -0,0 +1,62 @@
+use common::api::photos::get_photo::Output as Photo;
+use leptos::{component, create_resource, view, IntoView, SignalWith, Suspense};
+use leptos_router::use_params_map;
+
+use crate::{
+    features::gallery::{self, Tile},
+    infrastructure::by_features::photos::get_photo,
+};
+
+/// The page of a photo, opened from the gallery.
+#[must_use]
+#[component]
+pub fn ComponentPhoto() -> impl IntoView {
+    let params = use_params_map();
+    let id = move || params.with( |params| params.get( "id" ).and_then( |id| id.parse::<u32>().ok() ) );
+    let photo = create_resource( id, |id| async move {
+        match id {
+            Some( id ) => get_photo( id ).await,
+            None => Ok( None ),
+        }
+    } );
+
+    view! {
+        <section class="mx-auto max-w-5xl p-4">
+            <a class="link" href="/photos">"Back to the gallery"</a>
+
+            <Suspense fallback=|| ()>
+                {move || photo.with( |photo| match photo {
+                    None => ().into_view(),
+                    Some( Ok( Some( photo ) ) ) => view! { <PhotoDetails photo=photo.clone()/> }.into_view(),
+                    Some( Ok( None ) ) => view! { <p class="p-4">"The photo doesn't exist."</p> }.into_view(),
+                    Some( Err( err ) ) => view! {
+                        <p class="text-error p-4">{format!( "The photo can't be loaded: {err}" )}</p>
+                    }
+                    .into_view(),
+                } )}
+            </Suspense>
+        </section>
+    }
+}
+
+#[component]
+fn PhotoDetails( photo: Photo ) -> impl IntoView {
+    // The height of the photo is reserved from the aspect ratio of its attributes, before it is loaded.
+    let tile = Tile::new( gallery::aspect_ratio( photo.width, photo.height ) );
+
+    view! {
+        <figure class="mt-4">
+            <img
+                class="bg-base-200 h-auto w-full object-contain"
+                width=tile.width
+                height=tile.height
+                src=photo.url
+                alt=photo.title.clone()
+            />
+            <figcaption class="mt-4">
+                <h1 class="text-2xl font-bold">{photo.title}</h1>
+                {photo.description.map( |description| view! { <p class="mt-2">{description}</p> } )}
+            </figcaption>
+        </figure>
+    }
+}
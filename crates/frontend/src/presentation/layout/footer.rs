@@ -44,10 +44,7 @@ fn FooterTop() -> impl IntoView {
                     <div class="relative">
                         <input class="input input-bordered w-100" placeholder="Email address"></input>
 
-                        <button
-                            class="btn btn-primary btn-sm absolute"
-                            style="right: 0.5rem; top: 0.5rem;"
-                        >
+                        <button class="btn btn-primary btn-sm absolute right-2 top-2">
                             "Subscribe"
                         </button>
                     </div>
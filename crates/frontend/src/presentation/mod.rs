@@ -2,9 +2,10 @@
 #![allow( clippy::module_name_repetitions )]
 
 use leptos::{component, create_signal, view, Errors, IntoView, SignalUpdate};
-use leptos_meta::{provide_meta_context, Html, Meta, Style, Title};
+use leptos_meta::{provide_meta_context, Html, Meta, Stylesheet, Title};
 use leptos_router::Router;
 
+pub mod by_features;
 pub mod components;
 pub mod layout;
 pub mod routes;
@@ -12,15 +13,21 @@ pub mod routes;
 use components::error_template::AppErrorComponent;
 use routes::ComponentRouter;
 
-use crate::presentation::{
-    components::error_template::ErrorComponent,
-    layout::{ComponentFooter, ComponentHeader},
+use crate::{
+    presentation::{
+        by_features::photos::provide_gallery_state,
+        components::error_template::ErrorComponent,
+        layout::{ComponentFooter, ComponentHeader},
+    },
+    utils::assets::asset_url,
 };
 
 #[component]
 pub fn AppComponent() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
+    // Keeps the loaded pages of the gallery and its scroll position while the user browses the photos.
+    provide_gallery_state();
 
     view! {
         <Html lang="en" />
@@ -62,11 +69,9 @@ pub fn AppComponent() -> impl IntoView {
 
 #[component]
 fn TailwindStyle() -> impl IntoView {
-    let style = include_str!( "../../styles/dist/tailwind.css" );
+    // The stylesheet built from the tailwind styles, fingerprinted in the release build so it is cached as immutable.
     view! {
-        <Style>
-            {style}
-        </Style>
+        <Stylesheet href=asset_url( "/static/frontend.css" ) />
     }
 }
 
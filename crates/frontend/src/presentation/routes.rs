@@ -3,12 +3,16 @@
 use leptos::{component, view, IntoView };
 use leptos_router::{Route, Routes};
 
+use crate::presentation::by_features::photos::{ComponentPhoto, ComponentPhotoGallery};
+
 #[must_use]
 #[component]
 pub fn ComponentRouter() -> impl IntoView {
     view! {
         <Routes>
             <Route path="/" view=|| view! { <Home/> }/>
+            <Route path="/photos" view=|| view! { <ComponentPhotoGallery/> }/>
+            <Route path="/photos/:id" view=|| view! { <ComponentPhoto/> }/>
             // <Route path="" view=|cx| view! { cx, <NotFound/> }/>
         </Routes>
     }
@@ -21,8 +25,8 @@ fn Home() -> impl IntoView {
 
             "Home"
             <br/>
-            <a href="/hello-server">
-                "click here to go to hello-server"
+            <a href="/photos">
+                "click here to see the photos"
             </a>
         </h1>
     }
@@ -0,0 +1,10 @@
+use common::assets::AssetManifest;
+use leptos::use_context;
+
+/// The fingerprinted URL of a static file, provided by the web server when it renders the page. The hydrated page keeps
+/// the URLs it was rendered with, so without manifest the `url` is returned unchanged.
+#[must_use]
+pub fn asset_url( url: &str ) -> String {
+    use_context::<AssetManifest>()
+        .map_or_else( || url.to_string(), |asset_manifest| asset_manifest.url( url ).to_string() )
+}
@@ -0,0 +1,81 @@
+#![cfg( feature = "ssr" )]
+
+use std::sync::{Arc, Mutex};
+
+use common::api::photos::get_photo::Output as Photo;
+use frontend::infrastructure::backend::BackendApi;
+use futures::executor::block_on;
+use http::{Response, StatusCode};
+use leptos::ServerFnError;
+
+fn create_photo() -> Photo {
+    Photo {
+        id:          7,
+        created_at:  1_700_000_000,
+        url:         "/photos/7.jpg".to_string(),
+        title:       "Lighthouse".to_string(),
+        description: None,
+        width:       Some( 1600 ),
+        height:      Some( 1200 ),
+    }
+}
+
+/// A backend API answering every request with the `status` and `body`.
+fn create_backend_api( status: StatusCode, body: Vec<u8> ) -> BackendApi {
+    BackendApi::new( move |_, _| {
+        let response = Response::builder().status( status ).body( body.clone() ).unwrap();
+        Box::pin( async move { Ok( response ) } )
+    } )
+}
+
+#[test]
+fn resources_are_decoded_with_rkyv() {
+    let photo = create_photo();
+    let backend_api = create_backend_api( StatusCode::OK, rkyv::to_bytes::<_, 256>( &photo ).unwrap().to_vec() );
+
+    assert_eq!( block_on( backend_api.get::<Photo>( "/api/v1/photos/7" ) ).unwrap(), Some( photo ) );
+}
+
+#[test]
+fn requests_are_sent_with_the_request_id() {
+    let request_ids = Arc::new( Mutex::new( Vec::new() ) );
+    let backend_request_ids = request_ids.clone();
+    let backend_api = BackendApi::new( move |_, request_id| {
+        backend_request_ids.lock().unwrap().push( request_id );
+        Box::pin( async { Ok( Response::builder().status( StatusCode::NOT_FOUND ).body( Vec::new() ).unwrap() ) } )
+    } );
+
+    block_on( backend_api.get::<Photo>( "/api/v1/photos/7" ) ).unwrap();
+    let request_backend_api = backend_api.with_request_id( Some( "frontend-request-id".to_string() ) );
+    block_on( request_backend_api.get::<Photo>( "/api/v1/photos/7" ) ).unwrap();
+
+    assert_eq!( *request_ids.lock().unwrap(), [None, Some( "frontend-request-id".to_string() )] );
+}
+
+#[test]
+fn resources_not_found_are_none() {
+    let backend_api = create_backend_api( StatusCode::NOT_FOUND, Vec::new() );
+
+    assert_eq!( block_on( backend_api.get::<Photo>( "/api/v1/photos/7" ) ).unwrap(), None );
+}
+
+#[test]
+fn backend_errors_are_server_errors() {
+    let backend_api = create_backend_api( StatusCode::SERVICE_UNAVAILABLE, Vec::new() );
+    let result = block_on( backend_api.get::<Photo>( "/api/v1/photos/7" ) );
+    assert!( matches!( result, Err( ServerFnError::ServerError( message ) ) if message.contains( "503" ) ) );
+
+    // E.g. the timeout or the body limit of the proxy.
+    let backend_api =
+        BackendApi::new( |_, _| Box::pin( async { Err( "The backend took more than 1s.".to_string() ) } ) );
+    let result = block_on( backend_api.get::<Photo>( "/api/v1/photos/7" ) );
+    assert!( matches!( result, Err( ServerFnError::ServerError( message ) ) if message.contains( "1s" ) ) );
+}
+
+#[test]
+fn invalid_resources_are_deserialization_errors() {
+    let backend_api = create_backend_api( StatusCode::OK, b"not rkyv".to_vec() );
+
+    let result = block_on( backend_api.get::<Photo>( "/api/v1/photos/7" ) );
+    assert!( matches!( result, Err( ServerFnError::Deserialization( _ ) ) ) );
+}
@@ -0,0 +1,29 @@
+use frontend::features::gallery::{self, Tile};
+
+#[test]
+fn aspect_ratios_are_bounded() {
+    assert!( ( gallery::aspect_ratio( Some( 3000 ), Some( 2000 ) ) - 1.5 ).abs() < f64::EPSILON );
+    assert!( ( gallery::aspect_ratio( Some( 2000 ), Some( 3000 ) ) - 2.0 / 3.0 ).abs() < f64::EPSILON );
+
+    // A panorama and a tall photo are clamped.
+    assert!( ( gallery::aspect_ratio( Some( 8000 ), Some( 1000 ) ) - 3.0 ).abs() < f64::EPSILON );
+    assert!( ( gallery::aspect_ratio( Some( 1000 ), Some( 4000 ) ) - 0.5 ).abs() < f64::EPSILON );
+}
+
+#[test]
+fn photos_without_dimensions_get_the_default_aspect_ratio() {
+    let dimensions = [( None, None ), ( Some( 3000 ), None ), ( Some( 0 ), Some( 2000 ) ), ( Some( 3000 ), Some( 0 ) )];
+    for ( width, height ) in dimensions {
+        assert!( ( gallery::aspect_ratio( width, height ) - 1.5 ).abs() < f64::EPSILON, "{width:?}x{height:?}" );
+    }
+}
+
+#[test]
+fn tiles_are_as_wide_as_their_photo_at_the_height_of_the_rows() {
+    assert_eq!( Tile::new( 1.5 ), Tile { width: 360, height: gallery::ROW_HEIGHT } );
+    assert_eq!( Tile::new( 0.5 ), Tile { width: 120, height: 240 } );
+    assert_eq!( Tile::new( 3.0 ), Tile { width: 720, height: 240 } );
+    // Rounded to the nearest pixel.
+    assert_eq!( Tile::new( 4.0 / 3.0 ).width, 320 );
+    assert_eq!( Tile::new( 2.0 / 3.0 ).width, 160 );
+}
@@ -8,5 +8,8 @@
 #![warn( clippy::complexity )]
 #![warn( clippy::perf )]
 
+pub mod log_filter;
 pub mod logger;
 pub mod prometheus;
+pub mod redact;
+pub mod rolling_file;
@@ -0,0 +1,290 @@
+//! Runtime changes of the log filter.
+//!
+//! The filter installed by [`logger::init`](crate::logger::init) can be replaced without a restart by any directive
+//! string accepted by [`EnvFilter`], such as `backend::features=trace,sqlx=warn`. A change can revert by itself to the
+//! startup filter after a time to live, so a forgotten debug session does not flood the logs. The startup filter itself
+//! can be replaced with [`set_initial`], e.g. when the configured log level is reloaded.
+//!
+//! Every change is logged under the [`AUDIT_TARGET`] target, which the filter always lets through.
+//!
+//! # Examples
+//!
+//! ```
+//! use axum::Router;
+//! use monitoring::log_filter;
+//!
+//! // Serve the filter at `/log-filter`. The route must be protected by the caller's authentication.
+//! let admin: Router = Router::new().merge( log_filter::create_log_filter_route() );
+//! ```
+
+use std::{
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Target of the audit events, enabled at the info level whatever the filter is.
+pub const AUDIT_TARGET: &str = "audit";
+
+static LOG_FILTER: OnceLock<LogFilter> = OnceLock::new();
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error( "The logger was not initialized." )]
+    NotInitialized,
+    #[error( "The directives are invalid: {0}" )]
+    InvalidDirectives( String ),
+    #[error( "Failed to reload the log filter: {0}" )]
+    ReloadFailed( #[from] reload::Error ),
+}
+
+/// The directives in use and until when.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFilterState {
+    pub directives:         String,
+    pub initial_directives: String,
+    /// Seconds left before reverting to the initial directives, if the change has a time to live.
+    pub reverts_in_secs:    Option<u64>,
+}
+
+/// Body of a log filter change request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogFilterChange {
+    pub directives: String,
+    /// Seconds after which the initial directives are restored. The change is permanent if it is not set.
+    pub ttl_secs:   Option<u64>,
+}
+
+struct LogFilter {
+    handle:  reload::Handle<EnvFilter, Registry>,
+    current: Mutex<CurrentDirectives>,
+}
+
+struct CurrentDirectives {
+    directives:         String,
+    initial_directives: String,
+    /// Whether the directives were changed by [`set`], in which case the reloaded initial directives are only applied
+    /// once the change reverts or is reset.
+    is_overridden:      bool,
+    reverts_at:         Option<Instant>,
+    /// The task reverting the change with a time to live, aborted by the next change.
+    revert_task:        Option<JoinHandle<()>>,
+    /// Incremented on every change, so a pending revert knows whether it was superseded.
+    generation:         u64,
+}
+
+impl CurrentDirectives {
+    /// Reloads the filter with the directives, replacing the pending revert, and returns the directives it replaced.
+    fn apply(
+        &mut self,
+        handle: &reload::Handle<EnvFilter, Registry>,
+        directives: &str,
+        ttl: Option<Duration>,
+        is_overridden: bool,
+    ) -> Result<String, Error> {
+        handle.reload( build_filter( directives )? )?;
+
+        if let Some( revert_task ) = self.revert_task.take() {
+            revert_task.abort();
+        }
+        self.generation += 1;
+        self.is_overridden = is_overridden;
+        self.reverts_at = ttl.map( |ttl| Instant::now() + ttl );
+        self.revert_task = ttl.map( |ttl| tokio::spawn( revert_after( ttl, self.generation ) ) );
+
+        Ok( std::mem::replace( &mut self.directives, directives.to_string() ) )
+    }
+}
+
+/// Creates the reloadable filter layer from the initial directives and keeps its handle for the runtime changes.
+pub( crate ) fn create_layer( initial_directives: &str ) -> reload::Layer<EnvFilter, Registry> {
+    let filter = build_filter( initial_directives ).unwrap_or_else( |err| {
+        eprintln!( "Invalid log filter directives ( {initial_directives} ), falling back to info: {err}" );
+        EnvFilter::new( "info" )
+    } );
+    let ( layer, handle ) = reload::Layer::new( filter );
+
+    let log_filter = LogFilter {
+        handle,
+        current: Mutex::new( CurrentDirectives {
+            directives:         initial_directives.to_string(),
+            initial_directives: initial_directives.to_string(),
+            is_overridden:      false,
+            reverts_at:         None,
+            revert_task:        None,
+            generation:         0,
+        } ),
+    };
+
+    if LOG_FILTER.set( log_filter ).is_err() {
+        eprintln!( "The log filter was already initialized, runtime changes only apply to the first one." );
+    }
+
+    layer
+}
+
+/// Returns the directives in use.
+///
+/// # Errors
+///
+/// If the logger was not initialized.
+pub fn current() -> Result<LogFilterState, Error> {
+    let log_filter = LOG_FILTER.get().ok_or( Error::NotInitialized )?;
+    let current = log_filter.current.lock().unwrap_or_else( std::sync::PoisonError::into_inner );
+
+    Ok( LogFilterState {
+        directives:         current.directives.clone(),
+        initial_directives: current.initial_directives.clone(),
+        reverts_in_secs:    current
+            .reverts_at
+            .map( |reverts_at| reverts_at.saturating_duration_since( Instant::now() ).as_secs() ),
+    } )
+}
+
+/// Replaces the directives in use, reverting to the initial ones after `ttl` if it is set. The change overrides the
+/// reloaded initial directives until it reverts or is [`reset`].
+///
+/// Must be called within a Tokio runtime if `ttl` is set, which runs the revert.
+///
+/// # Errors
+///
+/// If the logger was not initialized, the directives are invalid or the filter could not be reloaded.
+pub fn set( directives: &str, ttl: Option<Duration> ) -> Result<LogFilterState, Error> {
+    let log_filter = LOG_FILTER.get().ok_or( Error::NotInitialized )?;
+
+    let mut state = log_filter.current.lock().unwrap_or_else( std::sync::PoisonError::into_inner );
+    let previous = state.apply( &log_filter.handle, directives, ttl, true )?;
+    drop( state );
+
+    tracing::info!(
+        target: AUDIT_TARGET,
+        previous,
+        directives,
+        ttl_secs = ttl.map( |ttl| ttl.as_secs() ),
+        "Log filter changed."
+    );
+
+    current()
+}
+
+/// Restores the initial directives, so the reloaded ones apply again.
+///
+/// # Errors
+///
+/// If the logger was not initialized or the filter could not be reloaded.
+pub fn reset() -> Result<LogFilterState, Error> {
+    let log_filter = LOG_FILTER.get().ok_or( Error::NotInitialized )?;
+
+    let mut state = log_filter.current.lock().unwrap_or_else( std::sync::PoisonError::into_inner );
+    let directives = state.initial_directives.clone();
+    let previous = state.apply( &log_filter.handle, &directives, None, false )?;
+    drop( state );
+
+    tracing::info!( target: AUDIT_TARGET, previous, directives, "Log filter reset." );
+
+    current()
+}
+
+/// Replaces the initial directives, which are also applied unless the directives were changed by [`set`]. In that
+/// case, they are applied once the change reverts or is reset.
+///
+/// # Errors
+///
+/// If the logger was not initialized, the directives are invalid or the filter could not be reloaded.
+pub fn set_initial( directives: &str ) -> Result<LogFilterState, Error> {
+    let log_filter = LOG_FILTER.get().ok_or( Error::NotInitialized )?;
+
+    let mut state = log_filter.current.lock().unwrap_or_else( std::sync::PoisonError::into_inner );
+    let is_applied = !state.is_overridden;
+    if is_applied {
+        state.apply( &log_filter.handle, directives, None, false )?;
+    } else {
+        build_filter( directives )?;
+    }
+    let previous = std::mem::replace( &mut state.initial_directives, directives.to_string() );
+    drop( state );
+
+    tracing::info!( target: AUDIT_TARGET, previous, directives, is_applied, "Initial log filter changed." );
+
+    current()
+}
+
+/// Creates a router serving the log filter at `/log-filter`:
+/// * `GET` returns the [`LogFilterState`].
+/// * `PUT` applies a [`LogFilterChange`].
+/// * `DELETE` restores the initial directives.
+///
+/// The router has no authentication of its own, it must be nested behind the admin authentication of the caller.
+pub fn create_log_filter_route<T>() -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Router::new().route(
+        "/log-filter",
+        get( get_log_filter ).put( put_log_filter ).delete( delete_log_filter ),
+    )
+}
+
+impl IntoResponse for Error {
+    fn into_response( self ) -> Response {
+        let status = match self {
+            Self::InvalidDirectives( _ ) => StatusCode::BAD_REQUEST,
+            Self::NotInitialized => StatusCode::SERVICE_UNAVAILABLE,
+            Self::ReloadFailed( _ ) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        ( status, self.to_string() ).into_response()
+    }
+}
+
+async fn get_log_filter() -> Result<Json<LogFilterState>, Error> { current().map( Json ) }
+
+async fn put_log_filter( Json( change ): Json<LogFilterChange> ) -> Result<Json<LogFilterState>, Error> {
+    set( &change.directives, change.ttl_secs.map( Duration::from_secs ) ).map( Json )
+}
+
+async fn delete_log_filter() -> Result<Json<LogFilterState>, Error> { reset().map( Json ) }
+
+/// Reverts to the initial directives after `ttl`, unless another change happened since the one of the `generation`.
+async fn revert_after( ttl: Duration, generation: u64 ) {
+    tokio::time::sleep( ttl ).await;
+
+    let Some( log_filter ) = LOG_FILTER.get() else {
+        return;
+    };
+    let mut state = log_filter.current.lock().unwrap_or_else( std::sync::PoisonError::into_inner );
+    if state.generation != generation {
+        return;
+    }
+    // The task is finishing, it must not be aborted by the change it applies.
+    state.revert_task = None;
+    let directives = state.initial_directives.clone();
+    let result = state.apply( &log_filter.handle, &directives, None, false );
+    drop( state );
+
+    match result {
+        Ok( previous ) => tracing::info!(
+            target: AUDIT_TARGET,
+            previous,
+            directives,
+            "Log filter time to live elapsed, reverted."
+        ),
+        Err( err ) => tracing::error!( "Failed to revert the log filter: {err}" ),
+    }
+}
+
+/// Parses the directives, adding the one that keeps the audit events enabled.
+fn build_filter( directives: &str ) -> Result<EnvFilter, Error> {
+    EnvFilter::try_new( format!( "{directives},{AUDIT_TARGET}=info" ) )
+        .map_err( |err| Error::InvalidDirectives( err.to_string() ) )
+}
@@ -4,39 +4,94 @@
 //! * stdout
 //! * file
 //! * wasm
+//! * jaeger ( open telemetry traces )
 //!
 //! # Examples
 //!
 //! ```
 //! # use std::path::Path;
-//! use monitoring::logger::{self, Level, EnableLayer};
+//! use monitoring::logger::{self, Level, EnableLayer, StdoutFormat};
+//! use monitoring::rolling_file::{RollingOptions, Rotation};
+//!
+//! let rolling = RollingOptions {
+//!     rotation:      Rotation::Hourly,
+//!     max_file_size: 0,
+//!     max_files:     Some( 24 ),
+//!     max_age:       None,
+//!     is_compressed: true,
+//! };
 //!
 //! // Initialize the logger with the desired options. The guards returned by this function must be
 //! // kept alive for the duration of the program.
 //! let ( _maybe_stdout_writer_guard, _maybe_file_writer_guard  ) = logger::init(
-//!     &Level::INFO,
-//!     &vec![ EnableLayer::Stdout, EnableLayer::File { app_name: "monitoring", directory: Path::new("../../logs"), prefix: "doc.tests"} ],
+//!     &"info".parse::<Level>().unwrap(),
+//!     &vec![ EnableLayer::Stdout( StdoutFormat::Pretty ), EnableLayer::File { app_name: "monitoring", directory: Path::new("../../logs"), prefix: "doc.tests", rolling } ],
 //! );
 //! ```
 
-use std::{error::Error, fmt::Display, path::Path, str::FromStr, time::Duration};
+use std::{
+    error::Error,
+    fmt::Display,
+    future::Future,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs},
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    time::Duration,
+};
+
+use async_trait::async_trait;
 
 use axum::{
     body::{Body, BoxBody, Bytes},
-    http::{HeaderMap, Request, Response},
+    http::{HeaderMap, HeaderName, HeaderValue, Request, Response},
     Router,
 };
+use opentelemetry::{
+    global,
+    propagation::{Extractor, Injector},
+    runtime::{Runtime, RuntimeChannel},
+    sdk::{
+        propagation::TraceContextPropagator,
+        trace::{self as sdk_trace, BatchMessage, Sampler},
+    },
+};
+use opentelemetry_jaeger::JaegerTraceRuntime;
 use serde::{Deserialize, Serialize};
-use tower_http::{classify::ServerErrorsFailureClass, trace as http_trace};
+use tower_http::{
+    classify::ServerErrorsFailureClass,
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace as http_trace,
+};
 use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 pub use tracing_appender::non_blocking::WorkerGuard;
 use tracing_bunyan_formatter::BunyanFormattingLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
-use uuid::Uuid;
+use tracing_subscriber::{
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+use crate::{
+    log_filter,
+    redact::{RedactLayer, RedactedHeaders},
+    rolling_file::{RollingFileAppender, RollingOptions},
+};
+
+/// Header carrying the id of a request across the services it goes through.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
 
 /// Wrapper enum for tracing level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Level( tracing::Level );
 
+impl Level {
+    /// The level as a log filter directive, e.g. `info`.
+    #[must_use]
+    pub fn as_directive( self ) -> String { self.0.as_str().to_lowercase() }
+}
+
 /// Error that can occur when parsing a `Level` from a string.
 #[derive(Debug)]
 pub struct LevelParseError( String );
@@ -84,6 +139,18 @@ impl<'de> Deserialize<'de> for Level {
     }
 }
 
+/// Format of the logs written to stdout. Sensitive fields are redacted in all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde( rename_all = "lowercase" )]
+pub enum StdoutFormat {
+    /// Human readable, over multiple lines.
+    Pretty,
+    /// Human readable, one line per event.
+    Compact,
+    /// One json object per line, e.g. for Loki.
+    Json,
+}
+
 /// Layers that can be enabled for the tracing.
 #[derive(Debug)]
 pub enum EnableLayer<'a> {
@@ -95,19 +162,106 @@ pub enum EnableLayer<'a> {
         directory: &'a Path,
         /// The prefix to use for the file name.
         prefix:    &'a str,
+        /// The rotation, retention and compression of the files.
+        rolling:   RollingOptions,
     },
-    /// Output to stdout.
-    Stdout,
+    /// Output to stdout in the given format.
+    Stdout( StdoutFormat ),
     /// Output to wasm ( console.log ).
     Wasm,
     /// Tokio-console.
     TokioConsole,
-    /// Jaeger with open telemetry. The associated value is the tracer created from opentelemetry_jaeger.
+    /// Jaeger with open telemetry. The associated value is the tracer created from `opentelemetry_jaeger`.
     Jaeger( opentelemetry::sdk::trace::Tracer ),
     /// Enable capture `SpanTraces`.
     SpanTraces,
 }
 
+/// Options to export open telemetry traces to a Jaeger agent.
+#[derive(Debug)]
+pub struct JaegerExportOptions<'a> {
+    /// The service name the spans are reported under.
+    pub service_name:   &'a str,
+    /// The UDP address of the Jaeger agent, e.g. `127.0.0.1:6831`.
+    pub agent_endpoint: &'a str,
+    /// Ratio of the root traces sampled, between 0 and 1. Traces continued from a sampled parent are always sampled.
+    pub sampling_ratio: f64,
+}
+
+/// Creates an open telemetry tracer exporting spans to a Jaeger agent, to be used with [`EnableLayer::Jaeger`].
+///
+/// The spans are exported in batches by a background task, so the tracer must be created within a Tokio runtime. Call
+/// [`shutdown_tracer`] before exiting to flush the spans still pending.
+///
+/// # Errors
+///
+/// If the exporter could not be created, e.g. the agent endpoint is not a valid address.
+pub fn init_jaeger_tracer(
+    options: &JaegerExportOptions,
+) -> Result<opentelemetry::sdk::trace::Tracer, opentelemetry::trace::TraceError> {
+    let sampler = Sampler::ParentBased( Box::new( Sampler::TraceIdRatioBased( options.sampling_ratio ) ) );
+
+    opentelemetry_jaeger::new_agent_pipeline()
+        .with_endpoint( options.agent_endpoint )
+        .with_service_name( options.service_name )
+        .with_trace_config( sdk_trace::config().with_sampler( sampler ) )
+        .install_batch( JaegerTokio( opentelemetry::runtime::Tokio ) )
+}
+
+/// The Tokio runtime of the batch export, with a non-blocking socket to the agent. The socket of the exporter is
+/// blocking, which Tokio refuses to register.
+#[derive(Debug, Clone)]
+struct JaegerTokio( opentelemetry::runtime::Tokio );
+
+impl Runtime for JaegerTokio {
+    type Delay = <opentelemetry::runtime::Tokio as Runtime>::Delay;
+    type Interval = <opentelemetry::runtime::Tokio as Runtime>::Interval;
+
+    fn interval( &self, duration: Duration ) -> Self::Interval { self.0.interval( duration ) }
+
+    fn spawn( &self, future: Pin<Box<dyn Future<Output = ()> + Send + 'static>> ) { self.0.spawn( future ); }
+
+    fn delay( &self, duration: Duration ) -> Self::Delay { self.0.delay( duration ) }
+}
+
+impl RuntimeChannel<BatchMessage> for JaegerTokio {
+    type Receiver = <opentelemetry::runtime::Tokio as RuntimeChannel<BatchMessage>>::Receiver;
+    type Sender = <opentelemetry::runtime::Tokio as RuntimeChannel<BatchMessage>>::Sender;
+
+    fn batch_message_channel( &self, capacity: usize ) -> ( Self::Sender, Self::Receiver ) {
+        self.0.batch_message_channel( capacity )
+    }
+}
+
+#[async_trait]
+impl JaegerTraceRuntime for JaegerTokio {
+    type Socket = tokio::net::UdpSocket;
+
+    fn create_socket<T: ToSocketAddrs>( &self, endpoint: T ) -> thrift::Result<Self::Socket> {
+        let addrs = endpoint.to_socket_addrs()?.collect::<Vec<_>>();
+        let unspecified = match addrs.first() {
+            Some( SocketAddr::V6( _ ) ) => SocketAddr::from( ( Ipv6Addr::UNSPECIFIED, 0 ) ),
+            Some( SocketAddr::V4( _ ) ) | None => SocketAddr::from( ( Ipv4Addr::UNSPECIFIED, 0 ) ),
+        };
+
+        let socket = std::net::UdpSocket::bind( unspecified )?;
+        socket.connect( addrs.as_slice() )?;
+        socket.set_nonblocking( true )?;
+        Ok( tokio::net::UdpSocket::from_std( socket )? )
+    }
+
+    async fn write_to_socket( &self, socket: &Self::Socket, payload: Vec<u8> ) -> thrift::Result<()> {
+        socket.send( &payload ).await?;
+        Ok( () )
+    }
+}
+
+/// Flushes the pending spans and shuts down the global open telemetry tracer provider.
+///
+/// It blocks until the batch of pending spans is exported, so it must not be called from an async runtime thread
+/// that the export task needs, e.g. the one of a current thread runtime.
+pub fn shutdown_tracer() { opentelemetry::global::shutdown_tracer_provider(); }
+
 /// Initializes the logger with the given options.
 ///
 /// # Arguments
@@ -139,20 +293,40 @@ pub fn init( level_filter: &Level, layers_enabled: &Vec<EnableLayer> ) -> ( Opti
     for output_type in layers_enabled {
         match output_type {
             // Write logs to stdout.
-            EnableLayer::Stdout => {
+            EnableLayer::Stdout( format ) => {
                 let ( non_blocking_io_writer, guard ) = tracing_appender::non_blocking( std::io::stdout() );
-                let stdout_layer = tracing_subscriber::fmt::layer().with_writer( non_blocking_io_writer );
+                let stdout_layer = match format {
+                    StdoutFormat::Pretty => {
+                        tracing_subscriber::fmt::layer().pretty().with_writer( non_blocking_io_writer ).boxed()
+                    }
+                    StdoutFormat::Compact => {
+                        tracing_subscriber::fmt::layer().compact().with_writer( non_blocking_io_writer ).boxed()
+                    }
+                    StdoutFormat::Json => {
+                        tracing_subscriber::fmt::layer().json().with_writer( non_blocking_io_writer ).boxed()
+                    }
+                };
 
                 guard_io_writer = Some( guard );
-                layers.push( stdout_layer.boxed() );
+                layers.push( stdout_layer );
             }
             // Write logs to local file.
             EnableLayer::File {
                 app_name,
                 directory,
                 prefix,
+                rolling,
             } => {
-                let file_appender = tracing_appender::rolling::hourly( directory, prefix );
+                let file_appender = match RollingFileAppender::new( directory, prefix, rolling.clone() ) {
+                    Ok( file_appender ) => file_appender,
+                    Err( err ) => {
+                        eprintln!(
+                            "Failed to open the log file in {}, logs will not be written to files: {err}",
+                            directory.display()
+                        );
+                        continue;
+                    }
+                };
                 let ( non_blocking_file_writer, guard ) = tracing_appender::non_blocking( file_appender );
 
                 // TODO: Change into tracing_subscriber::fmt::format::Json when stable.
@@ -188,14 +362,20 @@ pub fn init( level_filter: &Level, layers_enabled: &Vec<EnableLayer> ) -> ( Opti
         }
     }
 
-    // Log level filter.
-    let log_level_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else( |_| tracing_subscriber::EnvFilter::new( level_filter.as_str() ) );
+    // Continue and propagate traces with the W3C trace context ( `traceparent` and `tracestate` headers ).
+    global::set_text_map_propagator( TraceContextPropagator::new() );
+
+    // Log level filter, from the env variable if it is valid. It can be changed at runtime through the log filter.
+    let initial_directives = std::env::var( tracing_subscriber::EnvFilter::DEFAULT_ENV )
+        .ok()
+        .filter( |directives| tracing_subscriber::EnvFilter::try_new( directives ).is_ok() )
+        .unwrap_or_else( || level_filter.as_str().to_lowercase() );
+    let log_level_filter = log_filter::create_layer( &initial_directives );
 
-    // Register layers to registry.
+    // Register layers to registry. The sensitive fields are redacted once, before any layer records them.
     tracing_subscriber::Registry::default()
-        .with( layers )
         .with( log_level_filter )
+        .with( RedactLayer( layers ) )
         .init();
 
     tracing::info!(
@@ -208,6 +388,9 @@ pub fn init( level_filter: &Level, layers_enabled: &Vec<EnableLayer> ) -> ( Opti
 
 /// Adds tracing instrumentation to the given router. This will add tracing to all http requests and responses.
 ///
+/// The request id is taken from the `x-request-id` header, or generated if the header is missing, and echoed back in
+/// the response headers. The span continues the trace of the `traceparent` header if there is one.
+///
 /// # Arguments
 ///
 /// * `router` - The router to add the instrumentation to.
@@ -220,12 +403,28 @@ where
     T: Clone + Send + Sync + 'static,
 {
     let trace_layer = http_trace::TraceLayer::new_for_http()
-        .make_span_with( |_request: &Request<Body>| {
-            let request_id = Uuid::new_v4().to_string();
-            tracing::info_span!("HTTP", %request_id)
+        .make_span_with( |request: &Request<Body>| {
+            let request_id = request
+                .headers()
+                .get( REQUEST_ID_HEADER )
+                .and_then( |value| value.to_str().ok() )
+                .unwrap_or_default();
+            let span = tracing::info_span!("HTTP", %request_id);
+
+            let parent_context = global::get_text_map_propagator( |propagator| {
+                propagator.extract( &HeaderExtractor( request.headers() ) )
+            } );
+            span.set_parent( parent_context );
+
+            span
         } )
         .on_request( |request: &Request<Body>, _span: &Span| {
-            tracing::debug!( "REQUEST{{method={}, path={}}}", request.method(), request.uri().path() );
+            tracing::debug!(
+                "REQUEST{{method={}, path={}, headers={:?}}}",
+                request.method(),
+                request.uri().path(),
+                RedactedHeaders( request.headers() )
+            );
         } )
         .on_response( |response: &Response<BoxBody>, latency: Duration, _span: &Span| {
             tracing::debug!( "RESPONSE{{status={}, latency={:?}}}", response.status(), latency );
@@ -242,5 +441,47 @@ where
             tracing::error!( "ERROR{{{}}}", error );
         } );
 
-    router.layer( trace_layer )
+    let request_id_header = HeaderName::from_static( REQUEST_ID_HEADER );
+
+    // The layers added last run first: the request id is set before the span is created and echoed after.
+    router
+        .layer( trace_layer )
+        .layer( PropagateRequestIdLayer::new( request_id_header.clone() ) )
+        .layer( SetRequestIdLayer::new( request_id_header, MakeRequestUuid ) )
+}
+
+/// Injects the trace context of the current span into the headers of an outgoing request, so the receiving service
+/// continues the same trace.
+///
+/// # Arguments
+///
+/// * `headers` - The headers of the outgoing request.
+pub fn inject_trace_context( headers: &mut HeaderMap ) {
+    let context = Span::current().context();
+    global::get_text_map_propagator( |propagator| {
+        propagator.inject_context( &context, &mut HeaderInjector( headers ) );
+    } );
+}
+
+/// Reads the trace context from http headers.
+struct HeaderExtractor<'a>( &'a HeaderMap );
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get( &self, key: &str ) -> Option<&str> { self.0.get( key ).and_then( |value| value.to_str().ok() ) }
+
+    fn keys( &self ) -> Vec<&str> { self.0.keys().map( HeaderName::as_str ).collect() }
+}
+
+/// Writes the trace context to http headers.
+struct HeaderInjector<'a>( &'a mut HeaderMap );
+
+impl Injector for HeaderInjector<'_> {
+    fn set( &mut self, key: &str, value: String ) {
+        let name = HeaderName::from_bytes( key.as_bytes() );
+        let value = HeaderValue::from_str( &value );
+
+        if let ( Ok( name ), Ok( value ) ) = ( name, value ) {
+            self.0.insert( name, value );
+        }
+    }
 }
@@ -1,10 +1,20 @@
 //! Provides metrics collection for prometheus.
 //!
-//! Provides a set of metrics that can be used to collect data for prometheus, as well as a function
-//! to add them to a registry.
+//! Provides a set of metrics that can be used to collect data for prometheus, a middleware that records the RED
+//! ( rate, errors, duration ) metrics of every http route, and a router that serves them.
 //!
 //! # Examples
 //! ```
+//! use axum::{routing::get, Router};
+//! use monitoring::prometheus;
+//!
+//! // Record the metrics of every route and serve them at `/metrics`.
+//! let app: Router = Router::new().route( "/photos/:id", get( || async { "photo" } ) );
+//! let app = prometheus::middleware_http_metrics( app ).merge( prometheus::create_metrics_route() );
+//! ```
+//!
+//! Metrics can also be added to a custom registry:
+//! ```
 //! use lazy_static::lazy_static;
 //! use monitoring::prometheus;
 //! use monitoring::prometheus::metrics;
@@ -25,26 +35,65 @@
 //! );
 //! ```
 
-use ::prometheus::{core::Collector, Registry};
+use std::time::Instant;
+
+use ::prometheus::{core::Collector, Encoder, Registry, TextEncoder};
+use axum::{
+    extract::MatchedPath,
+    http::{header, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+
+/// Route label used for requests that did not match any route template, so unknown paths do not create new series.
+const UNMATCHED_ROUTE: &str = "unmatched";
 
 /// Metrics that can be used to collect data for prometheus.
 pub mod metrics {
     use lazy_static::lazy_static;
-    use prometheus::{HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts};
+    use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry};
 
     lazy_static! {
-        pub static ref INCOMING_REQUESTS: IntCounter =
-            IntCounter::new( "incoming_requests", "Incoming Requests" ).expect( "metric can't be created" );
+        pub static ref INCOMING_REQUESTS: IntCounterVec = IntCounterVec::new(
+            Opts::new( "incoming_requests", "Incoming Requests" ),
+            &["method", "route"]
+        )
+        .expect( "metric can't be created" );
         pub static ref CONNECTED_CLIENTS: IntGauge =
             IntGauge::new( "connected_clients", "Connected Clients" ).expect( "metric can't be created" );
         pub static ref RESPONSE_CODE_COLLECTOR: IntCounterVec = IntCounterVec::new(
             Opts::new( "response_code", "Response Codes" ),
-            &["env", "statuscode", "type"]
+            &["method", "route", "statuscode", "type"]
         )
         .expect( "metric can't be created" );
-        pub static ref RESPONSE_TIME_COLLECTOR: HistogramVec =
-            HistogramVec::new( HistogramOpts::new( "response_time", "Response Times" ), &["env"] )
-                .expect( "metric can't be created" );
+        pub static ref RESPONSE_TIME_COLLECTOR: HistogramVec = HistogramVec::new(
+            HistogramOpts::new( "response_time", "Response Times in seconds" ),
+            &["method", "route"]
+        )
+        .expect( "metric can't be created" );
+        pub static ref HEALTH_CHECK_STATUS: IntGaugeVec = IntGaugeVec::new(
+            Opts::new( "health_check_status", "Health Check Status ( 1 pass, 0 fail )" ),
+            &["check"]
+        )
+        .expect( "metric can't be created" );
+
+        /// Registry with all the metrics above, served by [`create_metrics_route`](super::create_metrics_route).
+        pub static ref REGISTRY: Registry = {
+            let registry = Registry::new();
+            super::add_metrics_to_registry(
+                &registry,
+                vec![
+                    Box::new( INCOMING_REQUESTS.clone() ),
+                    Box::new( CONNECTED_CLIENTS.clone() ),
+                    Box::new( RESPONSE_CODE_COLLECTOR.clone() ),
+                    Box::new( RESPONSE_TIME_COLLECTOR.clone() ),
+                    Box::new( HEALTH_CHECK_STATUS.clone() ),
+                ],
+            );
+            registry
+        };
     }
 }
 
@@ -67,3 +116,74 @@ pub fn add_metrics_to_registry( registry: &Registry, metrics: Vec<Box<dyn Collec
         registry.register( metric ).expect( "metric can't be registered" );
     }
 }
+
+/// Adds the RED metrics instrumentation to the given router. Requests are labelled by method and by the matched route
+/// template ( e.g. `/photos/:id` ) instead of the raw path.
+///
+/// # Arguments
+///
+/// * `router` - The router to add the instrumentation to.
+///
+/// # Returns
+///
+/// The router with the instrumentation added.
+pub fn middleware_http_metrics<T>( router: Router<T> ) -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    router.layer( middleware::from_fn( record_http_metrics ) )
+}
+
+/// Creates a router serving the metrics of [`metrics::REGISTRY`] at `/metrics` in the prometheus text format.
+pub fn create_metrics_route<T>() -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Router::new().route( "/metrics", get( metrics_handler ) )
+}
+
+async fn record_http_metrics<B>( request: Request<B>, next: Next<B> ) -> Response {
+    let start = Instant::now();
+
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else( || UNMATCHED_ROUTE.to_string(), |path| path.as_str().to_string() );
+
+    metrics::INCOMING_REQUESTS.with_label_values( &[&method, &route] ).inc();
+
+    let response = next.run( request ).await;
+
+    let status = response.status();
+    metrics::RESPONSE_CODE_COLLECTOR
+        .with_label_values( &[&method, &route, status.as_str(), status_class( status )] )
+        .inc();
+    metrics::RESPONSE_TIME_COLLECTOR
+        .with_label_values( &[&method, &route] )
+        .observe( start.elapsed().as_secs_f64() );
+
+    response
+}
+
+async fn metrics_handler() -> Response {
+    let mut buffer = Vec::new();
+
+    match TextEncoder::new().encode( &metrics::REGISTRY.gather(), &mut buffer ) {
+        Ok( () ) => ( [( header::CONTENT_TYPE, ::prometheus::TEXT_FORMAT )], buffer ).into_response(),
+        Err( err ) => {
+            tracing::error!( "Failed to encode the prometheus metrics: {err}" );
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn status_class( status: StatusCode ) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
@@ -0,0 +1,360 @@
+//! Log files rotated by time or size.
+//!
+//! The files are named `<prefix>.<date>`, where the date has the precision of the rotation ( e.g.
+//! `backend.2023-05-01-13` when rotating hourly, or `backend.2023-05-01-13-45-10` when rotating by size ). A rotated
+//! file can be compressed into `<name>.gz` once the next one is rotated in turn, so the log shippers still reading it
+//! have a rotation period to finish. The oldest files are removed past the maximum number of files or the maximum age.
+//! Only the files named after the prefix and a date are compressed or removed.
+//!
+//! The compression and the removal run on a thread of the appender, so they don't hold up the writes. The pending ones
+//! finish when the appender is dropped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::{io::Write, path::Path};
+//! use monitoring::rolling_file::{RollingFileAppender, RollingOptions, Rotation};
+//!
+//! let options = RollingOptions {
+//!     rotation:      Rotation::Size,
+//!     max_file_size: 10 * 1024 * 1024,
+//!     max_files:     Some( 10 ),
+//!     max_age:       None,
+//!     is_compressed: true,
+//! };
+//! let mut appender = RollingFileAppender::new( Path::new( "./logs" ), "backend", options ).unwrap();
+//! appender.write_all( b"log line\n" ).unwrap();
+//! ```
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, SyncSender},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use time::{format_description::FormatItem, macros::format_description, parsing::Parsed, OffsetDateTime};
+
+/// Extension of the compressed files.
+const COMPRESSED_EXTENSION: &str = "gz";
+
+/// When the log file is rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde( rename_all = "lowercase" )]
+pub enum Rotation {
+    Minutely,
+    Hourly,
+    Daily,
+    /// Once the file reaches [`RollingOptions::max_file_size`].
+    Size,
+}
+
+impl Rotation {
+    const ALL: [Self; 4] = [Self::Minutely, Self::Hourly, Self::Daily, Self::Size];
+
+    const fn date_format( self ) -> &'static [FormatItem<'static>] {
+        match self {
+            Self::Minutely => format_description!( "[year]-[month]-[day]-[hour]-[minute]" ),
+            Self::Hourly => format_description!( "[year]-[month]-[day]-[hour]" ),
+            Self::Daily => format_description!( "[year]-[month]-[day]" ),
+            Self::Size => format_description!( "[year]-[month]-[day]-[hour]-[minute]-[second]" ),
+        }
+    }
+}
+
+/// Rotation, retention and compression of the log files.
+#[derive(Debug, Clone)]
+pub struct RollingOptions {
+    pub rotation:      Rotation,
+    /// Size in bytes a file can reach before being rotated, only used by [`Rotation::Size`].
+    pub max_file_size: u64,
+    /// Maximum number of rotated files kept, the current one excluded.
+    pub max_files:     Option<usize>,
+    /// Maximum age of the rotated files kept.
+    pub max_age:       Option<Duration>,
+    /// Whether the rotated files are compressed with gzip.
+    pub is_compressed: bool,
+}
+
+/// Writer to log files rotated according to the [`RollingOptions`].
+#[derive(Debug)]
+pub struct RollingFileAppender {
+    log_files:       Arc<LogFiles>,
+    file:            BufWriter<File>,
+    path:            PathBuf,
+    /// Date of the current file with the precision of the rotation.
+    date:            String,
+    size:            u64,
+    /// Requests a cleanup of the rotated files, unless one is already pending.
+    cleanup_request: Option<SyncSender<()>>,
+    cleanup_thread:  Option<JoinHandle<()>>,
+}
+
+/// The log files of an appender, shared with its cleanup thread.
+#[derive(Debug)]
+struct LogFiles {
+    directory:    PathBuf,
+    prefix:       String,
+    options:      RollingOptions,
+    /// The file being written, which is never cleaned up.
+    current_path: Mutex<PathBuf>,
+}
+
+impl RollingFileAppender {
+    /// Opens the log file of the current period, or a new one when rotating by size, and cleans up the files left by
+    /// previous runs.
+    ///
+    /// # Errors
+    ///
+    /// If the directory or the log file could not be created, or the cleanup thread could not be spawned.
+    pub fn new( directory: &Path, prefix: &str, options: RollingOptions ) -> io::Result<Self> {
+        fs::create_dir_all( directory )?;
+
+        let date = format_date( options.rotation, OffsetDateTime::now_utc() );
+        let path = file_path( directory, prefix, &date, options.rotation )?;
+        let ( file, size ) = open_file( &path )?;
+
+        let log_files = Arc::new( LogFiles {
+            directory:    directory.to_path_buf(),
+            prefix:       prefix.to_string(),
+            options,
+            current_path: Mutex::new( path.clone() ),
+        } );
+
+        // A single pending request is enough, as a cleanup covers all the rotated files.
+        let ( cleanup_request, cleanup_requests ) = mpsc::sync_channel( 1 );
+        let cleanup_log_files = log_files.clone();
+        let cleanup_thread = thread::Builder::new().name( format!( "{prefix}-log-cleanup" ) ).spawn( move || {
+            while cleanup_requests.recv().is_ok() {
+                cleanup_log_files.clean_up();
+            }
+        } )?;
+
+        let appender = Self {
+            log_files,
+            file,
+            path,
+            date,
+            size,
+            cleanup_request: Some( cleanup_request ),
+            cleanup_thread:  Some( cleanup_thread ),
+        };
+        appender.request_cleanup();
+
+        Ok( appender )
+    }
+
+    fn should_rotate( &self, now: OffsetDateTime, incoming_size: usize ) -> bool {
+        match self.log_files.options.rotation {
+            Rotation::Size => self.size > 0 && self.size + incoming_size as u64 > self.log_files.options.max_file_size,
+            rotation => format_date( rotation, now ) != self.date,
+        }
+    }
+
+    fn rotate( &mut self, now: OffsetDateTime ) -> io::Result<()> {
+        self.file.flush()?;
+
+        let LogFiles { directory, prefix, options, .. } = &*self.log_files;
+        let date = format_date( options.rotation, now );
+        let path = file_path( directory, prefix, &date, options.rotation )?;
+        let ( file, size ) = open_file( &path )?;
+
+        self.log_files.current_path.lock().unwrap_or_else( PoisonError::into_inner ).clone_from( &path );
+        self.file = file;
+        self.path = path;
+        self.date = date;
+        self.size = size;
+
+        self.request_cleanup();
+
+        Ok( () )
+    }
+
+    /// Wakes up the cleanup thread, unless a cleanup is already pending.
+    fn request_cleanup( &self ) {
+        if let Some( cleanup_request ) = &self.cleanup_request {
+            let _ = cleanup_request.try_send( () );
+        }
+    }
+}
+
+impl Drop for RollingFileAppender {
+    fn drop( &mut self ) {
+        // Closing the channel stops the cleanup thread once the pending cleanup is done.
+        self.cleanup_request = None;
+        if let Some( cleanup_thread ) = self.cleanup_thread.take() {
+            let _ = cleanup_thread.join();
+        }
+    }
+}
+
+impl LogFiles {
+    fn clean_up( &self ) {
+        if self.options.is_compressed {
+            self.compress_rotated_files();
+        }
+        self.remove_expired_files();
+    }
+
+    /// Log files of this appender other than the current one, with their modification time, the newest first.
+    fn rotated_files( &self ) -> Vec<( PathBuf, SystemTime )> {
+        let current_path = self.current_path.lock().unwrap_or_else( PoisonError::into_inner ).clone();
+        let mut files: Vec<( PathBuf, SystemTime )> = fs::read_dir( &self.directory )
+            .map( |entries| {
+                entries
+                    .filter_map( Result::ok )
+                    .map( |entry| entry.path() )
+                    .filter( |path| path.is_file() && *path != current_path )
+                    .filter( |path| {
+                        path.file_name()
+                            .and_then( |name| name.to_str() )
+                            .is_some_and( |name| is_log_file_name( &self.prefix, name ) )
+                    } )
+                    .filter_map( |path| {
+                        let modified = path.metadata().and_then( |metadata| metadata.modified() ).ok()?;
+                        Some( ( path, modified ) )
+                    } )
+                    .collect()
+            } )
+            .unwrap_or_default();
+
+        // The names hold the creation date, so they break the ties of files modified at the same time.
+        files.sort_by( |( a_path, a ), ( b_path, b )| b.cmp( a ).then_with( || b_path.cmp( a_path ) ) );
+        files
+    }
+
+    /// Compresses the rotated files, except the newest one which may still be read by a log shipper.
+    fn compress_rotated_files( &self ) {
+        let uncompressed_files = self
+            .rotated_files()
+            .into_iter()
+            .map( |( path, _ )| path )
+            .filter( |path| path.extension().and_then( |ext| ext.to_str() ) != Some( COMPRESSED_EXTENSION ) )
+            .skip( 1 );
+
+        for path in uncompressed_files {
+            if let Err( err ) = compress_file( &path ) {
+                eprintln!( "Failed to compress the log file {}: {err}", path.display() );
+            }
+        }
+    }
+
+    /// Removes the rotated files past the maximum number of files or older than the maximum age.
+    fn remove_expired_files( &self ) {
+        if self.options.max_files.is_none() && self.options.max_age.is_none() {
+            return;
+        }
+
+        let now = SystemTime::now();
+        for ( index, ( path, modified ) ) in self.rotated_files().into_iter().enumerate() {
+            let is_over_max_files = self.options.max_files.is_some_and( |max_files| index >= max_files );
+            let is_over_max_age = self.options.max_age.is_some_and( |max_age| {
+                now.duration_since( modified ).is_ok_and( |age| age > max_age )
+            } );
+
+            if is_over_max_files || is_over_max_age {
+                if let Err( err ) = fs::remove_file( &path ) {
+                    eprintln!( "Failed to remove the log file {}: {err}", path.display() );
+                }
+            }
+        }
+    }
+}
+
+impl Write for RollingFileAppender {
+    fn write( &mut self, buf: &[u8] ) -> io::Result<usize> {
+        let now = OffsetDateTime::now_utc();
+
+        // Keep writing to the current file if the new one can't be opened, rather than losing the logs.
+        if self.should_rotate( now, buf.len() ) {
+            if let Err( err ) = self.rotate( now ) {
+                eprintln!( "Failed to rotate the log file {}: {err}", self.path.display() );
+            }
+        }
+
+        let written = self.file.write( buf )?;
+        self.size += written as u64;
+
+        Ok( written )
+    }
+
+    fn flush( &mut self ) -> io::Result<()> { self.file.flush() }
+}
+
+fn format_date( rotation: Rotation, now: OffsetDateTime ) -> String {
+    now.format( rotation.date_format() ).unwrap_or_default()
+}
+
+/// Whether the file is named `<prefix>.<date>`, with the date of any rotation, followed by the counter of the files
+/// rotated by size and the extension of the compressed files if any. Other files sharing the prefix, e.g. the ones of
+/// `<prefix>.worker`, are not the appender's.
+fn is_log_file_name( prefix: &str, name: &str ) -> bool {
+    let Some( suffix ) = name.strip_prefix( prefix ).and_then( |suffix| suffix.strip_prefix( '.' ) ) else {
+        return false;
+    };
+    let suffix = suffix
+        .strip_suffix( COMPRESSED_EXTENSION )
+        .and_then( |suffix| suffix.strip_suffix( '.' ) )
+        .unwrap_or( suffix );
+
+    Rotation::ALL.iter().any( |rotation| {
+        let Ok( rest ) = Parsed::new().parse_items( suffix.as_bytes(), rotation.date_format() ) else {
+            return false;
+        };
+
+        match rest {
+            [] => true,
+            [b'.', counter @ ..] => !counter.is_empty() && counter.iter().all( u8::is_ascii_digit ),
+            _ => false,
+        }
+    } )
+}
+
+/// The path of the file for the date. Files rotated by size get a counter when several are created within a second.
+fn file_path( directory: &Path, prefix: &str, date: &str, rotation: Rotation ) -> io::Result<PathBuf> {
+    let path = directory.join( format!( "{prefix}.{date}" ) );
+
+    if rotation != Rotation::Size || !is_taken( &path ) {
+        return Ok( path );
+    }
+
+    ( 1..=u16::MAX )
+        .map( |counter| directory.join( format!( "{prefix}.{date}.{counter}" ) ) )
+        .find( |path| !is_taken( path ) )
+        .ok_or_else( || {
+            io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!( "All the log files of {} were created within the same second.", path.display() ),
+            )
+        } )
+}
+
+/// Whether the file exists, compressed or not.
+fn is_taken( path: &Path ) -> bool { path.exists() || compressed_path( path ).exists() }
+
+fn compressed_path( path: &Path ) -> PathBuf { PathBuf::from( format!( "{}.{COMPRESSED_EXTENSION}", path.display() ) ) }
+
+fn open_file( path: &Path ) -> io::Result<( BufWriter<File>, u64 )> {
+    let file = OpenOptions::new().create( true ).append( true ).open( path )?;
+    let size = file.metadata()?.len();
+
+    Ok( ( BufWriter::new( file ), size ) )
+}
+
+/// Compresses the file into `<name>.gz` and removes the original.
+fn compress_file( path: &Path ) -> io::Result<()> {
+    let mut reader = BufReader::new( File::open( path )? );
+    let compressed_file = BufWriter::new( File::create( compressed_path( path ) )? );
+    let mut encoder = GzEncoder::new( compressed_file, Compression::default() );
+    io::copy( &mut reader, &mut encoder )?;
+    encoder.finish()?.flush()?;
+
+    fs::remove_file( path )
+}
@@ -0,0 +1,29 @@
+use std::{net::UdpSocket, time::Duration};
+
+use monitoring::logger::{self, JaegerExportOptions};
+use opentelemetry::trace::Tracer;
+
+/// Uses a UDP socket as a stand-in for the Jaeger agent and checks the spans reach it once the tracer is shut down.
+#[tokio::test( flavor = "multi_thread" )]
+async fn spans_are_exported_to_jaeger_agent() {
+    let agent = UdpSocket::bind( "127.0.0.1:0" ).unwrap();
+    agent.set_read_timeout( Some( Duration::from_secs( 5 ) ) ).unwrap();
+    let agent_endpoint = agent.local_addr().unwrap().to_string();
+
+    let tracer = logger::init_jaeger_tracer( &JaegerExportOptions {
+        service_name:   "monitoring-tests",
+        agent_endpoint: &agent_endpoint,
+        sampling_ratio: 1.0,
+    } )
+    .unwrap();
+
+    tracer.in_span( "exported-test-span", |_cx| {} );
+    logger::shutdown_tracer();
+
+    let mut buffer = [0; 65_507];
+    let received = agent.recv( &mut buffer ).expect( "The Jaeger agent stand-in did not receive any span." );
+    let payload = &buffer[..received];
+
+    assert!( payload.windows( b"monitoring-tests".len() ).any( |window| window == b"monitoring-tests" ) );
+    assert!( payload.windows( b"exported-test-span".len() ).any( |window| window == b"exported-test-span" ) );
+}
@@ -0,0 +1,39 @@
+use std::{net::UdpSocket, time::Duration};
+
+use monitoring::logger::{self, EnableLayer, JaegerExportOptions, Level};
+
+/// Checks the `tracing` spans reach the Jaeger agent stand-in through the subscriber configured by the logger, once the
+/// tracer is shut down.
+#[tokio::test( flavor = "multi_thread" )]
+async fn tracing_spans_are_exported_through_the_subscriber() {
+    let agent = UdpSocket::bind( "127.0.0.1:0" ).unwrap();
+    agent.set_read_timeout( Some( Duration::from_secs( 5 ) ) ).unwrap();
+    let agent_endpoint = agent.local_addr().unwrap().to_string();
+
+    let tracer = logger::init_jaeger_tracer( &JaegerExportOptions {
+        service_name:   "monitoring-subscriber-tests",
+        agent_endpoint: &agent_endpoint,
+        sampling_ratio: 1.0,
+    } )
+    .unwrap();
+    let _guards = logger::init( &"info".parse::<Level>().unwrap(), &vec![EnableLayer::Jaeger( tracer )] );
+
+    let span = tracing::info_span!( "subscriber-test-span", password = "hunter2" );
+    span.in_scope( || tracing::info!( "Inside the span." ) );
+    drop( span );
+    // Filtered out by the level of the logger.
+    tracing::debug_span!( "filtered-test-span" ).in_scope( || {} );
+    logger::shutdown_tracer();
+
+    let mut buffer = [0; 65_507];
+    let received = agent.recv( &mut buffer ).expect( "The Jaeger agent stand-in did not receive any span." );
+    let payload = &buffer[..received];
+
+    let contains = |needle: &[u8]| payload.windows( needle.len() ).any( |window| window == needle );
+    assert!( contains( b"monitoring-subscriber-tests" ) );
+    assert!( contains( b"subscriber-test-span" ) );
+    assert!( !contains( b"filtered-test-span" ) );
+    // Redacted before the tracer records the span.
+    assert!( contains( b"[REDACTED]" ) );
+    assert!( !contains( b"hunter2" ) );
+}
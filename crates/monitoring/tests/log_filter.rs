@@ -0,0 +1,70 @@
+use std::{str::FromStr, time::Duration};
+
+use monitoring::{
+    log_filter,
+    logger::{self, Level},
+};
+
+/// The logger is global to the process, so the whole lifecycle is checked in a single test.
+#[tokio::test]
+async fn log_filter_changes_and_reverts_at_runtime() {
+    let _guards = logger::init( &Level::from_str( "info" ).unwrap(), &vec![logger::EnableLayer::SpanTraces] );
+
+    assert!( tracing::enabled!( tracing::Level::INFO ) );
+    assert!( !tracing::enabled!( tracing::Level::DEBUG ) );
+
+    // Invalid directives are rejected and leave the filter untouched.
+    assert!( matches!(
+        log_filter::set( "monitoring=not-a-level", None ),
+        Err( log_filter::Error::InvalidDirectives( _ ) )
+    ) );
+    assert_eq!( log_filter::current().unwrap().directives, "info" );
+
+    // A permanent change.
+    let state = log_filter::set( "debug", None ).unwrap();
+    assert_eq!( state.directives, "debug" );
+    assert_eq!( state.reverts_in_secs, None );
+    assert!( tracing::enabled!( tracing::Level::DEBUG ) );
+
+    // A change with a time to live reverts to the initial directives.
+    let state = log_filter::set( "log_filter=trace,warn", Some( Duration::from_secs( 1 ) ) ).unwrap();
+    assert_eq!( state.directives, "log_filter=trace,warn" );
+    assert!( state.reverts_in_secs.is_some() );
+    assert!( tracing::enabled!( tracing::Level::TRACE ) );
+
+    tokio::time::sleep( Duration::from_millis( 1500 ) ).await;
+
+    let state = log_filter::current().unwrap();
+    assert_eq!( state.directives, "info" );
+    assert_eq!( state.reverts_in_secs, None );
+    assert!( !tracing::enabled!( tracing::Level::DEBUG ) );
+
+    // The initial directives follow the reloaded settings.
+    let state = log_filter::set_initial( "debug" ).unwrap();
+    assert_eq!( state.directives, "debug" );
+    assert_eq!( state.initial_directives, "debug" );
+    assert!( tracing::enabled!( tracing::Level::DEBUG ) );
+
+    // A permanent change overrides the reloaded initial directives until it is reset.
+    log_filter::set( "warn", None ).unwrap();
+    let state = log_filter::set_initial( "trace" ).unwrap();
+    assert_eq!( state.directives, "warn" );
+    assert_eq!( state.initial_directives, "trace" );
+    assert!( !tracing::enabled!( tracing::Level::INFO ) );
+
+    let state = log_filter::reset().unwrap();
+    assert_eq!( state.directives, "trace" );
+    assert!( tracing::enabled!( tracing::Level::TRACE ) );
+
+    // Once reverted, a change with a time to live applies the initial directives reloaded meanwhile.
+    log_filter::set( "warn", Some( Duration::from_secs( 1 ) ) ).unwrap();
+    let state = log_filter::set_initial( "info" ).unwrap();
+    assert_eq!( state.directives, "warn" );
+
+    tokio::time::sleep( Duration::from_millis( 1500 ) ).await;
+
+    let state = log_filter::current().unwrap();
+    assert_eq!( state.directives, "info" );
+    assert!( tracing::enabled!( tracing::Level::INFO ) );
+    assert!( !tracing::enabled!( tracing::Level::DEBUG ) );
+}
@@ -0,0 +1,117 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use monitoring::redact::{self, RedactLayer, REDACTED};
+use tracing_subscriber::{fmt::MakeWriter, layer::SubscriberExt};
+
+/// Writer collecting the logs in memory.
+#[derive(Clone, Default)]
+struct Buffer( Arc<Mutex<Vec<u8>>> );
+
+impl Buffer {
+    fn contents( &self ) -> String { String::from_utf8( self.0.lock().unwrap().clone() ).unwrap() }
+}
+
+impl Write for Buffer {
+    fn write( &mut self, buf: &[u8] ) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice( buf );
+        Ok( buf.len() )
+    }
+
+    fn flush( &mut self ) -> std::io::Result<()> { Ok( () ) }
+}
+
+impl<'a> MakeWriter<'a> for Buffer {
+    type Writer = Self;
+
+    fn make_writer( &'a self ) -> Self::Writer { self.clone() }
+}
+
+#[test]
+fn url_credentials_are_masked() {
+    assert_eq!(
+        redact::redact_url_credentials( "connecting to postgres://app:s3cret@db:5432/photos failed" ),
+        "connecting to postgres://app:[REDACTED]@db:5432/photos failed"
+    );
+    assert_eq!(
+        redact::redact_url_credentials( "http://localhost:5555/health" ),
+        "http://localhost:5555/health"
+    );
+}
+
+#[test]
+fn sensitive_names_are_detected() {
+    assert!( redact::is_sensitive( "Authorization" ) );
+    assert!( redact::is_sensitive( "set-cookie" ) );
+    assert!( redact::is_sensitive( "admin_token" ) );
+    assert!( redact::is_sensitive( "x-api-key" ) );
+    assert!( !redact::is_sensitive( "request_id" ) );
+}
+
+#[test]
+fn text_fields_are_redacted() {
+    let buffer = Buffer::default();
+    let subscriber = tracing_subscriber::registry()
+        .with( RedactLayer( tracing_subscriber::fmt::layer().with_writer( buffer.clone() ) ) );
+
+    tracing::subscriber::with_default( subscriber, || {
+        tracing::info!(
+            password = "hunter2",
+            url = "postgres://app:s3cret@db/photos",
+            user = "alice",
+            "Logged in."
+        );
+    } );
+
+    let logs = buffer.contents();
+    assert!( logs.contains( REDACTED ) );
+    assert!( logs.contains( "postgres://app:[REDACTED]@db/photos" ) );
+    assert!( logs.contains( "alice" ) );
+    assert!( logs.contains( "Logged in." ) );
+    assert!( !logs.contains( "hunter2" ) );
+    assert!( !logs.contains( "s3cret" ) );
+}
+
+#[test]
+fn json_fields_are_redacted() {
+    let buffer = Buffer::default();
+    let subscriber = tracing_subscriber::registry()
+        .with( RedactLayer( tracing_subscriber::fmt::layer().json().with_writer( buffer.clone() ) ) );
+
+    tracing::subscriber::with_default( subscriber, || {
+        tracing::info!( token = "abc123", url = "redis://:s3cret@cache:6379", attempts = 2, "Connected." );
+    } );
+
+    let logs = buffer.contents();
+    let line: serde_json::Value = serde_json::from_str( logs.trim() ).unwrap();
+    assert_eq!( line["fields"]["token"], REDACTED );
+    assert_eq!( line["fields"]["url"], "redis://:[REDACTED]@cache:6379" );
+    assert_eq!( line["fields"]["attempts"], 2 );
+    assert_eq!( line["fields"]["message"], "Connected." );
+}
+
+#[test]
+fn span_fields_are_redacted_when_created_and_recorded() {
+    let buffer = Buffer::default();
+    let subscriber = tracing_subscriber::registry().with( RedactLayer(
+        tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span( true )
+            .with_writer( buffer.clone() ),
+    ) );
+
+    tracing::subscriber::with_default( subscriber, || {
+        let span = tracing::info_span!( "login", api_key = "k3y", user = tracing::field::Empty );
+        span.record( "user", "postgres://alice:s3cret@db" );
+        span.in_scope( || tracing::info!( "Inside the span." ) );
+    } );
+
+    let logs = buffer.contents();
+    let line: serde_json::Value = serde_json::from_str( logs.trim() ).unwrap();
+    assert_eq!( line["span"]["api_key"], REDACTED );
+    assert_eq!( line["span"]["user"], "postgres://alice:[REDACTED]@db" );
+    assert!( !logs.contains( "k3y" ) );
+    assert!( !logs.contains( "s3cret" ) );
+}
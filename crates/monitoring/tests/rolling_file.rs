@@ -0,0 +1,134 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    path::PathBuf,
+};
+
+use flate2::read::GzDecoder;
+use monitoring::rolling_file::{RollingFileAppender, RollingOptions, Rotation};
+
+fn create_logs_dir( name: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "monitoring-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    dir
+}
+
+fn list_files( dir: &PathBuf ) -> Vec<String> {
+    let mut files: Vec<String> = fs::read_dir( dir )
+        .unwrap()
+        .map( |entry| entry.unwrap().file_name().into_string().unwrap() )
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn files_rotate_by_size_and_are_compressed_one_rotation_later() {
+    let dir = create_logs_dir( "size-rotation" );
+    let options = RollingOptions {
+        rotation:      Rotation::Size,
+        max_file_size: 16,
+        max_files:     None,
+        max_age:       None,
+        is_compressed: true,
+    };
+    let mut appender = RollingFileAppender::new( &dir, "app", options ).unwrap();
+
+    appender.write_all( b"first log line\n" ).unwrap();
+    appender.write_all( b"second log line\n" ).unwrap();
+    appender.flush().unwrap();
+
+    // The rotated file is left for the log shippers still reading it.
+    let files = list_files( &dir );
+    assert_eq!( files.len(), 2 );
+    assert!( files.iter().all( |name| !name.ends_with( ".gz" ) ) );
+
+    appender.write_all( b"third log line\n" ).unwrap();
+    // Waits for the compression, done by the thread of the appender.
+    drop( appender );
+
+    let files = list_files( &dir );
+    assert_eq!( files.len(), 3 );
+    let compressed_files: Vec<&String> = files.iter().filter( |name| name.ends_with( ".gz" ) ).collect();
+    assert_eq!( compressed_files.len(), 1 );
+
+    let mut decompressed = String::new();
+    GzDecoder::new( fs::File::open( dir.join( compressed_files[0] ) ).unwrap() )
+        .read_to_string( &mut decompressed )
+        .unwrap();
+    assert_eq!( decompressed, "first log line\n" );
+
+    let contents: Vec<String> = files
+        .iter()
+        .filter( |name| !name.ends_with( ".gz" ) )
+        .map( |name| fs::read_to_string( dir.join( name ) ).unwrap() )
+        .collect();
+    assert!( contents.contains( &"second log line\n".to_string() ) );
+    assert!( contents.contains( &"third log line\n".to_string() ) );
+
+    fs::remove_dir_all( &dir ).unwrap();
+}
+
+#[test]
+fn rotated_files_past_the_maximum_are_removed() {
+    let dir = create_logs_dir( "retention" );
+    let options = RollingOptions {
+        rotation:      Rotation::Size,
+        max_file_size: 8,
+        max_files:     Some( 2 ),
+        max_age:       None,
+        is_compressed: false,
+    };
+    let mut appender = RollingFileAppender::new( &dir, "app", options ).unwrap();
+
+    for line in 0..5 {
+        appender.write_all( format!( "line {line}\n" ).as_bytes() ).unwrap();
+    }
+    drop( appender );
+
+    // The current file and the two most recent rotated ones.
+    let files = list_files( &dir );
+    assert_eq!( files.len(), 3 );
+
+    let contents: Vec<String> = files
+        .iter()
+        .map( |name| fs::read_to_string( dir.join( name ) ).unwrap() )
+        .collect();
+    assert!( contents.contains( &"line 4\n".to_string() ) );
+    assert!( !contents.contains( &"line 0\n".to_string() ) );
+
+    fs::remove_dir_all( &dir ).unwrap();
+}
+
+#[test]
+fn only_the_files_named_after_the_prefix_and_a_date_are_removed() {
+    let dir = create_logs_dir( "foreign-files" );
+    fs::create_dir_all( &dir ).unwrap();
+    for name in [
+        "app.2020-01-01-00",
+        "app.2020-01-01-00-00-00.3.gz",
+        "app.worker.2020-01-01-00",
+        "app.notes",
+        "app.2020-01-01-00.bak",
+    ] {
+        fs::write( dir.join( name ), "old log line\n" ).unwrap();
+    }
+    let options = RollingOptions {
+        rotation:      Rotation::Daily,
+        max_file_size: 0,
+        max_files:     Some( 0 ),
+        max_age:       None,
+        is_compressed: false,
+    };
+
+    drop( RollingFileAppender::new( &dir, "app", options ).unwrap() );
+
+    let files = list_files( &dir );
+    assert_eq!( files.len(), 4 );
+    assert!( files.contains( &"app.worker.2020-01-01-00".to_string() ) );
+    assert!( files.contains( &"app.notes".to_string() ) );
+    assert!( files.contains( &"app.2020-01-01-00.bak".to_string() ) );
+    assert!( !files.contains( &"app.2020-01-01-00".to_string() ) );
+
+    fs::remove_dir_all( &dir ).unwrap();
+}
@@ -0,0 +1,49 @@
+//! Authentication of the admin routes.
+//!
+//! Requests must send the configured admin token as `Authorization: Bearer <token>`. When no token is configured the
+//! admin routes are disabled.
+
+use axum::{
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use settings::Secret;
+
+/// The token that admin requests must present. [`None`] disables the admin routes.
+#[derive(Debug, Clone)]
+pub struct AdminToken( pub Option<Secret<String>> );
+
+/// Middleware letting through the requests with the [`AdminToken`] extension, answering `404 Not Found` when the admin
+/// routes are disabled and `401 Unauthorized` when the token is missing or invalid.
+pub async fn require_admin_token<B>(
+    Extension( AdminToken( admin_token ) ): Extension<AdminToken>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some( admin_token ) = admin_token else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let bearer_token = request
+        .headers()
+        .get( header::AUTHORIZATION )
+        .and_then( |value| value.to_str().ok() )
+        .and_then( |value| value.strip_prefix( "Bearer " ) );
+
+    match bearer_token {
+        Some( token ) if constant_time_eq( token.as_bytes(), admin_token.expose_secret().as_bytes() ) => {
+            next.run( request ).await
+        }
+        _ => {
+            tracing::warn!( "Rejected admin request to {} with a missing or invalid token.", request.uri().path() );
+            StatusCode::UNAUTHORIZED.into_response()
+        }
+    }
+}
+
+/// Compares both slices without returning early, so the comparison time does not leak the matching prefix length.
+fn constant_time_eq( a: &[u8], b: &[u8] ) -> bool {
+    a.len() == b.len() && a.iter().zip( b ).fold( 0, |acc, ( x, y )| acc | ( x ^ y ) ) == 0
+}
@@ -0,0 +1,188 @@
+//! Compression of the responses, negotiated with the `Accept-Encoding` header of the requests.
+//!
+//! The algorithm is chosen once per request, among the ones of the settings: the one with the highest q-value, an
+//! algorithm not named by the client getting the q-value of `*`, and the ties going to the first one of the settings.
+//! The request then only accepts the chosen algorithm, so the compression layers and the precompressed `.br`, `.zst`
+//! and `.gz` static files agree on it. Each algorithm has its own compression layer, with its own level.
+//!
+//! The already compressed formats, such as the JPEG or WebP images, the videos or the archives, are never compressed
+//! again.
+
+use axum::{
+    body::HttpBody,
+    http::{header, HeaderMap, HeaderValue, Request, Response},
+    extract::State,
+    middleware::{self, Next},
+    response::Response as AxumResponse,
+    Router,
+};
+use tower_http::{
+    compression::{
+        predicate::{Predicate, SizeAbove},
+        CompressionLayer, CompressionLevel,
+    },
+    services::ServeDir,
+};
+
+use settings::configs::{CompressionAlgorithm, CompressionSubconfig};
+
+/// The content types of the already compressed formats, matched by prefix.
+const COMPRESSED_CONTENT_TYPES: [&str; 18] = [
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+    "image/heic",
+    "image/heif",
+    "image/jxl",
+    "video/",
+    "audio/",
+    "font/woff",
+    "application/zip",
+    "application/gzip",
+    "application/zstd",
+    "application/x-7z-compressed",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/grpc",
+];
+
+/// The q-value of the codings, in thousandths.
+type QValue = u16;
+
+/// Compresses the responses of the `router` with the algorithm negotiated with each request.
+pub fn middleware_compression<T>(
+    router: Router<T>,
+    compression_settings: &CompressionSubconfig,
+) -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let mut router = router;
+    // The minimum size is checked to fit by the validation of the settings.
+    let should_compress = ShouldCompress( SizeAbove::new(
+        u16::try_from( compression_settings.min_bytes.as_u64() ).unwrap_or( u16::MAX ),
+    ) );
+    for &algorithm in &compression_settings.algorithms {
+        let level = compression_settings
+            .levels
+            .get( &algorithm )
+            .map_or( CompressionLevel::Default, |&level| CompressionLevel::Precise( level ) );
+        let compression_layer = CompressionLayer::new()
+            .br( algorithm == CompressionAlgorithm::Brotli )
+            .zstd( algorithm == CompressionAlgorithm::Zstd )
+            .gzip( algorithm == CompressionAlgorithm::Gzip )
+            .quality( level )
+            .compress_when( should_compress );
+        router = router.layer( compression_layer );
+    }
+
+    router.layer( middleware::from_fn_with_state( compression_settings.algorithms.clone(), negotiate_encoding ) )
+}
+
+/// Serves the precompressed variants of the files, when the negotiated algorithm has one.
+pub fn precompressed( serve_dir: ServeDir ) -> ServeDir {
+    serve_dir.precompressed_br().precompressed_zstd().precompressed_gzip()
+}
+
+/// Restricts the `Accept-Encoding` of the request to the negotiated algorithm, and makes the response vary with it.
+async fn negotiate_encoding<B>(
+    State( algorithms ): State<Vec<CompressionAlgorithm>>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> AxumResponse {
+    let encoding =
+        choose_algorithm( request.headers(), &algorithms ).map_or( "identity", CompressionAlgorithm::as_str );
+    request.headers_mut().insert( header::ACCEPT_ENCODING, HeaderValue::from_static( encoding ) );
+
+    let mut response = next.run( request ).await;
+
+    // The caches must not serve a response compressed for a client to the others.
+    let is_vary_set = response.headers().get_all( header::VARY ).iter().any( |vary| {
+        vary.to_str()
+            .is_ok_and( |vary| vary.split( ',' ).any( |name| name.trim().eq_ignore_ascii_case( "accept-encoding" ) ) )
+    } );
+    if !is_vary_set {
+        response.headers_mut().append( header::VARY, HeaderValue::from_static( "accept-encoding" ) );
+    }
+
+    response
+}
+
+/// Chooses the algorithm accepted with the highest q-value, the ties going to the first of the `algorithms`. Returns
+/// [`None`] if the client accepts none of them.
+#[must_use]
+pub fn choose_algorithm( headers: &HeaderMap, algorithms: &[CompressionAlgorithm] ) -> Option<CompressionAlgorithm> {
+    let codings: Vec<( &str, QValue )> = headers
+        .get_all( header::ACCEPT_ENCODING )
+        .iter()
+        .filter_map( |value| value.to_str().ok() )
+        .flat_map( |value| value.split( ',' ) )
+        .filter_map( parse_coding )
+        .collect();
+    let qvalue_of = |name: &str| {
+        codings
+            .iter()
+            .find( |( coding, _ )| coding.eq_ignore_ascii_case( name ) )
+            .map( |&( _, qvalue )| qvalue )
+    };
+    let wildcard_qvalue = qvalue_of( "*" ).unwrap_or( 0 );
+
+    let mut chosen: Option<( CompressionAlgorithm, QValue )> = None;
+    for &algorithm in algorithms {
+        let qvalue = qvalue_of( algorithm.as_str() ).unwrap_or( wildcard_qvalue );
+        if qvalue > 0 && chosen.is_none_or( |( _, chosen_qvalue )| qvalue > chosen_qvalue ) {
+            chosen = Some( ( algorithm, qvalue ) );
+        }
+    }
+
+    chosen.map( |( algorithm, _ )| algorithm )
+}
+
+/// Parses a coding of `Accept-Encoding`, e.g. `gzip` or `br;q=0.8`. An invalid q-value ignores the coding.
+fn parse_coding( coding: &str ) -> Option<( &str, QValue )> {
+    let mut parts = coding.split( ';' ).map( str::trim );
+    let name = parts.next().filter( |name| !name.is_empty() )?;
+    let qvalue = match parts.find_map( |param| param.strip_prefix( "q=" ).or_else( || param.strip_prefix( "Q=" ) ) ) {
+        Some( qvalue ) => parse_qvalue( qvalue )?,
+        None => 1000,
+    };
+
+    Some( ( name, qvalue ) )
+}
+
+/// Parses a q-value, between 0 and 1 with at most 3 decimals, into thousandths.
+fn parse_qvalue( qvalue: &str ) -> Option<QValue> {
+    let ( units, decimals ) = qvalue.split_once( '.' ).unwrap_or( ( qvalue, "" ) );
+    if decimals.len() > 3 || !decimals.bytes().all( |digit| digit.is_ascii_digit() ) {
+        return None;
+    }
+
+    let decimals = format!( "{decimals:0<3}" ).parse::<QValue>().ok()?;
+    match units {
+        "0" => Some( decimals ),
+        "1" if decimals == 0 => Some( 1000 ),
+        _ => None,
+    }
+}
+
+/// Compresses the responses above the minimum size, except the already compressed formats.
+#[derive(Debug, Clone, Copy)]
+struct ShouldCompress( SizeAbove );
+
+impl Predicate for ShouldCompress {
+    fn should_compress<B>( &self, response: &Response<B> ) -> bool
+    where
+        B: HttpBody,
+    {
+        let content_type = response
+            .headers()
+            .get( header::CONTENT_TYPE )
+            .and_then( |content_type| content_type.to_str().ok() )
+            .unwrap_or_default();
+
+        self.0.should_compress( response )
+            && !COMPRESSED_CONTENT_TYPES.iter().any( |compressed| content_type.starts_with( compressed ) )
+    }
+}
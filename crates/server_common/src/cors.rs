@@ -0,0 +1,50 @@
+//! Cross-origin resource sharing, configured by the `cors` settings section.
+//!
+//! The allowed origins are read from the latest settings on each request, so they are applied when the configuration
+//! files change. The other settings need a restart.
+
+use std::sync::Arc;
+
+use axum::http::{HeaderName, Method};
+use settings::{configs::CorsConfigs, validators::HttpToken};
+use tokio::sync::watch;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+/// Creates the CORS layer from the settings of the `configs_receiver`, whose `cors` section is given by `cors`.
+pub fn create_layer<T>( configs_receiver: watch::Receiver<Arc<T>>, cors: fn( &T ) -> &CorsConfigs ) -> CorsLayer
+where
+    T: Send + Sync + 'static,
+{
+    let cors_settings = cors( &configs_receiver.borrow() ).clone();
+
+    let allow_methods = if cors_settings.allows_any_method() {
+        AllowMethods::from( Any )
+    } else {
+        AllowMethods::list( parse_tokens( &cors_settings.allowed_methods, Method::from_bytes ) )
+    };
+    let allow_headers = if cors_settings.allows_any_header() {
+        AllowHeaders::from( Any )
+    } else {
+        AllowHeaders::list( parse_tokens( &cors_settings.allowed_headers, HeaderName::from_bytes ) )
+    };
+    let allow_origin = AllowOrigin::predicate( move |origin, _| {
+        let Ok( origin ) = origin.to_str() else {
+            return false;
+        };
+
+        let configs = configs_receiver.borrow();
+        cors( &configs ).allowed_origins.iter().any( |allowed_origin| allowed_origin.matches( origin ) )
+    } );
+
+    CorsLayer::new()
+        .allow_origin( allow_origin )
+        .allow_methods( allow_methods )
+        .allow_headers( allow_headers )
+        .allow_credentials( cors_settings.allow_credentials )
+        .max_age( cors_settings.max_age.as_std() )
+}
+
+/// Parses the tokens validated by the settings into methods or header names.
+fn parse_tokens<T, E>( tokens: &[HttpToken], parse: fn( &[u8] ) -> Result<T, E> ) -> Vec<T> {
+    tokens.iter().filter_map( |token| parse( token.as_str().as_bytes() ).ok() ).collect()
+}
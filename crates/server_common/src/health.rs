@@ -0,0 +1,31 @@
+//! Responses of the liveness and readiness endpoints.
+
+use axum::{http, response::IntoResponse, Json};
+use common::api::health::{Report, Status};
+use monitoring::prometheus;
+
+/// Responds with the report, using `503 Service Unavailable` when any check failed so probes do not need to parse the
+/// body.
+#[must_use]
+pub fn report_response( report: Report ) -> impl IntoResponse {
+    let status = match report.status {
+        Status::Pass => http::StatusCode::OK,
+        Status::Fail => http::StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    ( status, Json( report ) )
+}
+
+/// Exposes the status of every readiness check as a metric and logs the failed ones.
+pub fn record_readiness( report: &Report ) {
+    for ( name, check ) in &report.checks {
+        let is_passing = check.status == Status::Pass;
+        prometheus::metrics::HEALTH_CHECK_STATUS
+            .with_label_values( &[name] )
+            .set( i64::from( is_passing ) );
+
+        if let Some( message ) = &check.message {
+            tracing::warn!( "Readiness check {name} failed: {message}" );
+        }
+    }
+}
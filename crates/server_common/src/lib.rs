@@ -0,0 +1,15 @@
+//! Server building blocks shared by the backend and the web server.
+
+#![deny( clippy::all )]
+#![warn( clippy::pedantic )]
+#![warn( clippy::nursery )]
+#![warn( clippy::complexity )]
+#![warn( clippy::perf )]
+
+pub mod admin;
+pub mod compression;
+pub mod cors;
+pub mod health;
+pub mod listener;
+pub mod shutdown;
+pub mod tls;
@@ -0,0 +1,226 @@
+//! Listeners of the server, on TCP sockets, IPv4 or IPv6, and on Unix domain sockets.
+//!
+//! The server binds all the listen addresses first, so a wrong one fails the start, and then serves the same router on
+//! each of them until the shutdown.
+//!
+//! The plain listeners serve HTTP/1.1, and when h2c is enabled HTTP/2 to the clients starting with it (h2c with prior
+//! knowledge), e.g. the internal traffic. The HTTPS listeners terminate TLS first, see [`crate::tls`].
+
+use std::{
+    fs, io,
+    net::SocketAddr,
+    os::unix::fs::FileTypeExt,
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{extract::connect_info::Connected, http::uri::Scheme, Extension, Router};
+use futures::{future::BoxFuture, stream::FuturesUnordered, StreamExt};
+use hyper::server::{
+    accept::Accept,
+    conn::{AddrIncoming, AddrStream},
+};
+use settings::validators::ListenAddr;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{UnixListener, UnixStream},
+};
+use tokio_rustls::TlsAcceptor;
+
+use crate::shutdown::Shutdown;
+
+/// Maximum time a client has to finish the TLS handshake, so slow ones can't hold the connections.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs( 10 );
+
+/// A bound listener.
+pub enum Listener {
+    Tcp( AddrIncoming ),
+    Tls( TlsIncoming ),
+    Unix( UnixIncoming ),
+}
+
+impl Listener {
+    /// Binds the listen address. A Unix domain socket left by a previous run is replaced.
+    ///
+    /// # Errors
+    ///
+    /// If the address can't be bound.
+    pub fn bind( listen_addr: &ListenAddr ) -> io::Result<Self> {
+        match listen_addr {
+            ListenAddr::Tcp( addr ) => AddrIncoming::bind( addr ).map( Self::Tcp ).map_err( io::Error::other ),
+            ListenAddr::Unix( path ) => UnixIncoming::bind( path.clone() ).map( Self::Unix ),
+        }
+    }
+
+    /// Binds the HTTPS address, whose connections are accepted once their TLS handshake succeeded.
+    ///
+    /// # Errors
+    ///
+    /// If the address can't be bound.
+    pub fn bind_tls( addr: &SocketAddr, acceptor: TlsAcceptor ) -> io::Result<Self> {
+        let incoming = AddrIncoming::bind( addr ).map_err( io::Error::other )?;
+
+        Ok( Self::Tls( TlsIncoming {
+            incoming,
+            acceptor,
+            handshakes: FuturesUnordered::new(),
+        } ) )
+    }
+
+    /// The address the listener is bound to, e.g. with the port picked by the system for the port 0. The Unix domain
+    /// sockets have none.
+    #[must_use]
+    pub fn local_addr( &self ) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp( incoming ) => Some( incoming.local_addr() ),
+            Self::Tls( incoming ) => Some( incoming.incoming.local_addr() ),
+            Self::Unix( _ ) => None,
+        }
+    }
+
+    /// Serves the `app` until the shutdown starts. The address of the clients is only known on TCP sockets, where it is
+    /// given to the handlers as [`ConnectInfo`](axum::extract::ConnectInfo).
+    ///
+    /// The plain listeners only serve HTTP/1.1 unless `is_h2c_enabled`, the HTTPS ones negotiate the protocol.
+    ///
+    /// # Errors
+    ///
+    /// If the server failed.
+    pub async fn serve( self, app: Router, shutdown: Shutdown, is_h2c_enabled: bool ) -> hyper::Result<()> {
+        match self {
+            Self::Tcp( incoming ) => {
+                axum::Server::builder( incoming )
+                    .http1_only( !is_h2c_enabled )
+                    .serve( app.into_make_service_with_connect_info::<SocketAddr>() )
+                    .with_graceful_shutdown( shutdown.stop_accepting() )
+                    .await
+            }
+            Self::Tls( incoming ) => {
+                // The scheme tells the handlers, e.g. the reverse proxy, that the request came over TLS.
+                let app = app.layer( Extension( Scheme::HTTPS ) );
+                axum::Server::builder( incoming )
+                    .serve( app.into_make_service_with_connect_info::<SocketAddr>() )
+                    .with_graceful_shutdown( shutdown.stop_accepting() )
+                    .await
+            }
+            Self::Unix( incoming ) => {
+                axum::Server::builder( incoming )
+                    .http1_only( !is_h2c_enabled )
+                    .serve( app.into_make_service() )
+                    .with_graceful_shutdown( shutdown.stop_accepting() )
+                    .await
+            }
+        }
+    }
+}
+
+/// The connections accepted on a TCP socket, once their TLS handshake succeeded.
+pub struct TlsIncoming {
+    incoming:   AddrIncoming,
+    acceptor:   TlsAcceptor,
+    handshakes: FuturesUnordered<BoxFuture<'static, io::Result<TlsStream>>>,
+}
+
+impl Accept for TlsIncoming {
+    type Conn = TlsStream;
+    type Error = io::Error;
+
+    fn poll_accept( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let this = self.get_mut();
+
+        // Start the handshakes of the new connections, without waiting for the ones in progress.
+        loop {
+            match Pin::new( &mut this.incoming ).poll_accept( cx ) {
+                Poll::Ready( Some( Ok( stream ) ) ) => {
+                    let acceptor = this.acceptor.clone();
+                    this.handshakes.push( Box::pin( async move {
+                        tokio::time::timeout( TLS_HANDSHAKE_TIMEOUT, acceptor.accept( stream ) )
+                            .await
+                            .map_err( |_| io::Error::from( io::ErrorKind::TimedOut ) )?
+                            .map( TlsStream )
+                    } ) );
+                }
+                Poll::Ready( Some( Err( err ) ) ) => return Poll::Ready( Some( Err( err ) ) ),
+                Poll::Ready( None ) => return Poll::Ready( None ),
+                Poll::Pending => break,
+            }
+        }
+
+        // A failed handshake only concerns its client, the listener keeps accepting.
+        loop {
+            match this.handshakes.poll_next_unpin( cx ) {
+                Poll::Ready( Some( Ok( stream ) ) ) => return Poll::Ready( Some( Ok( stream ) ) ),
+                Poll::Ready( Some( Err( err ) ) ) => tracing::debug!( "TLS handshake failed: {err}" ),
+                Poll::Ready( None ) | Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A connection of an HTTPS listener.
+pub struct TlsStream( tokio_rustls::server::TlsStream<AddrStream> );
+
+impl TlsStream {
+    /// The address of the client.
+    #[must_use]
+    pub fn remote_addr( &self ) -> SocketAddr { self.0.get_ref().0.remote_addr() }
+}
+
+impl Connected<&TlsStream> for SocketAddr {
+    fn connect_info( target: &TlsStream ) -> Self { target.remote_addr() }
+}
+
+impl AsyncRead for TlsStream {
+    fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll<io::Result<()>> {
+        Pin::new( &mut self.get_mut().0 ).poll_read( cx, buf )
+    }
+}
+
+impl AsyncWrite for TlsStream {
+    fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll<io::Result<usize>> {
+        Pin::new( &mut self.get_mut().0 ).poll_write( cx, buf )
+    }
+
+    fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<io::Result<()>> {
+        Pin::new( &mut self.get_mut().0 ).poll_flush( cx )
+    }
+
+    fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<io::Result<()>> {
+        Pin::new( &mut self.get_mut().0 ).poll_shutdown( cx )
+    }
+}
+
+/// The connections accepted on a Unix domain socket. The socket file is removed once it is dropped.
+pub struct UnixIncoming {
+    listener: UnixListener,
+    path:     PathBuf,
+}
+
+impl UnixIncoming {
+    fn bind( path: PathBuf ) -> io::Result<Self> {
+        // Only a socket is removed, never a file that would be there by mistake.
+        if fs::symlink_metadata( &path ).is_ok_and( |metadata| metadata.file_type().is_socket() ) {
+            fs::remove_file( &path )?;
+        }
+
+        Ok( Self {
+            listener: UnixListener::bind( &path )?,
+            path,
+        } )
+    }
+}
+
+impl Accept for UnixIncoming {
+    type Conn = UnixStream;
+    type Error = io::Error;
+
+    fn poll_accept( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.listener.poll_accept( cx ).map( |result| Some( result.map( |( stream, _ )| stream ) ) )
+    }
+}
+
+impl Drop for UnixIncoming {
+    fn drop( &mut self ) { let _ = fs::remove_file( &self.path ); }
+}
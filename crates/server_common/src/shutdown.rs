@@ -0,0 +1,106 @@
+//! Graceful shutdown.
+//!
+//! Listens to the SIGTERM and SIGINT signals and notifies every subscriber. The readiness probe starts failing right
+//! away, and the listeners keep accepting connections during the pre-stop delay, so the load balancers have the time
+//! to stop routing to the server before it stops accepting connections and drains the in-flight ones.
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::{signal, sync::watch};
+
+/// Handle to the shutdown state, cheap to clone and shared by every component that needs to react to a shutdown.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    sender:         Arc<watch::Sender<bool>>,
+    receiver:       watch::Receiver<bool>,
+    /// Time between the start of the shutdown and the listeners stop accepting connections.
+    pre_stop_delay: Duration,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let ( sender, receiver ) = watch::channel( false );
+        Self {
+            sender: Arc::new( sender ),
+            receiver,
+            pre_stop_delay: Duration::ZERO,
+        }
+    }
+}
+
+impl Shutdown {
+    /// Creates the shutdown handle and spawns the task listening to the termination signals.
+    #[must_use]
+    pub fn listen( pre_stop_delay: Duration ) -> Self {
+        let shutdown = Self::with_pre_stop_delay( pre_stop_delay );
+
+        let signal_shutdown = shutdown.clone();
+        tokio::spawn( async move {
+            wait_for_termination_signal().await;
+            signal_shutdown.trigger();
+        } );
+
+        shutdown
+    }
+
+    /// Creates the shutdown handle without listening to the termination signals.
+    #[must_use]
+    pub fn with_pre_stop_delay( pre_stop_delay: Duration ) -> Self {
+        Self {
+            pre_stop_delay,
+            ..Self::default()
+        }
+    }
+
+    /// Starts the shutdown without waiting for a signal.
+    pub fn trigger( &self ) { self.sender.send_replace( true ); }
+
+    /// Whether the shutdown started and the server is draining its connections.
+    #[must_use]
+    pub fn is_draining( &self ) -> bool { *self.receiver.borrow() }
+
+    /// Resolves once the shutdown starts.
+    pub async fn draining( &self ) {
+        let mut receiver = self.receiver.clone();
+
+        while !*receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Resolves once the listeners must stop accepting connections, the pre-stop delay after the shutdown started.
+    pub async fn stop_accepting( &self ) {
+        self.draining().await;
+        tokio::time::sleep( self.pre_stop_delay ).await;
+    }
+
+    /// Resolves once the listeners stopped accepting connections and the drain timeout elapsed.
+    pub async fn drain_deadline( &self, drain_timeout: Duration ) {
+        self.stop_accepting().await;
+        tokio::time::sleep( drain_timeout ).await;
+    }
+}
+
+async fn wait_for_termination_signal() {
+    let interrupt = async {
+        signal::ctrl_c().await.expect( "Failed to install the SIGINT handler." );
+    };
+
+    #[cfg( unix )]
+    let terminate = async {
+        signal::unix::signal( signal::unix::SignalKind::terminate() )
+            .expect( "Failed to install the SIGTERM handler." )
+            .recv()
+            .await;
+    };
+
+    #[cfg( not( unix ) )]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = interrupt => tracing::info!( "Received SIGINT, starting graceful shutdown." ),
+        () = terminate => tracing::info!( "Received SIGTERM, starting graceful shutdown." ),
+    }
+}
@@ -0,0 +1,231 @@
+//! TLS termination of the HTTPS listeners, with rustls.
+//!
+//! The HTTPS listeners offer HTTP/2 and HTTP/1.1 through ALPN. The certificate and its private key are read again when
+//! their files change, so a renewed certificate is served without a restart. A renewal that can't be read is logged
+//! and the certificate in use is kept. When a client CA is set, the clients must authenticate with a certificate it
+//! signed.
+//!
+//! An optional plain HTTP listener redirects the requests to HTTPS, see [`create_redirect_route`].
+//!
+//! The TLS clients, e.g. of an object storage, trust the CA certificates of a file, see [`create_connector`].
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, RwLock},
+    thread,
+};
+
+use axum::{
+    extract::State,
+    http::{header, uri::Authority, HeaderMap, StatusCode, Uri},
+    response::Redirect,
+    Router,
+};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rustls::{
+    server::{AllowAnyAuthenticatedClient, ClientHello, ResolvesServerCert},
+    sign::{self, CertifiedKey},
+    Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig,
+};
+use rustls_pemfile::Item;
+use thiserror::Error;
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use settings::configs::TlsSubconfig;
+
+/// Protocols offered through ALPN, by order of preference.
+const ALPN_PROTOCOLS: [&[u8]; 2] = [b"h2", b"http/1.1"];
+
+/// Port of HTTPS, left out of the redirect URLs.
+const HTTPS_DEFAULT_PORT: u16 = 443;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// A file can't be read.
+    #[error( "Failed to read the file {0}: {1}" )]
+    Read( PathBuf, #[source] io::Error ),
+    /// The file has no certificate.
+    #[error( "No certificate found in the file {0}." )]
+    NoCertificate( PathBuf ),
+    /// The file has no private key.
+    #[error( "No private key found in the file {0}." )]
+    NoPrivateKey( PathBuf ),
+    /// The private key is not supported by rustls.
+    #[error( "The private key of the file {0} is not supported." )]
+    UnsupportedPrivateKey( PathBuf ),
+    /// The certificate files can't be watched.
+    #[error( "Failed to watch the certificate file {0}: {1}" )]
+    Watch( PathBuf, #[source] notify::Error ),
+}
+
+/// The TLS acceptor shared by the HTTPS listeners. The certificate files stop being watched once it is dropped.
+pub struct Tls {
+    acceptor: TlsAcceptor,
+    _watcher: RecommendedWatcher,
+}
+
+impl Tls {
+    /// Reads the certificate, its private key and the client CA, and starts watching the certificate files.
+    ///
+    /// # Errors
+    ///
+    /// If the files can't be read or are not valid, or if they can't be watched.
+    pub fn new( tls_settings: &TlsSubconfig ) -> Result<Self, Error> {
+        let cert_path = tls_settings.cert_path.as_ref().to_path_buf();
+        let key_path = tls_settings.key_path.as_ref().to_path_buf();
+        let certified_key = load_certified_key( &cert_path, &key_path )?;
+        let certificate = Arc::new( ReloadableCertificate( RwLock::new( certified_key ) ) );
+
+        let config_builder = ServerConfig::builder().with_safe_defaults();
+        let config_builder = match &tls_settings.client_ca_path {
+            Some( client_ca_path ) => {
+                let client_ca_certificates = read_certificates( client_ca_path.as_ref() )?;
+                let mut roots = RootCertStore::empty();
+                let ( valid_count, _ ) = roots.add_parsable_certificates( &client_ca_certificates );
+                if valid_count == 0 {
+                    return Err( Error::NoCertificate( client_ca_path.as_ref().to_path_buf() ) );
+                }
+
+                config_builder.with_client_cert_verifier( AllowAnyAuthenticatedClient::new( roots ).boxed() )
+            }
+            None => config_builder.with_no_client_auth(),
+        };
+        let mut config = config_builder.with_cert_resolver( certificate.clone() );
+        config.alpn_protocols = ALPN_PROTOCOLS.iter().map( |protocol| protocol.to_vec() ).collect();
+
+        let watcher = watch_certificate( certificate, cert_path, key_path )?;
+
+        Ok( Self {
+            acceptor: TlsAcceptor::from( Arc::new( config ) ),
+            _watcher: watcher,
+        } )
+    }
+
+    /// The acceptor doing the TLS handshakes of the connections.
+    #[must_use]
+    pub fn acceptor( &self ) -> TlsAcceptor { self.acceptor.clone() }
+}
+
+/// Creates the connector of the TLS clients, trusting the CA certificates of the `ca_path` file, e.g. the system bundle.
+///
+/// # Errors
+///
+/// If the file can't be read or has no valid certificate.
+pub fn create_connector( ca_path: &Path ) -> Result<TlsConnector, Error> {
+    let mut roots = RootCertStore::empty();
+    let ( valid_count, _ ) = roots.add_parsable_certificates( &read_certificates( ca_path )? );
+    if valid_count == 0 {
+        return Err( Error::NoCertificate( ca_path.to_path_buf() ) );
+    }
+
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates( roots )
+        .with_no_client_auth();
+
+    Ok( TlsConnector::from( Arc::new( config ) ) )
+}
+
+/// The certificate in use, replaced when its files change.
+struct ReloadableCertificate( RwLock<Arc<CertifiedKey>> );
+
+impl ResolvesServerCert for ReloadableCertificate {
+    fn resolve( &self, _client_hello: ClientHello ) -> Option<Arc<CertifiedKey>> {
+        self.0.read().ok().map( |certified_key| Arc::clone( &certified_key ) )
+    }
+}
+
+/// Reloads the certificate whenever a file of its directories changes, which also covers the renewals replacing the
+/// files or the symbolic links to them.
+fn watch_certificate(
+    certificate: Arc<ReloadableCertificate>,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+) -> Result<RecommendedWatcher, Error> {
+    let ( event_sender, event_receiver ) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher( event_sender ).map_err( |err| Error::Watch( cert_path.clone(), err ) )?;
+    for path in [&cert_path, &key_path] {
+        let dir = path.parent().filter( |dir| !dir.as_os_str().is_empty() ).unwrap_or_else( || Path::new( "." ) );
+        watcher
+            .watch( dir, RecursiveMode::NonRecursive )
+            .map_err( |err| Error::Watch( path.clone(), err ) )?;
+    }
+
+    // The loop ends when the watcher is dropped, as it holds the events sender.
+    thread::spawn( move || {
+        // A renewal writes the certificate and the key in several steps, they are read once it is done.
+        while settings::wait_for_change( &event_receiver, settings::is_file_change ) {
+            match load_certified_key( &cert_path, &key_path ) {
+                Ok( certified_key ) => {
+                    if let Ok( mut current ) = certificate.0.write() {
+                        *current = certified_key;
+                        tracing::info!( "Reloaded the TLS certificate {}.", cert_path.display() );
+                    }
+                }
+                Err( err ) => tracing::error!( "Invalid TLS certificate, keeping the one in use: {err}" ),
+            }
+        }
+    } );
+
+    Ok( watcher )
+}
+
+fn load_certified_key( cert_path: &Path, key_path: &Path ) -> Result<Arc<CertifiedKey>, Error> {
+    let certificates = read_certificates( cert_path )?.into_iter().map( Certificate ).collect();
+    let private_key = read_private_key( key_path )?;
+    let signing_key =
+        sign::any_supported_type( &private_key ).map_err( |_| Error::UnsupportedPrivateKey( key_path.to_path_buf() ) )?;
+
+    Ok( Arc::new( CertifiedKey::new( certificates, signing_key ) ) )
+}
+
+fn read_certificates( path: &Path ) -> Result<Vec<Vec<u8>>, Error> {
+    let content = fs::read( path ).map_err( |err| Error::Read( path.to_path_buf(), err ) )?;
+    let certificates =
+        rustls_pemfile::certs( &mut content.as_slice() ).map_err( |err| Error::Read( path.to_path_buf(), err ) )?;
+    if certificates.is_empty() {
+        return Err( Error::NoCertificate( path.to_path_buf() ) );
+    }
+
+    Ok( certificates )
+}
+
+fn read_private_key( path: &Path ) -> Result<PrivateKey, Error> {
+    let content = fs::read( path ).map_err( |err| Error::Read( path.to_path_buf(), err ) )?;
+    let items =
+        rustls_pemfile::read_all( &mut content.as_slice() ).map_err( |err| Error::Read( path.to_path_buf(), err ) )?;
+
+    items
+        .into_iter()
+        .find_map( |item| match item {
+            Item::RSAKey( key ) | Item::PKCS8Key( key ) | Item::ECKey( key ) => Some( PrivateKey( key ) ),
+            _ => None,
+        } )
+        .ok_or_else( || Error::NoPrivateKey( path.to_path_buf() ) )
+}
+
+/// Creates the router of the plain HTTP listener, redirecting every request to the same URL on the HTTPS port.
+pub fn create_redirect_route( https_port: u16 ) -> Router {
+    Router::new().fallback( redirect_to_https ).with_state( https_port )
+}
+
+async fn redirect_to_https(
+    State( https_port ): State<u16>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<Redirect, StatusCode> {
+    let authority = headers
+        .get( header::HOST )
+        .and_then( |host| host.to_str().ok() )
+        .and_then( |host| host.parse::<Authority>().ok() )
+        .ok_or( StatusCode::BAD_REQUEST )?;
+
+    // The port of the plain listener is replaced by the HTTPS one.
+    let host = authority.host();
+    let authority = if https_port == HTTPS_DEFAULT_PORT { host.to_string() } else { format!( "{host}:{https_port}" ) };
+    let path_and_query = uri.path_and_query().map_or( "/", |path_and_query| path_and_query.as_str() );
+
+    Ok( Redirect::permanent( &format!( "https://{authority}{path_and_query}" ) ) )
+}
@@ -0,0 +1,52 @@
+use axum::{
+    body::Body,
+    http::{self, header, Request},
+    middleware,
+    routing::get,
+    Extension, Router,
+};
+use server_common::admin::{self, AdminToken};
+use settings::Secret;
+use tower::util::ServiceExt;
+
+fn create_admin( admin_token: Option<&str> ) -> Router {
+    let admin_token = AdminToken( admin_token.map( |token| Secret::new( token.to_string() ) ) );
+
+    Router::new()
+        .route( "/admin", get( || async { "admin" } ) )
+        .route_layer( middleware::from_fn( admin::require_admin_token ) )
+        .layer( Extension( admin_token ) )
+}
+
+async fn get_status( app: Router, authorization: Option<&str> ) -> http::StatusCode {
+    let mut request = Request::builder().uri( "/admin" );
+    if let Some( authorization ) = authorization {
+        request = request.header( header::AUTHORIZATION, authorization );
+    }
+
+    app.oneshot( request.body( Body::empty() ).unwrap() ).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn admin_routes_accept_the_admin_token() {
+    let status = get_status( create_admin( Some( "admin-token" ) ), Some( "Bearer admin-token" ) ).await;
+
+    assert_eq!( status, http::StatusCode::OK );
+}
+
+#[tokio::test]
+async fn admin_routes_reject_a_missing_or_invalid_token() {
+    let app = create_admin( Some( "admin-token" ) );
+
+    assert_eq!( get_status( app.clone(), None ).await, http::StatusCode::UNAUTHORIZED );
+    assert_eq!( get_status( app.clone(), Some( "Bearer admin-toke" ) ).await, http::StatusCode::UNAUTHORIZED );
+    assert_eq!( get_status( app.clone(), Some( "Bearer admin-token2" ) ).await, http::StatusCode::UNAUTHORIZED );
+    assert_eq!( get_status( app, Some( "admin-token" ) ).await, http::StatusCode::UNAUTHORIZED );
+}
+
+#[tokio::test]
+async fn admin_routes_are_disabled_without_admin_token() {
+    let status = get_status( create_admin( None ), Some( "Bearer admin-token" ) ).await;
+
+    assert_eq!( status, http::StatusCode::NOT_FOUND );
+}
@@ -0,0 +1,97 @@
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, Request},
+    routing::get,
+    Router,
+};
+use server_common::compression;
+use settings::{
+    configs::{CompressionAlgorithm, CompressionError, CompressionSubconfig},
+    validators::ByteSize,
+};
+use tower::util::ServiceExt;
+
+const ALGORITHMS: [CompressionAlgorithm; 3] =
+    [CompressionAlgorithm::Brotli, CompressionAlgorithm::Zstd, CompressionAlgorithm::Gzip];
+
+fn accept_encoding( value: &'static str ) -> HeaderMap {
+    HeaderMap::from_iter( [( header::ACCEPT_ENCODING, HeaderValue::from_static( value ) )] )
+}
+
+fn create_app() -> Router {
+    let page = "<p>photo story</p>".repeat( 256 );
+    let image = vec![0_u8; 4096];
+    let app = Router::new()
+        .route( "/", get( || async move { ( [( header::CONTENT_TYPE, "text/html" )], page ) } ) )
+        .route( "/photo.jpg", get( || async move { ( [( header::CONTENT_TYPE, "image/jpeg" )], image ) } ) );
+    compression::middleware_compression( app, &CompressionSubconfig::default() )
+}
+
+#[test]
+fn algorithm_is_negotiated_with_the_q_values() {
+    let choose = |value| compression::choose_algorithm( &accept_encoding( value ), &ALGORITHMS );
+
+    assert_eq!( choose( "gzip, deflate, br, zstd" ), Some( CompressionAlgorithm::Brotli ) );
+    assert_eq!( choose( "br;q=0.5, gzip" ), Some( CompressionAlgorithm::Gzip ) );
+    assert_eq!( choose( "br;q=0, zstd;q=0.8, gzip;q=0.9" ), Some( CompressionAlgorithm::Gzip ) );
+    assert_eq!( choose( "gzip;q=0.2, *;q=0.5" ), Some( CompressionAlgorithm::Brotli ) );
+    assert_eq!( choose( "*;q=0.5, br;q=0, zstd;q=0" ), Some( CompressionAlgorithm::Gzip ) );
+    assert_eq!( choose( "gzip;q=2, deflate" ), None );
+    assert_eq!( choose( "identity" ), None );
+    assert_eq!( choose( "" ), None );
+}
+
+#[tokio::test]
+async fn responses_are_compressed_with_the_negotiated_algorithm() {
+    let request = Request::get( "/" ).header( header::ACCEPT_ENCODING, "br;q=0.5, zstd;q=0, gzip" );
+    let response = create_app().oneshot( request.body( Body::empty() ).unwrap() ).await.unwrap();
+
+    assert_eq!( response.headers()[header::CONTENT_ENCODING], "gzip" );
+    assert_eq!( response.headers()[header::VARY], "accept-encoding" );
+}
+
+#[tokio::test]
+async fn compressed_images_are_not_compressed_again() {
+    let request = Request::get( "/photo.jpg" ).header( header::ACCEPT_ENCODING, "br, zstd, gzip" );
+    let response = create_app().oneshot( request.body( Body::empty() ).unwrap() ).await.unwrap();
+
+    assert!( !response.headers().contains_key( header::CONTENT_ENCODING ) );
+    assert_eq!( response.headers()[header::VARY], "accept-encoding" );
+}
+
+#[test]
+fn levels_are_validated_against_the_scale_of_their_algorithm() {
+    let with_level = |algorithm, level| CompressionSubconfig {
+        levels: [( algorithm, level )].into(),
+        ..CompressionSubconfig::default()
+    };
+
+    let scales = [
+        ( CompressionAlgorithm::Brotli, 0, 11 ),
+        ( CompressionAlgorithm::Zstd, 1, 22 ),
+        ( CompressionAlgorithm::Gzip, 0, 9 ),
+    ];
+    for ( algorithm, min, max ) in scales {
+        assert!( with_level( algorithm, min ).validate().is_ok() );
+        assert!( with_level( algorithm, max ).validate().is_ok() );
+        assert!( matches!(
+            with_level( algorithm, max + 1 ).validate(),
+            Err( CompressionError::InvalidLevel( _, level, _ ) ) if level == max + 1
+        ) );
+    }
+    assert!( with_level( CompressionAlgorithm::Zstd, 0 ).validate().is_err() );
+}
+
+#[test]
+fn minimum_size_is_validated_to_fit_16_bits() {
+    let with_min_bytes = |min_bytes| CompressionSubconfig {
+        min_bytes: ByteSize::new( min_bytes ),
+        ..CompressionSubconfig::default()
+    };
+
+    assert!( with_min_bytes( u64::from( u16::MAX ) ).validate().is_ok() );
+    assert!( matches!(
+        with_min_bytes( u64::from( u16::MAX ) + 1 ).validate(),
+        Err( CompressionError::MinSizeTooLarge( _ ) )
+    ) );
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use axum::{
+    http::{header, HeaderMap, Method, StatusCode},
+    routing::get,
+    Router,
+};
+use hyper::{Body, Request};
+use server_common::cors;
+use settings::configs::CorsConfigs;
+use tokio::sync::watch;
+use tower::ServiceExt;
+
+fn create_cors_settings( allowed_origins: &[&str] ) -> CorsConfigs {
+    CorsConfigs {
+        allowed_origins: allowed_origins.iter().map( |origin| origin.parse().unwrap() ).collect(),
+        ..CorsConfigs::default()
+    }
+}
+
+fn create_app( configs_receiver: watch::Receiver<Arc<CorsConfigs>> ) -> Router {
+    Router::new()
+        .route( "/photos", get( || async { "photos" } ) )
+        .layer( cors::create_layer( configs_receiver, |cors_settings| cors_settings ) )
+}
+
+async fn send( app: Router, method: Method, origin: &str ) -> ( StatusCode, HeaderMap ) {
+    let request = Request::builder()
+        .method( method )
+        .uri( "/photos" )
+        .header( header::ORIGIN, origin )
+        .header( header::ACCESS_CONTROL_REQUEST_METHOD, "PUT" )
+        .body( Body::empty() )
+        .unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+
+    ( response.status(), response.headers().clone() )
+}
+
+fn allowed_origin( headers: &HeaderMap ) -> Option<&str> {
+    headers.get( header::ACCESS_CONTROL_ALLOW_ORIGIN ).map( |origin| origin.to_str().unwrap() )
+}
+
+#[tokio::test]
+async fn preflight_requests_are_answered_with_the_allowed_methods() {
+    let ( _, configs_receiver ) = watch::channel( Arc::new( create_cors_settings( &["https://example.com"] ) ) );
+
+    let ( status, headers ) = send( create_app( configs_receiver ), Method::OPTIONS, "https://example.com" ).await;
+
+    assert_eq!( status, StatusCode::OK );
+    assert_eq!( allowed_origin( &headers ), Some( "https://example.com" ) );
+    let allowed_methods = headers[header::ACCESS_CONTROL_ALLOW_METHODS].to_str().unwrap();
+    assert!( allowed_methods.contains( "PUT" ) );
+    assert_eq!( headers[header::ACCESS_CONTROL_MAX_AGE], "3600" );
+}
+
+#[tokio::test]
+async fn only_the_allowed_origins_are_allowed() {
+    let ( _, configs_receiver ) = watch::channel( Arc::new( create_cors_settings( &["https://example.com"] ) ) );
+    let app = create_app( configs_receiver );
+
+    let ( status, headers ) = send( app.clone(), Method::GET, "https://example.com" ).await;
+    assert_eq!( status, StatusCode::OK );
+    assert_eq!( allowed_origin( &headers ), Some( "https://example.com" ) );
+
+    let ( status, headers ) = send( app, Method::GET, "https://example.org" ).await;
+    assert_eq!( status, StatusCode::OK );
+    assert_eq!( allowed_origin( &headers ), None );
+}
+
+#[tokio::test]
+async fn wildcard_subdomains_allow_the_subdomains_only() {
+    let ( _, configs_receiver ) = watch::channel( Arc::new( create_cors_settings( &["https://*.example.com"] ) ) );
+    let app = create_app( configs_receiver );
+
+    for ( origin, is_allowed ) in [
+        ( "https://photos.example.com", true ),
+        ( "https://a.b.example.com", true ),
+        ( "https://example.com", false ),
+        ( "https://photos.example.com.evil.com", false ),
+        ( "http://photos.example.com", false ),
+    ] {
+        let ( _, headers ) = send( app.clone(), Method::GET, origin ).await;
+        assert_eq!( allowed_origin( &headers ).is_some(), is_allowed, "{origin}" );
+    }
+}
+
+#[tokio::test]
+async fn reloaded_origins_are_applied_without_a_restart() {
+    let ( configs_sender, configs_receiver ) = watch::channel( Arc::new( create_cors_settings( &[] ) ) );
+    let app = create_app( configs_receiver );
+
+    let ( _, headers ) = send( app.clone(), Method::GET, "https://example.com" ).await;
+    assert_eq!( allowed_origin( &headers ), None );
+
+    configs_sender.send( Arc::new( create_cors_settings( &["https://example.com"] ) ) ).unwrap();
+    let ( _, headers ) = send( app, Method::GET, "https://example.com" ).await;
+    assert_eq!( allowed_origin( &headers ), Some( "https://example.com" ) );
+}
+
+#[tokio::test]
+async fn credentials_are_allowed_with_explicit_origins_only() {
+    let cors_settings = CorsConfigs {
+        allow_credentials: true,
+        ..create_cors_settings( &["https://example.com"] )
+    };
+    assert!( cors_settings.validate().is_ok() );
+    let ( _, configs_receiver ) = watch::channel( Arc::new( cors_settings ) );
+
+    let ( _, headers ) = send( create_app( configs_receiver ), Method::GET, "https://example.com" ).await;
+    assert_eq!( headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true" );
+
+    let with_any_origin = CorsConfigs {
+        allow_credentials: true,
+        ..create_cors_settings( &["*"] )
+    };
+    assert!( with_any_origin.validate().is_err() );
+
+    let with_any_header = CorsConfigs {
+        allow_credentials: true,
+        allowed_headers: vec!["*".parse().unwrap()],
+        ..create_cors_settings( &["https://example.com"] )
+    };
+    assert!( with_any_header.validate().is_err() );
+}
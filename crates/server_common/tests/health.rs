@@ -0,0 +1,42 @@
+use std::collections::BTreeMap;
+
+use axum::{http, response::IntoResponse};
+use common::api::health::{Check, Report, Status};
+use server_common::health;
+
+fn check( status: Status ) -> Check {
+    Check {
+        status,
+        latency_ms: 1.0,
+        message: ( status == Status::Fail ).then( || "The dependency failed.".to_string() ),
+    }
+}
+
+#[tokio::test]
+async fn health_report_response_passes_with_200() {
+    let report = Report::new( BTreeMap::from( [( "database".to_string(), check( Status::Pass ) )] ) );
+
+    let response = health::report_response( report ).into_response();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let report: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( report["status"], "pass" );
+    assert_eq!( report["checks"]["database"]["status"], "pass" );
+}
+
+#[tokio::test]
+async fn health_report_response_fails_with_503_if_any_check_failed() {
+    let report = Report::new( BTreeMap::from( [
+        ( "database".to_string(), check( Status::Pass ) ),
+        ( "storage".to_string(), check( Status::Fail ) ),
+    ] ) );
+
+    let response = health::report_response( report ).into_response();
+
+    assert_eq!( response.status(), http::StatusCode::SERVICE_UNAVAILABLE );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let report: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( report["status"], "fail" );
+    assert_eq!( report["checks"]["storage"]["message"], "The dependency failed." );
+}
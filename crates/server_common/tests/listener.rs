@@ -0,0 +1,80 @@
+use std::net::SocketAddr;
+
+use axum::{extract::ConnectInfo, routing::get, Router};
+use hyper::{body, client::conn, Body, Request};
+use server_common::{listener::Listener, shutdown::Shutdown};
+use settings::validators::ListenAddr;
+use tokio::net::{TcpStream, UnixStream};
+
+fn create_app() -> Router {
+    Router::new()
+        .route( "/peer", get( |ConnectInfo( addr ): ConnectInfo<SocketAddr>| async move { addr.to_string() } ) )
+        .route( "/", get( || async { "served" } ) )
+}
+
+/// Sends a GET request to the `uri` over the `stream` and returns the response body.
+async fn send_get<S>( stream: S, uri: &str ) -> String
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    let ( mut sender, connection ) = conn::handshake( stream ).await.unwrap();
+    tokio::spawn( connection );
+
+    let request = Request::builder().uri( uri ).header( "host", "localhost" ).body( Body::empty() ).unwrap();
+    let response = sender.send_request( request ).await.unwrap();
+    let response_body = body::to_bytes( response.into_body() ).await.unwrap();
+
+    String::from_utf8( response_body.to_vec() ).unwrap()
+}
+
+#[tokio::test]
+async fn tcp_listeners_give_the_client_address_to_the_handlers() {
+    let listener = Listener::bind( &ListenAddr::Tcp( "127.0.0.1:0".parse().unwrap() ) ).unwrap();
+    let Listener::Tcp( incoming ) = &listener else {
+        panic!( "A TCP address is bound to a TCP listener." );
+    };
+    let addr = incoming.local_addr();
+    tokio::spawn( listener.serve( create_app(), Shutdown::default(), false ) );
+
+    let stream = TcpStream::connect( addr ).await.unwrap();
+    let client_addr = stream.local_addr().unwrap();
+
+    assert_eq!( send_get( stream, "/peer" ).await, client_addr.to_string() );
+}
+
+#[tokio::test]
+async fn tcp_listeners_serve_http2_with_prior_knowledge_only_when_h2c_is_enabled() {
+    for is_h2c_enabled in [true, false] {
+        let listener = Listener::bind( &ListenAddr::Tcp( "127.0.0.1:0".parse().unwrap() ) ).unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn( listener.serve( create_app(), Shutdown::default(), is_h2c_enabled ) );
+
+        let stream = TcpStream::connect( addr ).await.unwrap();
+        let is_served = async {
+            let ( mut sender, connection ) = conn::Builder::new().http2_only( true ).handshake( stream ).await?;
+            tokio::spawn( connection );
+
+            let request = Request::builder().uri( "http://localhost/" ).body( Body::empty() ).unwrap();
+            sender.send_request( request ).await
+        }
+        .await
+        .is_ok_and( |response| response.status().is_success() );
+
+        assert_eq!( is_served, is_h2c_enabled );
+    }
+}
+
+#[tokio::test]
+async fn unix_listeners_serve_and_remove_their_socket() {
+    let path = std::env::temp_dir().join( format!( "server-common-listener-{}.sock", std::process::id() ) );
+    let listener = Listener::bind( &ListenAddr::Unix( path.clone() ) ).unwrap();
+    let shutdown = Shutdown::default();
+    let server = tokio::spawn( listener.serve( create_app(), shutdown.clone(), false ) );
+
+    let stream = UnixStream::connect( &path ).await.unwrap();
+    assert_eq!( send_get( stream, "/" ).await, "served" );
+
+    shutdown.trigger();
+    server.await.unwrap().unwrap();
+    assert!( !path.exists() );
+}
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use server_common::shutdown::Shutdown;
+use tokio::time::{timeout, Instant};
+
+#[tokio::test( start_paused = true )]
+async fn shutdown_starts_draining_when_triggered() {
+    let shutdown = Shutdown::default();
+    assert!( !shutdown.is_draining() );
+
+    shutdown.trigger();
+
+    assert!( shutdown.is_draining() );
+    timeout( Duration::from_millis( 1 ), shutdown.draining() ).await.unwrap();
+}
+
+#[tokio::test( start_paused = true )]
+async fn shutdown_stops_accepting_after_the_pre_stop_delay() {
+    let shutdown = Shutdown::with_pre_stop_delay( Duration::from_secs( 5 ) );
+    let started_at = Instant::now();
+
+    shutdown.trigger();
+    shutdown.stop_accepting().await;
+
+    assert_eq!( started_at.elapsed(), Duration::from_secs( 5 ) );
+}
+
+#[tokio::test( start_paused = true )]
+async fn shutdown_drain_deadline_follows_the_pre_stop_delay() {
+    let shutdown = Shutdown::with_pre_stop_delay( Duration::from_secs( 5 ) );
+    let started_at = Instant::now();
+
+    shutdown.trigger();
+    shutdown.drain_deadline( Duration::from_secs( 30 ) ).await;
+
+    assert_eq!( started_at.elapsed(), Duration::from_secs( 35 ) );
+}
+
+#[tokio::test( start_paused = true )]
+async fn shutdown_drain_deadline_waits_for_the_trigger() {
+    let shutdown = Shutdown::default();
+
+    assert!( timeout( Duration::from_secs( 60 ), shutdown.drain_deadline( Duration::from_secs( 1 ) ) ).await.is_err() );
+}
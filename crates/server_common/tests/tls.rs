@@ -0,0 +1,171 @@
+use std::{
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    http::{header, StatusCode},
+    routing::get,
+    Router,
+};
+use hyper::{body, client::conn, Body, Request};
+use rustls::{Certificate, ClientConfig, RootCertStore, ServerName};
+use server_common::{listener::Listener, shutdown::Shutdown, tls};
+use settings::{configs::TlsSubconfig, validators::FilePath};
+use tokio::net::TcpStream;
+use tokio_rustls::{client::TlsStream, TlsConnector};
+use tower::ServiceExt;
+
+fn create_certificates_dir( name: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "server-common-tls-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+    dir
+}
+
+/// Writes a new self signed certificate of `localhost` and its private key, and returns the certificate.
+fn write_certificate( dir: &Path ) -> Certificate {
+    let certificate = rcgen::generate_simple_self_signed( vec!["localhost".to_string()] ).unwrap();
+    fs::write( dir.join( "key.pem" ), certificate.serialize_private_key_pem() ).unwrap();
+    let cert_pem = certificate.serialize_pem().unwrap();
+    fs::write( dir.join( "cert.pem" ), &cert_pem ).unwrap();
+
+    // Each serialization signs the certificate again, so it is read back from the PEM file.
+    Certificate( rustls_pemfile::certs( &mut cert_pem.as_bytes() ).unwrap().remove( 0 ) )
+}
+
+fn create_tls_settings( dir: &Path ) -> TlsSubconfig {
+    TlsSubconfig {
+        sock_addrs:         "127.0.0.1:0".parse().unwrap(),
+        cert_path:          FilePath::try_from( dir.join( "cert.pem" ) ).unwrap(),
+        key_path:           FilePath::try_from( dir.join( "key.pem" ) ).unwrap(),
+        client_ca_path:     None,
+        redirect_sock_addr: None,
+    }
+}
+
+/// Binds an HTTPS listener on a port picked by the system, and serves a route answering `served`.
+fn serve_https( tls: &tls::Tls ) -> SocketAddr {
+    let listener = Listener::bind_tls( &"127.0.0.1:0".parse().unwrap(), tls.acceptor() ).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route( "/", get( || async { "served" } ) );
+    tokio::spawn( listener.serve( app, Shutdown::default(), false ) );
+
+    addr
+}
+
+/// Connects to the HTTPS listener trusting the `certificate`, and offering the `protocols` through ALPN.
+async fn connect(
+    addr: SocketAddr,
+    certificate: &Certificate,
+    protocols: &[&[u8]],
+) -> io::Result<TlsStream<TcpStream>> {
+    let mut roots = RootCertStore::empty();
+    roots.add( certificate ).unwrap();
+    let mut config = ClientConfig::builder().with_safe_defaults().with_root_certificates( roots ).with_no_client_auth();
+    config.alpn_protocols = protocols.iter().map( |protocol| protocol.to_vec() ).collect();
+
+    let stream = TcpStream::connect( addr ).await.unwrap();
+    TlsConnector::from( Arc::new( config ) ).connect( ServerName::try_from( "localhost" ).unwrap(), stream ).await
+}
+
+async fn send_get( stream: TlsStream<TcpStream>, is_http2: bool ) -> String {
+    let ( mut sender, connection ) = conn::Builder::new().http2_only( is_http2 ).handshake( stream ).await.unwrap();
+    tokio::spawn( connection );
+
+    let request = Request::builder().uri( "https://localhost/" ).body( Body::empty() ).unwrap();
+    let response = sender.send_request( request ).await.unwrap();
+    let response_body = body::to_bytes( response.into_body() ).await.unwrap();
+
+    String::from_utf8( response_body.to_vec() ).unwrap()
+}
+
+#[tokio::test]
+async fn https_listeners_negotiate_http2_and_http1_with_alpn() {
+    let dir = create_certificates_dir( "alpn" );
+    let certificate = write_certificate( &dir );
+    let tls = tls::Tls::new( &create_tls_settings( &dir ) ).unwrap();
+    let addr = serve_https( &tls );
+
+    let stream = connect( addr, &certificate, &[b"h2", b"http/1.1"] ).await.unwrap();
+    assert_eq!( stream.get_ref().1.alpn_protocol(), Some( b"h2".as_slice() ) );
+    assert_eq!( send_get( stream, true ).await, "served" );
+
+    let stream = connect( addr, &certificate, &[b"http/1.1"] ).await.unwrap();
+    assert_eq!( stream.get_ref().1.alpn_protocol(), Some( b"http/1.1".as_slice() ) );
+    assert_eq!( send_get( stream, false ).await, "served" );
+}
+
+#[tokio::test]
+async fn renewed_certificates_are_served_without_a_restart() {
+    let dir = create_certificates_dir( "reload" );
+    let certificate = write_certificate( &dir );
+    let tls = tls::Tls::new( &create_tls_settings( &dir ) ).unwrap();
+    let addr = serve_https( &tls );
+
+    let stream = connect( addr, &certificate, &[b"http/1.1"] ).await.unwrap();
+    assert_eq!( stream.get_ref().1.peer_certificates(), Some( [certificate].as_slice() ) );
+
+    // The handshake only succeeds once the renewed certificate is served, as the client only trusts it.
+    let renewed_certificate = write_certificate( &dir );
+    let deadline = Instant::now() + Duration::from_secs( 10 );
+    while connect( addr, &renewed_certificate, &[b"http/1.1"] ).await.is_err() {
+        assert!( Instant::now() < deadline, "The renewed certificate was not served." );
+        tokio::time::sleep( Duration::from_millis( 100 ) ).await;
+    }
+}
+
+#[tokio::test]
+async fn invalid_certificates_are_rejected_at_startup() {
+    let dir = create_certificates_dir( "invalid" );
+    write_certificate( &dir );
+    fs::write( dir.join( "key.pem" ), "not a key" ).unwrap();
+
+    assert!( matches!( tls::Tls::new( &create_tls_settings( &dir ) ), Err( tls::Error::NoPrivateKey( _ ) ) ) );
+}
+
+#[tokio::test]
+async fn clients_trust_the_certificates_of_their_ca_file() {
+    let dir = create_certificates_dir( "client" );
+    write_certificate( &dir );
+    let tls = tls::Tls::new( &create_tls_settings( &dir ) ).unwrap();
+    let addr = serve_https( &tls );
+
+    let connector = tls::create_connector( &dir.join( "cert.pem" ) ).unwrap();
+    let stream = TcpStream::connect( addr ).await.unwrap();
+    let stream = connector.connect( ServerName::try_from( "localhost" ).unwrap(), stream ).await.unwrap();
+    assert_eq!( send_get( stream, false ).await, "served" );
+
+    assert!( matches!( tls::create_connector( &dir.join( "key.pem" ) ), Err( tls::Error::NoCertificate( _ ) ) ) );
+}
+
+async fn redirect( https_port: u16, host: Option<&str>, uri: &str ) -> ( StatusCode, Option<String> ) {
+    let mut request = Request::builder().uri( uri );
+    if let Some( host ) = host {
+        request = request.header( header::HOST, host );
+    }
+    let response = tls::create_redirect_route( https_port )
+        .oneshot( request.body( Body::empty() ).unwrap() )
+        .await
+        .unwrap();
+    let location =
+        response.headers().get( header::LOCATION ).map( |location| location.to_str().unwrap().to_string() );
+
+    ( response.status(), location )
+}
+
+#[tokio::test]
+async fn plain_requests_are_redirected_to_https() {
+    assert_eq!(
+        redirect( 8443, Some( "example.com:8080" ), "/photos?page=2" ).await,
+        ( StatusCode::PERMANENT_REDIRECT, Some( "https://example.com:8443/photos?page=2".to_string() ) )
+    );
+    assert_eq!(
+        redirect( 443, Some( "example.com" ), "/" ).await,
+        ( StatusCode::PERMANENT_REDIRECT, Some( "https://example.com/".to_string() ) )
+    );
+    assert_eq!( redirect( 443, None, "/" ).await, ( StatusCode::BAD_REQUEST, None ) );
+}
@@ -0,0 +1,93 @@
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::validators;
+
+/// Error type for the [`CompressionSubconfig::validate`] function.
+#[derive(Error, Debug)]
+pub enum CompressionError {
+    /// The level is out of the scale of the algorithm.
+    #[error( "The {0} compression level must be between {min} and {max}, got {1}.", min = .2.start(), max = .2.end() )]
+    InvalidLevel( &'static str, u32, RangeInclusive<u32> ),
+    /// The minimum size doesn't fit the 16 bits of the compression predicate.
+    #[error( "The minimum size of the compressed responses must be at most {max}B, got {0}.", max = u16::MAX )]
+    MinSizeTooLarge( validators::ByteSize ),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompressionSubconfig {
+    /// The algorithms negotiated with the clients, by order of preference when they accept several with the same
+    /// q-value: `br`, `zstd` or `gzip`. The responses are not compressed if it is empty.
+    pub algorithms: Vec<CompressionAlgorithm>,
+    /// The level of the algorithms, whose scale depends on each of them: 0 to 11 for `br`, 1 to 22 for `zstd` and 0
+    /// to 9 for `gzip`. An algorithm without level uses its default one.
+    pub levels:     BTreeMap<CompressionAlgorithm, u32>,
+    /// Size below which the responses are not compressed, at most `65535B`.
+    pub min_bytes:  validators::ByteSize,
+}
+
+impl CompressionSubconfig {
+    /// Checks the levels against the scale of their algorithm, and the minimum size.
+    ///
+    /// # Errors
+    ///
+    /// If a level is out of the scale of its algorithm, or if the minimum size is over 65535 bytes.
+    pub fn validate( &self ) -> Result<(), CompressionError> {
+        for ( &algorithm, &level ) in &self.levels {
+            let levels = algorithm.levels();
+            if !levels.contains( &level ) {
+                return Err( CompressionError::InvalidLevel( algorithm.as_str(), level, levels ) );
+            }
+        }
+        if u16::try_from( self.min_bytes.as_u64() ).is_err() {
+            return Err( CompressionError::MinSizeTooLarge( self.min_bytes ) );
+        }
+
+        Ok( () )
+    }
+}
+
+impl Default for CompressionSubconfig {
+    fn default() -> Self {
+        Self {
+            algorithms: vec![CompressionAlgorithm::Brotli, CompressionAlgorithm::Zstd, CompressionAlgorithm::Gzip],
+            levels:     BTreeMap::from( [( CompressionAlgorithm::Brotli, 4 )] ),
+            min_bytes:  validators::ByteSize::new( 1024 ),
+        }
+    }
+}
+
+/// A compression algorithm, named by its `Content-Encoding` token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CompressionAlgorithm {
+    #[serde( rename = "br" )]
+    Brotli,
+    #[serde( rename = "zstd" )]
+    Zstd,
+    #[serde( rename = "gzip" )]
+    Gzip,
+}
+
+impl CompressionAlgorithm {
+    /// The `Content-Encoding` token of the algorithm.
+    #[must_use]
+    pub const fn as_str( self ) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// The scale of the levels of the algorithm.
+    #[must_use]
+    pub const fn levels( self ) -> RangeInclusive<u32> {
+        match self {
+            Self::Brotli => 0..=11,
+            Self::Zstd => 1..=22,
+            Self::Gzip => 0..=9,
+        }
+    }
+}
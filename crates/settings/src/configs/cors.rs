@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{validators, LayeredSettings};
+
+/// The token allowing any method or header.
+const WILDCARD: &str = "*";
+
+/// Error type for the [`CorsConfigs::validate`] function.
+#[derive(Error, Debug)]
+#[error( "The credentials can't be allowed with any {0}, the browsers reject the wildcard in that case." )]
+pub struct CorsCredentialsError( &'static str );
+
+/// The cross-origin requests allowed. An empty list of origins denies all of them.
+#[derive(Debug, Serialize, Deserialize, Clone, LayeredSettings)]
+pub struct CorsConfigs {
+    /// The origins allowed, e.g. `https://example.com`, `https://*.example.com` for its subdomains, or `*` for any.
+    pub allowed_origins:   Vec<validators::Origin>,
+    /// The methods allowed, or `*` for any.
+    pub allowed_methods:   Vec<validators::HttpToken>,
+    /// The request headers allowed, or `*` for any.
+    pub allowed_headers:   Vec<validators::HttpToken>,
+    /// Whether the requests can include the cookies and the authorization header. Can't be combined with a wildcard.
+    pub allow_credentials: bool,
+    /// How long the browsers cache the preflight responses, e.g. `1h`.
+    pub max_age:           validators::Duration,
+}
+
+impl CorsConfigs {
+    /// Whether any method is allowed.
+    #[must_use]
+    pub fn allows_any_method( &self ) -> bool { is_wildcard( &self.allowed_methods ) }
+
+    /// Whether any request header is allowed.
+    #[must_use]
+    pub fn allows_any_header( &self ) -> bool { is_wildcard( &self.allowed_headers ) }
+
+    /// Checks the settings the browsers would reject, as the credentials can't be allowed with a wildcard.
+    ///
+    /// # Errors
+    ///
+    /// If the credentials are allowed with any origin, method or header.
+    pub fn validate( &self ) -> Result<(), CorsCredentialsError> {
+        if !self.allow_credentials {
+            return Ok( () );
+        }
+
+        if self.allowed_origins.iter().any( validators::Origin::is_any ) {
+            return Err( CorsCredentialsError( "origin" ) );
+        }
+        if self.allows_any_method() {
+            return Err( CorsCredentialsError( "method" ) );
+        }
+        if self.allows_any_header() {
+            return Err( CorsCredentialsError( "header" ) );
+        }
+
+        Ok( () )
+    }
+}
+
+impl Default for CorsConfigs {
+    fn default() -> Self {
+        let tokens = |tokens: &[&str]| tokens.iter().filter_map( |token| token.parse().ok() ).collect();
+
+        Self {
+            allowed_origins:   Vec::new(),
+            allowed_methods:   tokens( &["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"] ),
+            allowed_headers:   tokens( &["content-type", "authorization"] ),
+            allow_credentials: false,
+            max_age:           validators::Duration::from_secs( 3600 ),
+        }
+    }
+}
+
+fn is_wildcard( tokens: &[validators::HttpToken] ) -> bool { tokens.iter().any( |token| token.as_str() == WILDCARD ) }
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+use crate::validators;
+
+/// The export of the traces to a Jaeger agent.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoggerTracesExportedSubconfig {
+    pub is_exported:    bool,
+    /// The UDP address of the Jaeger agent.
+    pub agent_endpoint: String,
+    /// Ratio of the root traces sampled, between 0 and 1.
+    pub sampling_ratio: validators::Ratio,
+    pub service_name:   String,
+}
+
+impl LoggerTracesExportedSubconfig {
+    /// The defaults, with the traces reported under `service_name`.
+    #[must_use]
+    pub fn new( service_name: &str ) -> Self {
+        Self {
+            is_exported:    false,
+            agent_endpoint: "127.0.0.1:6831".to_string(),
+            sampling_ratio: validators::Ratio::ALL,
+            service_name:   service_name.to_string(),
+        }
+    }
+}
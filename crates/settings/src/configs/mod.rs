@@ -0,0 +1,11 @@
+//! Configs shared by the servers, either whole settings sections or subconfigs to be nested in their sections.
+
+mod compression;
+mod cors;
+mod logger;
+mod tls;
+
+pub use compression::{CompressionAlgorithm, CompressionError, CompressionSubconfig};
+pub use cors::{CliArgsCors, CorsConfigs, CorsCredentialsError};
+pub use logger::LoggerTracesExportedSubconfig;
+pub use tls::TlsSubconfig;
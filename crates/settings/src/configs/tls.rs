@@ -0,0 +1,21 @@
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::validators;
+
+/// The HTTPS listeners of a server.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TlsSubconfig {
+    /// The HTTPS listen addresses, IPv4 or IPv6.
+    pub sock_addrs:         validators::SocketAddrs,
+    /// PEM file of the certificate chain, read again when it changes.
+    pub cert_path:          validators::FilePath,
+    /// PEM file of the private key, read again when it changes.
+    pub key_path:           validators::FilePath,
+    /// PEM file of the CA certificates of the clients. If it is set, the clients must authenticate with a certificate
+    /// it signed.
+    pub client_ca_path:     Option<validators::FilePath>,
+    /// Address of a plain HTTP listener redirecting the requests to the first HTTPS listen address.
+    pub redirect_sock_addr: Option<SocketAddr>,
+}
@@ -0,0 +1,63 @@
+//! Settings sections loaded from the same layers of sources.
+//!
+//! A section is a struct deriving [`LayeredSettings`](settings_derive::LayeredSettings), which generates its command
+//! line arguments from the annotated fields. The section is then read from its configuration file, the secret files,
+//! the environment variables prefixed with the section name and the command line arguments, see
+//! [`LayeredSettings::load`].
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ConfigReport, FigmentExtractionFailedError, FigmentExtractor, Profiles, RuntimeEnvironment};
+
+/// The sources shared by all the sections of the settings.
+#[derive(Debug, Clone, Copy)]
+pub struct Layers<'a> {
+    /// The directory of the configuration files.
+    pub configs_dir: &'a Path,
+    /// The prefix of the environment variables, e.g. `BACKEND`.
+    pub env_prefix:  &'a str,
+    /// The directory of the secret files.
+    pub secrets_dir: &'a Path,
+    /// The declared profiles, see [`Profiles::load`].
+    pub profiles:    &'a Profiles,
+    /// The runtime environment, which selects the profile of the configuration files.
+    pub run_env:     Option<&'a RuntimeEnvironment>,
+}
+
+/// A section of the settings, usually derived.
+pub trait LayeredSettings: Sized + Default + Serialize + Deserialize<'static> + for<'a> FigmentExtractor<'a, Self> {
+    /// The command line arguments of the section.
+    type CliArgs: Serialize;
+
+    /// The configuration file of the section, in the configs directory.
+    const FILE_NAME: &'static str;
+    /// The name of the section, e.g. `server`. It is the key of the section in the report, and is in the prefix of the
+    /// environment variables, e.g. `BACKEND_SERVER_`.
+    const SECTION: &'static str;
+
+    /// Loads the section from the `layers` and the `cli_args`.
+    ///
+    /// # Errors
+    ///
+    /// If the import failed, then return [`FigmentExtractionFailedError`].
+    fn load(
+        layers: &Layers<'_>,
+        cli_args: &Self::CliArgs,
+    ) -> Result<( Self, ConfigReport ), FigmentExtractionFailedError> {
+        let env_prefix = format!( "{}_{}_", layers.env_prefix, Self::SECTION.to_uppercase() );
+
+        let mut extract = Self::extract()
+            .file( layers.configs_dir.join( Self::FILE_NAME ) )
+            .secrets_dir( layers.secrets_dir.to_path_buf() )
+            .env_prefix( &env_prefix )
+            .profiles( layers.profiles )
+            .cli( cli_args );
+        if let Some( run_env ) = layers.run_env {
+            extract = extract.env( run_env );
+        }
+
+        extract.call_with_report()
+    }
+}
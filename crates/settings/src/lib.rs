@@ -9,41 +9,31 @@ use std::{cell::OnceCell, env, marker::PhantomData, path::PathBuf, sync::OnceLoc
 use derive_builder::Builder;
 use figment::{
     providers::{Env, Format, Serialized, Toml},
-    Figment,
+    value::{Dict, Map},
+    Figment, Metadata, Profile, Provider,
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-pub mod validators;
-
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
-#[serde( rename_all = "lowercase" )]
-pub enum RuntimeEnvironment {
-    Development,
-    Production,
-}
+pub use layered::{LayeredSettings, Layers};
+pub use profile::{ProfileError, Profiles, RuntimeEnvironment, PROFILES_FILE_NAME};
+pub use report::{ConfigReport, Origin, ReportEntry, ReportSerializationError};
+use report::{CLI_PROVIDER_NAME, DEFAULTS_PROVIDER_NAME};
+pub use secret::{Masked, Secret};
+use secret::SecretFile;
+pub use settings_derive::LayeredSettings;
+pub use watcher::{is_file_change, wait_for_change, ConfigWatcher, WatchError};
 
-impl Default for RuntimeEnvironment {
-    fn default() -> Self { Self::Development }
-}
+// The derive of the shared settings sections refers to this crate by its name.
+extern crate self as settings;
 
-impl std::fmt::Display for RuntimeEnvironment {
-    fn fmt( &self, f: &mut std::fmt::Formatter ) -> std::fmt::Result {
-        match self {
-            Self::Development => write!( f, "development" ),
-            Self::Production => write!( f, "production" ),
-        }
-    }
-}
-
-impl From<&str> for RuntimeEnvironment {
-    fn from( env: &str ) -> Self {
-        match env.to_lowercase().as_str() {
-            "production" | "prod" => Self::Production,
-            _ => Self::Development,
-        }
-    }
-}
+pub mod configs;
+mod layered;
+mod profile;
+mod report;
+mod secret;
+pub mod validators;
+mod watcher;
 
 /// Error type for the [`get_configs_dir_path`] function.
 #[derive(Error, Debug)]
@@ -154,8 +144,10 @@ pub trait FigmentExtractor<'a, T: Default + Serialize + Deserialize<'static>> {
     /// # Builder methods
     ///
     /// - `env()` - The runtime environment.
+    /// - `profiles()` - The profiles the runtime environment can extend.
     /// - `env_prefix()` - The prefix for environment variables.
     /// - `file()` - The path to the file with settings.
+    /// - `secrets_dir()` - The directory with the secret files.
     /// - `cli()` - The command line arguments.
     #[must_use]
     fn extract<U: Serialize>() -> ExtractBuilder<'a, T, U> { ExtractBuilder::<T, U>::default() }
@@ -163,10 +155,13 @@ pub trait FigmentExtractor<'a, T: Default + Serialize + Deserialize<'static>> {
 
 /// Error type for the function [`FigmentExtractor::extract`].
 #[derive(Error, Debug)]
-#[error( "Failed to extract settings due to the figment error: {source}." )]
-pub struct FigmentExtractionFailedError {
-    #[from]
-    source: figment::Error,
+pub enum FigmentExtractionFailedError {
+    #[error( "Failed to extract settings due to the figment error: {0}." )]
+    Figment( Box<figment::Error> ),
+    #[error( "Failed to report the settings: {0}." )]
+    Report( #[from] ReportSerializationError ),
+    #[error( "Failed to select the profile of the runtime environment: {0}" )]
+    Profile( #[from] ProfileError ),
 }
 
 #[derive(Default, Builder)]
@@ -175,12 +170,19 @@ pub struct Extract<'a, T: Default + Serialize + Deserialize<'static>, U: Seriali
     /// The runtime environment.
     #[builder( setter( into, strip_option ), default )]
     env:          Option<&'a RuntimeEnvironment>,
+    /// The declared profiles. Without them, the profile of the runtime environment extends no other one.
+    #[builder( setter( strip_option ), default )]
+    profiles:     Option<&'a Profiles>,
     /// The prefix for environment variables.
     #[builder( setter( strip_option ), default )]
     env_prefix:   Option<&'a str>,
     /// File path to load settings from. If file does not exist, the setting is ignored.
     #[builder( setter( strip_option ), default )]
     file:         Option<PathBuf>,
+    /// Directory with the secret files, named like the environment variables in lowercase. If the directory does not
+    /// exist, it is ignored.
+    #[builder( setter( strip_option ), default )]
+    secrets_dir:  Option<PathBuf>,
     /// The command line arguments.
     #[builder( setter( strip_option ), default )]
     cli:          Option<U>,
@@ -198,64 +200,146 @@ where
     /// The order of priority is as follows:
     /// 1. Default settings.
     /// 2. Load settings from file.
-    /// 3. Profile is the environment variables.
-    /// 4. Profile is the command line arguments.
+    /// 3. Secret files of the secrets directory.
+    /// 4. Profile is the environment variables.
+    /// 5. Secret files of the `_FILE` environment variables.
+    /// 6. Profile is the command line arguments.
     ///
     /// # Errors
     ///
     /// If the import failed, then return [`FigmentExtractionFailedError`].
     pub fn call( self ) -> Result<T, FigmentExtractionFailedError> {
+        self.call_with_figment().map( |( settings, _ )| settings )
+    }
+
+    /// Import settings like [`call`](Self::call), along with the report of the source of each value.
+    ///
+    /// # Errors
+    ///
+    /// If the import failed or the settings can't be represented in TOML, then return
+    /// [`FigmentExtractionFailedError`].
+    pub fn call_with_report( self ) -> Result<( T, ConfigReport ), FigmentExtractionFailedError> {
+        let ( settings, figment ) = self.call_with_figment()?;
+        let report = ConfigReport::new( &settings, &figment )?;
+
+        Ok( ( settings, report ) )
+    }
+
+    fn call_with_figment( self ) -> Result<( T, Figment ), FigmentExtractionFailedError> {
         let data = self.build().expect( "Extract build failed. This should never fail." );
 
         let mut figment = Figment::new();
 
-        // Profile is the runtime environment.
-        if let Some( run_env ) = data.env {
-            let run_env = run_env.to_string();
-            figment = figment.select( run_env );
-        }
+        // Profile is the runtime environment, applied after the profiles it extends.
+        let profiles = match data.env {
+            Some( run_env ) => {
+                figment = figment.select( run_env.to_string() );
+                match data.profiles {
+                    Some( declared_profiles ) => declared_profiles.resolve( run_env )?,
+                    None => vec![run_env.clone()],
+                }
+            }
+            None => Vec::new(),
+        };
 
         // Load settings from file.
         match data.file {
             Some( file_path ) if file_path.exists() => {
-                let profile = figment.profile().clone().to_string();
-                let figment_file = Figment::new().merge( Toml::file( file_path ) ).select( &profile );
+                let figment_file = Figment::new().merge( Toml::file( file_path ) );
 
-                // If default top level key is found, merge it first.
-                if figment_file.find_value( "default" ).is_ok() {
-                    figment = figment.merge( figment_file.focus( "default" ) );
+                // If default top level key is found, merge it first, then the profiles from the most generic one.
+                let profiles = profiles.iter().map( ToString::to_string );
+                for profile in std::iter::once( "default".to_string() ).chain( profiles ) {
+                    if figment_file.find_value( &profile ).is_ok() {
+                        figment = figment.merge( figment_file.focus( &profile ) );
+                    }
                 }
-
-                figment = figment.merge( figment_file.focus( &profile ) );
             }
             // Ignore if file does not exist.
             _ => {}
         }
 
-        // Load settings from environment variables.
+        // Load secrets from the secrets directory.
+        if let Some( secrets_dir ) = &data.secrets_dir {
+            for secret_file in SecretFile::from_dir( secrets_dir, data.env_prefix.unwrap_or_default() ) {
+                figment = figment.merge( secret_file );
+            }
+        }
+
+        // Load settings from environment variables, then the secrets from the files they point to.
         if let Some( env_prefix ) = data.env_prefix {
-            figment = figment.merge( Env::prefixed( env_prefix ) );
+            figment = figment.merge( WithMetadata::env( env_prefix ) );
+
+            for secret_file in SecretFile::from_env( env_prefix ) {
+                figment = figment.merge( secret_file );
+            }
         }
 
         // Load settings from command line arguments.
         if let Some( cli_args ) = data.cli {
-            figment = figment.merge( Serialized::defaults( cli_args ) );
+            figment = figment.merge( WithMetadata::named( CLI_PROVIDER_NAME, Serialized::defaults( cli_args ) ) );
         }
 
         // Extract settings to struct T.
-        figment.extract::<T>().or_else( |err| {
-            // If extraction failed due to missing values try to join with default settings.
+        let settings = figment.extract::<T>().or_else( |err| {
+            // If extraction failed due to missing values, fill them with the default settings. The report tells which
+            // values came from the defaults.
             if err.missing() {
-                eprintln!(
-                    "Failed to extract settings due to missing fields ({}).\nTrying to join with default settings...",
-                    err.kind
-                );
-                figment = figment.join( Serialized::defaults( T::default() ) );
+                figment = figment
+                    .clone()
+                    .join( WithMetadata::named( DEFAULTS_PROVIDER_NAME, Serialized::defaults( T::default() ) ) );
             }
 
-            figment
-                .extract::<T>()
-                .map_err( |err| FigmentExtractionFailedError { source: err } )
-        } )
+            figment.extract::<T>()
+        } )?;
+
+        Ok( ( settings, figment ) )
+    }
+}
+
+impl From<figment::Error> for FigmentExtractionFailedError {
+    fn from( err: figment::Error ) -> Self { Self::Figment( Box::new( err ) ) }
+}
+
+/// Provider wrapper replacing the metadata of the inner provider, so the origin of its values can be told apart.
+struct WithMetadata<P> {
+    metadata: Metadata,
+    provider: P,
+}
+
+impl<P> WithMetadata<P> {
+    fn named( name: &'static str, provider: P ) -> Self {
+        Self {
+            metadata: Metadata::named( name ),
+            provider,
+        }
+    }
+}
+
+impl WithMetadata<Env> {
+    /// The environment variables with the `prefix`. Unlike the metadata of [`Env`], the interpolation gives the full
+    /// name of the variable, e.g. `BACKEND_SERVER_ADMIN_TOKEN` for the key `admin_token`.
+    fn env( prefix: &str ) -> Self {
+        let variables_prefix = prefix.to_ascii_uppercase();
+        let metadata = Metadata::named( format!( "`{variables_prefix}` environment variable(s)" ) ).interpolater(
+            move |_: &Profile, keys: &[&str]| {
+                // Nested values are set by the variable of their top level key.
+                let key = keys.first().copied().unwrap_or_default();
+                format!( "{variables_prefix}{}", key.to_ascii_uppercase() )
+            },
+        );
+
+        Self {
+            metadata,
+            provider: Env::prefixed( prefix ),
+        }
     }
 }
+
+impl<P: Provider> Provider for WithMetadata<P> {
+    fn metadata( &self ) -> Metadata { self.metadata.clone() }
+
+    fn data( &self ) -> Result<Map<Profile, Dict>, figment::Error> { self.provider.data() }
+
+    fn profile( &self ) -> Option<Profile> { self.provider.profile() }
+}
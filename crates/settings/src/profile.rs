@@ -0,0 +1,200 @@
+//! Runtime environments and the profiles they select in the configuration files.
+//!
+//! The configuration files have a `default` table, and one table per profile overriding it. The runtime environment
+//! selects the profile, either a built-in one or a custom one, e.g. per developer. The custom profiles are declared in
+//! the [`PROFILES_FILE_NAME`] file of the configs directory, where a profile can also extend another one:
+//!
+//! ```toml
+//! [staging]
+//! extends = "production"
+//!
+//! [alice]
+//! extends = "development"
+//! ```
+//!
+//! The values of a profile then override the ones of the profile it extends, in every configuration file.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The file of the configs directory declaring the custom profiles and the profiles they extend.
+pub const PROFILES_FILE_NAME: &str = "profiles.toml";
+
+/// The table of the configuration files with the values of all the profiles.
+const DEFAULT_PROFILE: &str = "default";
+
+/// Error type of the runtime environments and the profiles.
+#[derive(Error, Debug)]
+pub enum ProfileError {
+    /// The name can't be a profile.
+    #[error(
+        "The runtime environment name is not valid: {0:?}. It must only have lowercase letters, digits, - and _."
+    )]
+    InvalidName( String ),
+    /// The custom profile is not declared.
+    #[error(
+        "Unknown runtime environment {0}, expected development, staging, production, test or a profile declared in \
+         {PROFILES_FILE_NAME}."
+    )]
+    Unknown( String ),
+    /// The profiles extend each other.
+    #[error( "The profiles extend each other: {0}." )]
+    Cycle( String ),
+    /// The profiles file can't be read.
+    #[error( "Failed to read the profiles file {0}: {1}" )]
+    Read( PathBuf, #[source] io::Error ),
+    /// The profiles file is not valid.
+    #[error( "Failed to parse the profiles file {0}: {1}" )]
+    Parse( PathBuf, #[source] toml::de::Error ),
+}
+
+/// The runtime environment, which selects the profile of the configuration files.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum RuntimeEnvironment {
+    #[default]
+    Development,
+    Staging,
+    Production,
+    Test,
+    /// A profile declared in the [`PROFILES_FILE_NAME`] file, e.g. per developer.
+    Custom( String ),
+}
+
+impl RuntimeEnvironment {
+    /// Whether the environment serves real users, i.e. production and staging.
+    #[must_use]
+    pub const fn is_production( &self ) -> bool { matches!( self, Self::Production | Self::Staging ) }
+}
+
+impl std::fmt::Display for RuntimeEnvironment {
+    fn fmt( &self, f: &mut std::fmt::Formatter ) -> std::fmt::Result {
+        match self {
+            Self::Development => write!( f, "development" ),
+            Self::Staging => write!( f, "staging" ),
+            Self::Production => write!( f, "production" ),
+            Self::Test => write!( f, "test" ),
+            Self::Custom( name ) => write!( f, "{name}" ),
+        }
+    }
+}
+
+impl FromStr for RuntimeEnvironment {
+    type Err = ProfileError;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "development" | "dev" => Ok( Self::Development ),
+            "staging" | "stage" => Ok( Self::Staging ),
+            "production" | "prod" => Ok( Self::Production ),
+            "test" => Ok( Self::Test ),
+            _ => {
+                let is_valid = !s.is_empty()
+                    && s != DEFAULT_PROFILE
+                    && s.chars().all( |c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_' );
+                if !is_valid {
+                    return Err( ProfileError::InvalidName( s.to_string() ) );
+                }
+
+                Ok( Self::Custom( s.to_string() ) )
+            }
+        }
+    }
+}
+
+impl Serialize for RuntimeEnvironment {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str( self )
+    }
+}
+
+impl<'de> Deserialize<'de> for RuntimeEnvironment {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize( deserializer )?;
+        name.parse().map_err( serde::de::Error::custom )
+    }
+}
+
+/// A profile of the [`PROFILES_FILE_NAME`] file.
+#[derive(Deserialize)]
+#[serde( deny_unknown_fields )]
+struct ProfileDeclaration {
+    extends: Option<RuntimeEnvironment>,
+}
+
+/// The profiles declared in the [`PROFILES_FILE_NAME`] file, and the profile each one extends.
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    parents: BTreeMap<String, Option<RuntimeEnvironment>>,
+}
+
+impl Profiles {
+    /// Reads the [`PROFILES_FILE_NAME`] file of the `configs_dir`. Without the file, only the built-in profiles exist
+    /// and none extends another.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or is not valid.
+    pub fn load( configs_dir: &Path ) -> Result<Self, ProfileError> {
+        let path = configs_dir.join( PROFILES_FILE_NAME );
+        if !path.exists() {
+            return Ok( Self::default() );
+        }
+
+        let content = fs::read_to_string( &path ).map_err( |err| ProfileError::Read( path.clone(), err ) )?;
+        let declarations: BTreeMap<String, ProfileDeclaration> =
+            toml::from_str( &content ).map_err( |err| ProfileError::Parse( path, err ) )?;
+
+        let mut parents = BTreeMap::new();
+        for ( name, declaration ) in declarations {
+            // The names are stored like they are displayed, e.g. `prod` is `production`.
+            let profile: RuntimeEnvironment = name.parse()?;
+            parents.insert( profile.to_string(), declaration.extends );
+        }
+
+        Ok( Self { parents } )
+    }
+
+    /// The profiles applied for the `run_env`, from the one it extends first to itself.
+    ///
+    /// # Errors
+    ///
+    /// If a custom profile is not declared, or if the profiles extend each other.
+    pub fn resolve( &self, run_env: &RuntimeEnvironment ) -> Result<Vec<RuntimeEnvironment>, ProfileError> {
+        let mut chain = vec![run_env.clone()];
+
+        let mut current = run_env;
+        loop {
+            let declaration = self.parents.get( &current.to_string() );
+            if declaration.is_none() && matches!( current, RuntimeEnvironment::Custom( _ ) ) {
+                return Err( ProfileError::Unknown( current.to_string() ) );
+            }
+
+            let Some( Some( parent ) ) = declaration else {
+                break;
+            };
+            if chain.contains( parent ) {
+                let names: Vec<String> = chain.iter().chain( [parent] ).map( ToString::to_string ).collect();
+                return Err( ProfileError::Cycle( names.join( " -> " ) ) );
+            }
+
+            chain.push( parent.clone() );
+            current = parent;
+        }
+
+        chain.reverse();
+        Ok( chain )
+    }
+}
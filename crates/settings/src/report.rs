@@ -0,0 +1,185 @@
+//! Report of the extracted settings and the source of each value.
+//!
+//! The origins are read from the figment metadata of every value, so the report works for any
+//! [`FigmentExtractor`](crate::FigmentExtractor) type. See [`ExtractBuilder::call_with_report`](crate::ExtractBuilder).
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt::{Display, Write},
+    path::PathBuf,
+};
+
+use figment::{Figment, Source};
+use monitoring::redact;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::Masked;
+
+/// Name of the provider with the default values.
+pub const DEFAULTS_PROVIDER_NAME: &str = "default values";
+/// Name of the provider with the command line arguments.
+pub const CLI_PROVIDER_NAME: &str = "command line arguments";
+
+/// Where a setting value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    File( PathBuf ),
+    /// The environment variable name.
+    Env( String ),
+    Cli,
+}
+
+impl Display for Origin {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        match self {
+            Self::Default => write!( f, "default" ),
+            Self::File( path ) => write!( f, "file {}", path.display() ),
+            Self::Env( name ) => write!( f, "env {name}" ),
+            Self::Cli => write!( f, "cli" ),
+        }
+    }
+}
+
+/// A setting value, in TOML, and where it came from.
+#[derive(Debug, Clone)]
+pub struct ReportEntry {
+    /// The dotted key of the setting, e.g. `server.metrics.is_exposed`.
+    pub key:    String,
+    pub value:  String,
+    pub origin: Origin,
+}
+
+/// Error type for the function [`ConfigReport::new`].
+#[derive(Error, Debug)]
+#[error( "Failed to serialize the settings into TOML: {0}" )]
+pub struct ReportSerializationError( #[from] toml::ser::Error );
+
+/// The extracted settings, one entry per value.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigReport {
+    entries: Vec<ReportEntry>,
+}
+
+impl ConfigReport {
+    /// Creates the report of the `settings` extracted from the `figment`.
+    ///
+    /// # Errors
+    ///
+    /// If the settings can't be represented in TOML.
+    pub fn new<T: Serialize>( settings: &T, figment: &Figment ) -> Result<Self, ReportSerializationError> {
+        let table = toml::Table::try_from( Masked( settings ) )?;
+
+        let mut entries = Vec::new();
+        flatten( "", &table, &mut |key, value| {
+            entries.push( ReportEntry {
+                origin: find_origin( figment, &key ),
+                key,
+                value: value.to_string(),
+            } );
+        } );
+
+        Ok( Self { entries } )
+    }
+
+    /// Adds the entries of another report under the `section`, e.g. `server`.
+    pub fn extend( &mut self, section: &str, report: Self ) {
+        self.entries.extend( report.entries.into_iter().map( |entry| ReportEntry {
+            key: format!( "{section}.{}", entry.key ),
+            ..entry
+        } ) );
+    }
+
+    #[must_use]
+    pub fn entries( &self ) -> &[ReportEntry] { &self.entries }
+
+    /// Replaces the values for which `mask` returns a replacement, e.g. to hide the secrets before printing.
+    pub fn mask( &mut self, mask: impl Fn( &ReportEntry ) -> Option<String> ) {
+        for entry in &mut self.entries {
+            if let Some( masked ) = mask( entry ) {
+                entry.value = masked;
+            }
+        }
+    }
+
+    /// Renders the report as TOML, one table per section and the origin of each value in a comment.
+    #[must_use]
+    pub fn to_toml( &self ) -> String {
+        let mut sections: BTreeMap<&str, Vec<&ReportEntry>> = BTreeMap::new();
+        for entry in &self.entries {
+            let section = entry.key.split_once( '.' ).map_or( "", |( section, _ )| section );
+            sections.entry( section ).or_default().push( entry );
+        }
+
+        let mut rendered = String::new();
+        for ( section, entries ) in sections {
+            if !section.is_empty() {
+                let _ = writeln!( rendered, "[{section}]" );
+            }
+
+            for entry in entries {
+                let key = entry.key.strip_prefix( &format!( "{section}." ) ).unwrap_or( &entry.key );
+                let _ = writeln!( rendered, "{key} = {} # {}", entry.value, entry.origin );
+            }
+
+            rendered.push( '\n' );
+        }
+
+        rendered
+    }
+
+    /// Renders the report as TOML with the secrets masked, for the `print-config` command.
+    ///
+    /// The values of the sensitive keys are replaced, and the passwords of the urls in the other values.
+    #[must_use]
+    pub fn to_masked_toml( &self ) -> String {
+        let mut report = self.clone();
+        report.mask( |entry| {
+            let name = entry.key.rsplit( '.' ).next().unwrap_or( &entry.key );
+            if redact::is_sensitive( name ) {
+                Some( format!( "\"{}\"", redact::REDACTED ) )
+            } else {
+                match redact::redact_url_credentials( &entry.value ) {
+                    Cow::Owned( value ) => Some( value ),
+                    Cow::Borrowed( _ ) => None,
+                }
+            }
+        } );
+
+        report.to_toml()
+    }
+}
+
+/// Calls `visit` with the dotted key of every value that is not a table.
+pub fn flatten( prefix: &str, table: &toml::Table, visit: &mut impl FnMut( String, &toml::Value ) ) {
+    for ( key, value ) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!( "{prefix}.{key}" )
+        };
+
+        match value {
+            toml::Value::Table( table ) => flatten( &key, table, visit ),
+            value => visit( key, value ),
+        }
+    }
+}
+
+fn find_origin( figment: &Figment, key: &str ) -> Origin {
+    let Some( metadata ) = figment.find_metadata( key ) else {
+        return Origin::Default;
+    };
+
+    match &metadata.source {
+        Some( Source::File( path ) ) => Origin::File( path.clone() ),
+        _ if metadata.name == DEFAULTS_PROVIDER_NAME => Origin::Default,
+        _ if metadata.name == CLI_PROVIDER_NAME => Origin::Cli,
+        _ => {
+            let keys: Vec<&str> = key.split( '.' ).collect();
+            Origin::Env( metadata.interpolate( figment.profile(), &keys ) )
+        }
+    }
+}
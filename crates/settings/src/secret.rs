@@ -0,0 +1,366 @@
+//! Secret settings.
+//!
+//! Secret values can be kept out of the configuration files and the environment:
+//! * The `<PREFIX><KEY>_FILE` environment variables hold the path of a file with the value, e.g.
+//!   `BACKEND_DATABASE_URL_FILE=/run/secrets/db_url`.
+//! * The files of the secrets directory are named like the environment variables in lowercase, e.g.
+//!   `/run/secrets/backend_database_url`, as mounted by the Docker secrets and the Kubernetes secret volumes.
+//!
+//! The value is the content of the file without the trailing whitespace, parsed like the value of an environment
+//! variable: `32` is a number and `true` a boolean, so a string reading as one of them must be quoted, e.g. `"123456"`.
+//! The fields holding secrets should be wrapped in [`Secret`], which keeps them out of the `Debug` output and the
+//! [`ConfigReport`](crate::ConfigReport).
+//!
+//! # Examples
+//!
+//! ```
+//! use settings::Secret;
+//!
+//! let password = Secret::new( "hunter2".to_string() );
+//! assert_eq!( format!( "{password:?}" ), "[REDACTED]" );
+//! assert_eq!( password.expose_secret(), "hunter2" );
+//! ```
+
+use std::{
+    env,
+    fmt::{self, Debug},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use figment::{
+    value::{Dict, Map, Value},
+    Metadata, Profile, Provider, Source,
+};
+use serde::{ser, Deserialize, Serialize, Serializer};
+
+/// Replacement of the secret values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Suffix of the environment variables holding the path of a secret file.
+const FILE_ENV_SUFFIX: &str = "_FILE";
+
+/// Name of the newtype struct [`Secret`] serializes as, so [`Masked`] can tell the secrets apart.
+const SECRET_NAME: &str = "$__settings_secret";
+
+/// Wrapper of a secret setting, printed as [`REDACTED`].
+///
+/// It is serialized with its value, so the settings can be merged and compared, and wrapped in [`Masked`] to be
+/// serialized as [`REDACTED`].
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde( transparent )]
+pub struct Secret<T>( T );
+
+impl<T> Secret<T> {
+    pub const fn new( value: T ) -> Self { Self( value ) }
+
+    /// The secret value. It should not be logged.
+    pub const fn expose_secret( &self ) -> &T { &self.0 }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from( value: T ) -> Self { Self( value ) }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt( &self, f: &mut fmt::Formatter<'_> ) -> fmt::Result { f.write_str( REDACTED ) }
+}
+
+impl<T: Serialize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>( &self, serializer: S ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct( SECRET_NAME, &self.0 )
+    }
+}
+
+/// Serializes the value with its secrets replaced by [`REDACTED`], e.g. to print the settings.
+///
+/// # Examples
+///
+/// ```
+/// use settings::{Masked, Secret};
+///
+/// let password = Secret::new( "hunter2".to_string() );
+/// assert_eq!( toml::Value::try_from( &password ).unwrap().as_str(), Some( "hunter2" ) );
+/// assert_eq!( toml::Value::try_from( Masked( &password ) ).unwrap().as_str(), Some( "[REDACTED]" ) );
+/// ```
+pub struct Masked<'a, T: ?Sized>( pub &'a T );
+
+impl<T: Serialize + ?Sized> Serialize for Masked<'_, T> {
+    fn serialize<S: Serializer>( &self, serializer: S ) -> Result<S::Ok, S::Error> {
+        self.0.serialize( MaskSerializer( serializer ) )
+    }
+}
+
+/// Serializer forwarding to the inner one, except the [`Secret`] values serialized as [`REDACTED`].
+struct MaskSerializer<S>( S );
+
+/// Forwards the serialization of the values without nested values.
+macro_rules! forward_serialize {
+    ( $( $method:ident( $( $arg:ident: $ty:ty ),* ); )* ) => {
+        $(
+            fn $method( self $( , $arg: $ty )* ) -> Result<Self::Ok, Self::Error> { self.0.$method( $( $arg ),* ) }
+        )*
+    };
+}
+
+impl<S: Serializer> Serializer for MaskSerializer<S> {
+    type Error = S::Error;
+    type Ok = S::Ok;
+    type SerializeMap = MaskCompound<S::SerializeMap>;
+    type SerializeSeq = MaskCompound<S::SerializeSeq>;
+    type SerializeStruct = MaskCompound<S::SerializeStruct>;
+    type SerializeStructVariant = MaskCompound<S::SerializeStructVariant>;
+    type SerializeTuple = MaskCompound<S::SerializeTuple>;
+    type SerializeTupleStruct = MaskCompound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = MaskCompound<S::SerializeTupleVariant>;
+
+    forward_serialize! {
+        serialize_bool( v: bool );
+        serialize_i8( v: i8 );
+        serialize_i16( v: i16 );
+        serialize_i32( v: i32 );
+        serialize_i64( v: i64 );
+        serialize_i128( v: i128 );
+        serialize_u8( v: u8 );
+        serialize_u16( v: u16 );
+        serialize_u32( v: u32 );
+        serialize_u64( v: u64 );
+        serialize_u128( v: u128 );
+        serialize_f32( v: f32 );
+        serialize_f64( v: f64 );
+        serialize_char( v: char );
+        serialize_str( v: &str );
+        serialize_bytes( v: &[u8] );
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct( name: &'static str );
+        serialize_unit_variant( name: &'static str, variant_index: u32, variant: &'static str );
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>( self, value: &T ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_some( &Masked( value ) )
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        if name == SECRET_NAME {
+            self.0.serialize_str( REDACTED )
+        } else {
+            self.0.serialize_newtype_struct( name, &Masked( value ) )
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.0.serialize_newtype_variant( name, variant_index, variant, &Masked( value ) )
+    }
+
+    fn serialize_seq( self, len: Option<usize> ) -> Result<Self::SerializeSeq, Self::Error> {
+        self.0.serialize_seq( len ).map( MaskCompound )
+    }
+
+    fn serialize_tuple( self, len: usize ) -> Result<Self::SerializeTuple, Self::Error> {
+        self.0.serialize_tuple( len ).map( MaskCompound )
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.0.serialize_tuple_struct( name, len ).map( MaskCompound )
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        self.0.serialize_tuple_variant( name, variant_index, variant, len ).map( MaskCompound )
+    }
+
+    fn serialize_map( self, len: Option<usize> ) -> Result<Self::SerializeMap, Self::Error> {
+        self.0.serialize_map( len ).map( MaskCompound )
+    }
+
+    fn serialize_struct( self, name: &'static str, len: usize ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.0.serialize_struct( name, len ).map( MaskCompound )
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.0.serialize_struct_variant( name, variant_index, variant, len ).map( MaskCompound )
+    }
+
+    fn is_human_readable( &self ) -> bool { self.0.is_human_readable() }
+}
+
+/// Serializer of the nested values of a [`MaskSerializer`], which masks them too.
+struct MaskCompound<C>( C );
+
+impl<C: ser::SerializeSeq> ser::SerializeSeq for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_element<T: Serialize + ?Sized>( &mut self, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_element( &Masked( value ) )
+    }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+impl<C: ser::SerializeTuple> ser::SerializeTuple for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_element<T: Serialize + ?Sized>( &mut self, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_element( &Masked( value ) )
+    }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+impl<C: ser::SerializeTupleStruct> ser::SerializeTupleStruct for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_field<T: Serialize + ?Sized>( &mut self, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_field( &Masked( value ) )
+    }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+impl<C: ser::SerializeTupleVariant> ser::SerializeTupleVariant for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_field<T: Serialize + ?Sized>( &mut self, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_field( &Masked( value ) )
+    }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+impl<C: ser::SerializeMap> ser::SerializeMap for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_key<T: Serialize + ?Sized>( &mut self, key: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_key( &Masked( key ) )
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>( &mut self, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_value( &Masked( value ) )
+    }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+impl<C: ser::SerializeStruct> ser::SerializeStruct for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_field<T: Serialize + ?Sized>( &mut self, key: &'static str, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_field( key, &Masked( value ) )
+    }
+
+    fn skip_field( &mut self, key: &'static str ) -> Result<(), Self::Error> { self.0.skip_field( key ) }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+impl<C: ser::SerializeStructVariant> ser::SerializeStructVariant for MaskCompound<C> {
+    type Error = C::Error;
+    type Ok = C::Ok;
+
+    fn serialize_field<T: Serialize + ?Sized>( &mut self, key: &'static str, value: &T ) -> Result<(), Self::Error> {
+        self.0.serialize_field( key, &Masked( value ) )
+    }
+
+    fn skip_field( &mut self, key: &'static str ) -> Result<(), Self::Error> { self.0.skip_field( key ) }
+
+    fn end( self ) -> Result<Self::Ok, Self::Error> { self.0.end() }
+}
+
+/// Provider of a setting read from a secret file.
+pub struct SecretFile {
+    key:  String,
+    path: PathBuf,
+}
+
+impl SecretFile {
+    /// The secret files set by the `<prefix><KEY>_FILE` environment variables.
+    pub fn from_env( prefix: &str ) -> Vec<Self> {
+        let prefix = prefix.to_ascii_uppercase();
+
+        let mut secret_files: Vec<Self> = env::vars_os()
+            .filter_map( |( name, path )| {
+                let name = name.to_str()?.to_ascii_uppercase();
+                let key = name.strip_prefix( &prefix )?.strip_suffix( FILE_ENV_SUFFIX )?;
+
+                Some( Self {
+                    key:  key.to_ascii_lowercase(),
+                    path: PathBuf::from( path ),
+                } )
+            } )
+            .filter( |secret_file| !secret_file.key.is_empty() )
+            .collect();
+        secret_files.sort_by( |a, b| a.key.cmp( &b.key ) );
+
+        secret_files
+    }
+
+    /// The secret files of the directory named `<prefix><key>` in lowercase. A missing directory has no secrets.
+    pub fn from_dir( dir: &Path, prefix: &str ) -> Vec<Self> {
+        let prefix = prefix.to_ascii_lowercase();
+        let Ok( entries ) = fs::read_dir( dir ) else {
+            return Vec::new();
+        };
+
+        let mut secret_files: Vec<Self> = entries
+            .filter_map( Result::ok )
+            .map( |entry| entry.path() )
+            // Skips the directories, e.g. the hidden `..data` link of the Kubernetes secret volumes.
+            .filter( |path| path.is_file() )
+            .filter_map( |path| {
+                let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+                let key = name.strip_prefix( &prefix )?.to_string();
+
+                ( !key.is_empty() && !key.starts_with( '.' ) ).then_some( Self { key, path } )
+            } )
+            .collect();
+        secret_files.sort_by( |a, b| a.key.cmp( &b.key ) );
+
+        secret_files
+    }
+}
+
+impl Provider for SecretFile {
+    fn metadata( &self ) -> Metadata { Metadata::from( "secret file", Source::File( self.path.clone() ) ) }
+
+    fn data( &self ) -> Result<Map<Profile, Dict>, figment::Error> {
+        let content = fs::read_to_string( &self.path ).map_err( |err| {
+            figment::Error::from( format!( "Failed to read the secret file {}: {err}", self.path.display() ) )
+        } )?;
+        let value = content.trim_end().parse::<Value>().unwrap_or_else( |never| match never {} );
+
+        let dict = Dict::from( [( self.key.clone(), value )] );
+        Ok( Map::from( [( Profile::Default, dict )] ) )
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fmt::{Debug, Display},
+    net::SocketAddr,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::ListenAddr;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The list has no address.
+    #[error( "The list of addresses is empty." )]
+    Empty,
+    /// An address is not valid.
+    #[error( "The address is not valid: {0}: {1}" )]
+    Invalid( String, String ),
+    /// An address is in the list more than once.
+    #[error( "The address is duplicated: {0}" )]
+    Duplicated( String ),
+}
+
+/// A non-empty list of distinct addresses, written as a list or as a text separated by commas, e.g.
+/// `"127.0.0.1:8080, [::1]:8080"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Addrs<A>( Vec<A> );
+
+/// A list of TCP socket addresses, IPv4 or IPv6.
+pub type SocketAddrs = Addrs<SocketAddr>;
+
+impl<A> Addrs<A> {
+    pub fn iter( &self ) -> std::slice::Iter<'_, A> { self.0.iter() }
+}
+
+impl<A: Display> Display for Addrs<A> {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        for ( index, addr ) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!( f, ", " )?;
+            }
+            write!( f, "{addr}" )?;
+        }
+
+        Ok( () )
+    }
+}
+
+impl<A> FromStr for Addrs<A>
+where
+    A: FromStr + PartialEq + Display,
+    A::Err: Display,
+{
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        s.split( ',' )
+            .map( str::trim )
+            .filter( |addr| !addr.is_empty() )
+            .map( |addr| addr.parse().map_err( |err: A::Err| Error::Invalid( addr.to_string(), err.to_string() ) ) )
+            .collect::<Result<Vec<_>, _>>()
+            .and_then( Self::try_from )
+    }
+}
+
+impl From<SocketAddr> for SocketAddrs {
+    fn from( addr: SocketAddr ) -> Self { Self( vec![addr] ) }
+}
+
+impl From<ListenAddr> for Addrs<ListenAddr> {
+    fn from( addr: ListenAddr ) -> Self { Self( vec![addr] ) }
+}
+
+impl<A: PartialEq + Display> TryFrom<Vec<A>> for Addrs<A> {
+    type Error = Error;
+
+    fn try_from( addrs: Vec<A> ) -> Result<Self, Self::Error> {
+        if addrs.is_empty() {
+            return Err( Error::Empty );
+        }
+
+        for ( index, addr ) in addrs.iter().enumerate() {
+            if addrs[..index].contains( addr ) {
+                return Err( Error::Duplicated( addr.to_string() ) );
+            }
+        }
+
+        Ok( Self( addrs ) )
+    }
+}
+
+impl<A> AsRef<[A]> for Addrs<A> {
+    fn as_ref( &self ) -> &[A] { &self.0 }
+}
+
+impl<'a, A> IntoIterator for &'a Addrs<A> {
+    type IntoIter = std::slice::Iter<'a, A>;
+    type Item = &'a A;
+
+    fn into_iter( self ) -> Self::IntoIter { self.iter() }
+}
+
+impl<A: Display> Serialize for Addrs<A> {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq( self.0.iter().map( ToString::to_string ) )
+    }
+}
+
+impl<'de, A> Deserialize<'de> for Addrs<A>
+where
+    A: FromStr + PartialEq + Display,
+    A::Err: Display,
+{
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        /// The addresses are either a list or a text separated by commas, e.g. from an environment variable.
+        #[derive(Deserialize)]
+        #[serde( untagged )]
+        enum RawAddrs {
+            List( Vec<String> ),
+            Text( String ),
+        }
+
+        let text = match RawAddrs::deserialize( deserializer )? {
+            RawAddrs::List( addrs ) => addrs.join( "," ),
+            RawAddrs::Text( text ) => text,
+        };
+        text.parse().map_err( serde::de::Error::custom )
+    }
+}
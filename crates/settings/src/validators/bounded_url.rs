@@ -0,0 +1,106 @@
+use std::{fmt::Debug, marker::PhantomData, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The text is not a URL.
+    #[error( "The URL is not valid: {0}" )]
+    Parse( #[from] url::ParseError ),
+    /// The scheme of the URL is not allowed.
+    #[error( "The URL scheme {scheme} is not one of {allowed:?}: {url}" )]
+    SchemeNotAllowed {
+        url:     Url,
+        scheme:  String,
+        allowed: &'static [&'static str],
+    },
+    /// The URL has no host.
+    #[error( "The URL has no host: {0}" )]
+    MissingHost( Url ),
+}
+
+/// The schemes allowed in a [`BoundedUrl`].
+pub trait UrlSchemes: Clone + Debug {
+    const SCHEMES: &'static [&'static str];
+}
+
+/// The `http` and `https` schemes.
+#[derive(Debug, Clone, Copy)]
+pub struct Http;
+
+impl UrlSchemes for Http {
+    const SCHEMES: &'static [&'static str] = &["http", "https"];
+}
+
+/// Only the `https` scheme.
+#[derive(Debug, Clone, Copy)]
+pub struct Https;
+
+impl UrlSchemes for Https {
+    const SCHEMES: &'static [&'static str] = &["https"];
+}
+
+/// A URL with a host and one of the [`UrlSchemes`].
+#[derive(Debug, Clone)]
+pub struct BoundedUrl<S: UrlSchemes = Http> {
+    url:     Url,
+    schemes: PhantomData<S>,
+}
+
+impl<S: UrlSchemes> std::fmt::Display for BoundedUrl<S> {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result { std::fmt::Display::fmt( &self.url, f ) }
+}
+
+impl<S: UrlSchemes> FromStr for BoundedUrl<S> {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> { Self::try_from( Url::parse( s )? ) }
+}
+
+impl<S: UrlSchemes> TryFrom<Url> for BoundedUrl<S> {
+    type Error = Error;
+
+    fn try_from( url: Url ) -> Result<Self, Self::Error> {
+        if !S::SCHEMES.contains( &url.scheme() ) {
+            return Err( Error::SchemeNotAllowed {
+                scheme: url.scheme().to_string(),
+                allowed: S::SCHEMES,
+                url,
+            } );
+        }
+
+        if !url.has_host() {
+            return Err( Error::MissingHost( url ) );
+        }
+
+        Ok( Self {
+            url,
+            schemes: PhantomData,
+        } )
+    }
+}
+
+impl<S: UrlSchemes> AsRef<Url> for BoundedUrl<S> {
+    fn as_ref( &self ) -> &Url { &self.url }
+}
+
+impl<S: UrlSchemes> Serialize for BoundedUrl<S> {
+    fn serialize<Ser>( &self, serializer: Ser ) -> Result<Ser::Ok, Ser::Error>
+    where
+        Ser: serde::Serializer,
+    {
+        serializer.serialize_str( self.url.as_str() )
+    }
+}
+
+impl<'de, S: UrlSchemes> Deserialize<'de> for BoundedUrl<S> {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let text = String::deserialize( deserializer )?;
+        text.parse().map_err( serde::de::Error::custom )
+    }
+}
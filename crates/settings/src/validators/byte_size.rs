@@ -0,0 +1,142 @@
+use std::{fmt::Debug, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The units of a size and their number of bytes, from the largest to the smallest.
+const UNITS: [( &str, u64 ); 9] = [
+    ( "TiB", 1 << 40 ),
+    ( "TB", 1_000_000_000_000 ),
+    ( "GiB", 1 << 30 ),
+    ( "GB", 1_000_000_000 ),
+    ( "MiB", 1 << 20 ),
+    ( "MB", 1_000_000 ),
+    ( "KiB", 1 << 10 ),
+    ( "KB", 1_000 ),
+    ( "B", 1 ),
+];
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The size does not start with a number.
+    #[error( "The size has an invalid number: {0}" )]
+    InvalidNumber( String ),
+    /// The unit is not one of `B`, `KB`, `KiB`, `MB`, `MiB`, `GB`, `GiB`, `TB` or `TiB`.
+    #[error( "The size has an unknown unit: {0}" )]
+    UnknownUnit( String ),
+    /// The size does not fit in 64 bits.
+    #[error( "The size is too large: {0}" )]
+    TooLarge( String ),
+}
+
+/// A size in bytes, written as a number of bytes or with a unit, e.g. `20MiB` or `1.5 GB`. The units are case
+/// insensitive, `KB` is 1000 bytes and `KiB` is 1024 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize( u64 );
+
+impl ByteSize {
+    #[must_use]
+    pub const fn new( bytes: u64 ) -> Self { Self( bytes ) }
+
+    #[must_use]
+    pub const fn as_u64( self ) -> u64 { self.0 }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        if self.0 == 0 {
+            return write!( f, "0B" );
+        }
+
+        // The largest unit that divides the size exactly, so the size is written back as it was parsed.
+        let ( unit, multiplier ) = UNITS
+            .iter()
+            .find( |( _, multiplier )| self.0.is_multiple_of( *multiplier ) )
+            .unwrap_or( &( "B", 1 ) );
+        write!( f, "{}{unit}", self.0 / multiplier )
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let ( number, unit ) = s.split_at( s.find( |c: char| !c.is_ascii_digit() && c != '.' ).unwrap_or( s.len() ) );
+
+        let unit = unit.trim();
+        let multiplier = if unit.is_empty() {
+            1
+        } else {
+            UNITS
+                .iter()
+                .find( |( name, _ )| name.eq_ignore_ascii_case( unit ) )
+                .map( |( _, multiplier )| *multiplier )
+                .ok_or_else( || Error::UnknownUnit( s.to_string() ) )?
+        };
+
+        // Parsed without floats, so an integer number of bytes is exact.
+        let ( integer, fraction ) = number.split_once( '.' ).unwrap_or( ( number, "" ) );
+        if integer.is_empty() && fraction.is_empty() {
+            return Err( Error::InvalidNumber( s.to_string() ) );
+        }
+
+        let parse = |digits: &str| -> Result<u128, Error> {
+            if digits.is_empty() {
+                return Ok( 0 );
+            }
+            digits.parse().map_err( |_| Error::InvalidNumber( s.to_string() ) )
+        };
+        let scale = u32::try_from( fraction.len() )
+            .ok()
+            .and_then( |digits| 10_u128.checked_pow( digits ) )
+            .ok_or_else( || Error::InvalidNumber( s.to_string() ) )?;
+
+        let ( integer, fraction, multiplier ) = ( parse( integer )?, parse( fraction )?, u128::from( multiplier ) );
+        let bytes = integer
+            .checked_mul( multiplier )
+            .and_then( |bytes| bytes.checked_add( fraction.checked_mul( multiplier )? / scale ) )
+            .ok_or_else( || Error::TooLarge( s.to_string() ) )?;
+
+        u64::try_from( bytes ).map( Self ).map_err( |_| Error::TooLarge( s.to_string() ) )
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from( bytes: u64 ) -> Self { Self( bytes ) }
+}
+
+impl TryFrom<String> for ByteSize {
+    type Error = Error;
+
+    fn try_from( value: String ) -> Result<Self, Self::Error> { value.parse() }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str( self )
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        /// A size is either a number of bytes or a text with a unit.
+        #[derive(Deserialize)]
+        #[serde( untagged )]
+        enum RawByteSize {
+            Bytes( u64 ),
+            Text( String ),
+        }
+
+        match RawByteSize::deserialize( deserializer )? {
+            RawByteSize::Bytes( bytes ) => Ok( Self( bytes ) ),
+            RawByteSize::Text( text ) => Self::try_from( text ).map_err( serde::de::Error::custom ),
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use std::{
     fmt::Debug,
+    fs,
+    io::{self, IsTerminal},
+    marker::PhantomData,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -16,70 +19,140 @@ pub enum Error {
     /// The path is not a directory.
     #[error( "The path is not a directory: {0}" )]
     NotADirectory( PathBuf ),
+    /// The directory is missing and could not be created.
+    #[error( "Failed to create the directory {0}: {1}" )]
+    CreationFailed( PathBuf, #[source] io::Error ),
+    /// The directory can't be read.
+    #[error( "The directory is not readable: {0}: {1}" )]
+    NotReadable( PathBuf, #[source] io::Error ),
+    /// The directory can't be written to.
+    #[error( "The directory is not writable: {0}: {1}" )]
+    NotWritable( PathBuf, #[source] io::Error ),
+    /// The user can't be prompted for a path.
+    #[error( "Can't prompt for a directory path, the standard input is not a terminal." )]
+    NoTerminal,
+    /// The user did not insert a valid path.
+    #[error( "Failed to get the directory path from the user: {0}" )]
+    PromptFailed( String ),
 }
 
-/// A path that exists and is a directory.
+/// How a [`DirectoryPath`] is validated.
+pub trait DirectoryAccess: Clone + Debug {
+    /// Whether the directory is created when it is missing.
+    const IS_CREATED: bool;
+    /// Whether the directory must be writable. Otherwise it must be readable.
+    const IS_WRITABLE: bool;
+}
+
+/// A directory that must exist and be readable, e.g. the static files.
+#[derive(Debug, Clone, Copy)]
+pub struct Readable;
+
+impl DirectoryAccess for Readable {
+    const IS_CREATED: bool = false;
+    const IS_WRITABLE: bool = false;
+}
+
+/// A directory that is created if it is missing and must be writable, e.g. the logs.
+#[derive(Debug, Clone, Copy)]
+pub struct Writable;
+
+impl DirectoryAccess for Writable {
+    const IS_CREATED: bool = true;
+    const IS_WRITABLE: bool = true;
+}
+
+/// A path that is a directory, validated according to the [`DirectoryAccess`].
+///
+/// Deserializing it does not access the file system, it is validated by [`DirectoryPath::ensure`] once the settings are
+/// loaded, e.g. at startup.
 #[derive(Debug, Clone)]
-pub struct DirectoryPath {
-    path: PathBuf,
+pub struct DirectoryPath<A: DirectoryAccess = Readable> {
+    path:   PathBuf,
+    access: PhantomData<A>,
 }
 
-impl DirectoryPath {
-    /// # Panics
+impl<A: DirectoryAccess> DirectoryPath<A> {
+    /// Creates the path without validating it, e.g. for the default settings, which are validated by
+    /// [`DirectoryPath::ensure`].
+    pub fn new_unchecked( path: impl Into<PathBuf> ) -> Self {
+        Self {
+            path:   path.into(),
+            access: PhantomData,
+        }
+    }
+
+    /// Validates the directory, creating it if it is missing and the [`DirectoryAccess`] allows it. When
+    /// `is_interactive`, an invalid path is replaced by one asked to the user instead of failing.
+    ///
+    /// # Errors
     ///
-    /// Panics if the user does not insert a valid directory path.
-    #[must_use]
-    pub fn prompt() -> Self {
-        CustomType::<Self>::new( "Please insert a new directory path:" )
+    /// If the directory is invalid, and it can't be asked to the user or the user cancels the prompt.
+    pub fn ensure( &mut self, is_interactive: bool ) -> Result<(), Error> {
+        match Self::try_from( self.path.clone() ) {
+            Ok( _ ) => Ok( () ),
+            Err( err ) if is_interactive => {
+                *self = Self::prompt( &err )?;
+                Ok( () )
+            }
+            Err( err ) => Err( err ),
+        }
+    }
+
+    /// Asks the user for a valid directory path, telling why the current one is not.
+    fn prompt( err: &Error ) -> Result<Self, Error> {
+        if !io::stdin().is_terminal() {
+            return Err( Error::NoTerminal );
+        }
+
+        CustomType::<Self>::new( &format!( "{err}. Please insert a new directory path:" ) )
             .with_error_message( "The inserted directory path is not valid." )
             .prompt()
-            .unwrap_or_else( |err| panic!( "Failed to get the directory path from the user: {err}" ) )
+            .map_err( |err| Error::PromptFailed( err.to_string() ) )
     }
 }
 
-impl std::fmt::Display for DirectoryPath {
+impl<A: DirectoryAccess> std::fmt::Display for DirectoryPath<A> {
     fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result { self.path.fmt( f ) }
 }
 
-impl FromStr for DirectoryPath {
+impl<A: DirectoryAccess> FromStr for DirectoryPath<A> {
     type Err = Error;
 
-    fn from_str( s: &str ) -> Result<Self, Self::Err> {
-        let path = PathBuf::from( s );
-
-        if !path.exists() {
-            return Err( Error::DoesNotExist( path ) );
-        }
-
-        if !path.is_dir() {
-            return Err( Error::NotADirectory( path ) );
-        }
-
-        Ok( Self { path } )
-    }
+    fn from_str( s: &str ) -> Result<Self, Self::Err> { Self::try_from( PathBuf::from( s ) ) }
 }
 
-impl TryFrom<PathBuf> for DirectoryPath {
+impl<A: DirectoryAccess> TryFrom<PathBuf> for DirectoryPath<A> {
     type Error = Error;
 
     fn try_from( path: PathBuf ) -> Result<Self, Self::Error> {
         if !path.exists() {
-            return Err( Error::DoesNotExist( path ) );
+            if !A::IS_CREATED {
+                return Err( Error::DoesNotExist( path ) );
+            }
+
+            fs::create_dir_all( &path ).map_err( |err| Error::CreationFailed( path.clone(), err ) )?;
         }
 
         if !path.is_dir() {
             return Err( Error::NotADirectory( path ) );
         }
 
-        Ok( Self { path } )
+        if A::IS_WRITABLE {
+            check_writable( &path ).map_err( |err| Error::NotWritable( path.clone(), err ) )?;
+        } else {
+            fs::read_dir( &path ).map_err( |err| Error::NotReadable( path.clone(), err ) )?;
+        }
+
+        Ok( Self::new_unchecked( path ) )
     }
 }
 
-impl AsRef<Path> for DirectoryPath {
+impl<A: DirectoryAccess> AsRef<Path> for DirectoryPath<A> {
     fn as_ref( &self ) -> &Path { &self.path }
 }
 
-impl Serialize for DirectoryPath {
+impl<A: DirectoryAccess> Serialize for DirectoryPath<A> {
     fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -88,12 +161,18 @@ impl Serialize for DirectoryPath {
     }
 }
 
-impl<'de> Deserialize<'de> for DirectoryPath {
+impl<'de, A: DirectoryAccess> Deserialize<'de> for DirectoryPath<A> {
     fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let path = PathBuf::deserialize( deserializer )?;
-        Self::try_from( path ).map_err( serde::de::Error::custom )
+        PathBuf::deserialize( deserializer ).map( Self::new_unchecked )
     }
 }
+
+/// Creates and removes a file in the directory.
+fn check_writable( path: &Path ) -> io::Result<()> {
+    let check_file = path.join( format!( ".write-check-{}", std::process::id() ) );
+    fs::OpenOptions::new().write( true ).create_new( true ).open( &check_file )?;
+    fs::remove_file( check_file )
+}
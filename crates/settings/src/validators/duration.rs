@@ -0,0 +1,132 @@
+use std::{fmt::Debug, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The units of a duration and their number of milliseconds, from the largest to the smallest.
+const UNITS: [( &str, u128 ); 5] = [( "d", 86_400_000 ), ( "h", 3_600_000 ), ( "m", 60_000 ), ( "s", 1_000 ), ( "ms", 1 )];
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The duration is empty.
+    #[error( "The duration is empty." )]
+    Empty,
+    /// A part of the duration does not start with a number.
+    #[error( "The duration has an invalid number: {0}" )]
+    InvalidNumber( String ),
+    /// A number of the duration has no unit.
+    #[error( "The duration has a number without unit: {0}" )]
+    MissingUnit( String ),
+    /// The unit is not one of `d`, `h`, `m`, `s` or `ms`.
+    #[error( "The duration has an unknown unit: {0}" )]
+    UnknownUnit( String ),
+    /// The duration does not fit in 64 bits of milliseconds.
+    #[error( "The duration is too long: {0}" )]
+    TooLong( String ),
+}
+
+/// A duration written as numbers with units, e.g. `30m`, `1h 30m` or `500ms`. The units are `d`, `h`, `m`, `s` and
+/// `ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration( std::time::Duration );
+
+impl Duration {
+    #[must_use]
+    pub const fn from_secs( secs: u64 ) -> Self { Self( std::time::Duration::from_secs( secs ) ) }
+
+    #[must_use]
+    pub const fn from_millis( millis: u64 ) -> Self { Self( std::time::Duration::from_millis( millis ) ) }
+
+    #[must_use]
+    pub const fn as_std( self ) -> std::time::Duration { self.0 }
+}
+
+impl std::fmt::Display for Duration {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        // The precision below a millisecond is not written.
+        let mut millis = self.0.as_millis();
+        if millis == 0 {
+            return write!( f, "0s" );
+        }
+
+        for ( unit, unit_millis ) in UNITS {
+            if millis >= unit_millis {
+                write!( f, "{}{unit}", millis / unit_millis )?;
+                millis %= unit_millis;
+            }
+        }
+
+        Ok( () )
+    }
+}
+
+impl FromStr for Duration {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        let mut rest = s.trim();
+        if rest.is_empty() {
+            return Err( Error::Empty );
+        }
+
+        let mut millis: u128 = 0;
+        while !rest.is_empty() {
+            let ( number, tail ) = rest.split_at( rest.find( |c: char| !c.is_ascii_digit() ).unwrap_or( rest.len() ) );
+            let number: u128 = number.parse().map_err( |_| Error::InvalidNumber( s.to_string() ) )?;
+
+            let tail = tail.trim_start();
+            let ( unit, tail ) = tail.split_at( tail.find( |c: char| !c.is_ascii_alphabetic() ).unwrap_or( tail.len() ) );
+            if unit.is_empty() {
+                return Err( Error::MissingUnit( s.to_string() ) );
+            }
+
+            let unit_millis = UNITS
+                .iter()
+                .find( |( name, _ )| *name == unit )
+                .map( |( _, unit_millis )| *unit_millis )
+                .ok_or_else( || Error::UnknownUnit( s.to_string() ) )?;
+            millis = number
+                .checked_mul( unit_millis )
+                .and_then( |part| millis.checked_add( part ) )
+                .ok_or_else( || Error::TooLong( s.to_string() ) )?;
+
+            rest = tail.trim_start();
+        }
+
+        let millis = u64::try_from( millis ).map_err( |_| Error::TooLong( s.to_string() ) )?;
+        Ok( Self( std::time::Duration::from_millis( millis ) ) )
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    fn from( duration: std::time::Duration ) -> Self { Self( duration ) }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from( duration: Duration ) -> Self { duration.0 }
+}
+
+impl TryFrom<String> for Duration {
+    type Error = Error;
+
+    fn try_from( value: String ) -> Result<Self, Self::Error> { value.parse() }
+}
+
+impl Serialize for Duration {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str( self )
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let text = String::deserialize( deserializer )?;
+        Self::try_from( text ).map_err( serde::de::Error::custom )
+    }
+}
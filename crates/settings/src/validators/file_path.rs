@@ -0,0 +1,85 @@
+use std::{
+    fmt::Debug,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The path does not exist.
+    #[error( "The path does not exist: {0}" )]
+    DoesNotExist( PathBuf ),
+    /// The path is not a file.
+    #[error( "The path is not a file: {0}" )]
+    NotAFile( PathBuf ),
+    /// The file can't be read.
+    #[error( "The file is not readable: {0}: {1}" )]
+    NotReadable( PathBuf, #[source] io::Error ),
+}
+
+/// A path to an existing and readable file, e.g. a TLS certificate.
+#[derive(Debug, Clone)]
+pub struct FilePath {
+    path: PathBuf,
+}
+
+impl FilePath {
+    /// Creates the path without validating it, e.g. for the default settings, which are validated once the settings
+    /// are extracted.
+    pub fn new_unchecked( path: impl Into<PathBuf> ) -> Self { Self { path: path.into() } }
+}
+
+impl std::fmt::Display for FilePath {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result { self.path.fmt( f ) }
+}
+
+impl FromStr for FilePath {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> { Self::try_from( PathBuf::from( s ) ) }
+}
+
+impl TryFrom<PathBuf> for FilePath {
+    type Error = Error;
+
+    fn try_from( path: PathBuf ) -> Result<Self, Self::Error> {
+        if !path.exists() {
+            return Err( Error::DoesNotExist( path ) );
+        }
+
+        if !path.is_file() {
+            return Err( Error::NotAFile( path ) );
+        }
+
+        fs::File::open( &path ).map_err( |err| Error::NotReadable( path.clone(), err ) )?;
+
+        Ok( Self { path } )
+    }
+}
+
+impl AsRef<Path> for FilePath {
+    fn as_ref( &self ) -> &Path { &self.path }
+}
+
+impl Serialize for FilePath {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.path.serialize( serializer )
+    }
+}
+
+impl<'de> Deserialize<'de> for FilePath {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let path = PathBuf::deserialize( deserializer )?;
+        Self::try_from( path ).map_err( serde::de::Error::custom )
+    }
+}
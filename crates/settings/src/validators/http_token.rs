@@ -0,0 +1,64 @@
+use std::{fmt::Debug, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The characters of a token besides the ASCII letters and digits ( RFC 9110 section 5.6.2 ).
+const TOKEN_SYMBOLS: &str = "!#$%&'*+-.^_`|~";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The text is not a token.
+    #[error( "The HTTP token is not valid: {0:?}. It must only have letters, digits and !#$%&'*+-.^_`|~" )]
+    Invalid( String ),
+}
+
+/// A token of the HTTP grammar, such as a method or a header name, e.g. `GET` or `content-type`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpToken( String );
+
+impl HttpToken {
+    #[must_use]
+    pub fn as_str( &self ) -> &str { &self.0 }
+}
+
+impl std::fmt::Display for HttpToken {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result { write!( f, "{}", self.0 ) }
+}
+
+impl FromStr for HttpToken {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        let is_token =
+            !s.is_empty() && s.chars().all( |c| c.is_ascii_alphanumeric() || TOKEN_SYMBOLS.contains( c ) );
+        if !is_token {
+            return Err( Error::Invalid( s.to_string() ) );
+        }
+
+        Ok( Self( s.to_string() ) )
+    }
+}
+
+impl AsRef<str> for HttpToken {
+    fn as_ref( &self ) -> &str { &self.0 }
+}
+
+impl Serialize for HttpToken {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str( &self.0 )
+    }
+}
+
+impl<'de> Deserialize<'de> for HttpToken {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let token = String::deserialize( deserializer )?;
+        token.parse().map_err( serde::de::Error::custom )
+    }
+}
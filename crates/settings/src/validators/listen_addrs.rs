@@ -0,0 +1,81 @@
+use std::{fmt::Debug, net::SocketAddr, path::PathBuf, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::Addrs;
+
+/// Prefix of the Unix domain socket addresses, e.g. `unix:/run/backend.sock`.
+const UNIX_PREFIX: &str = "unix:";
+
+/// The listen address is not a socket address nor a Unix domain socket.
+#[derive(Error, Debug)]
+#[error( "Expected e.g. 127.0.0.1:8080, [::]:8080 or unix:/run/app.sock" )]
+pub struct Error;
+
+/// A list of listen addresses.
+pub type ListenAddrs = Addrs<ListenAddr>;
+
+/// An address to listen on, either a TCP socket address, IPv4 or IPv6, or the path of a Unix domain socket prefixed
+/// with `unix:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    Tcp( SocketAddr ),
+    Unix( PathBuf ),
+}
+
+impl ListenAddr {
+    /// The TCP socket address, if it is not a Unix domain socket.
+    #[must_use]
+    pub const fn as_socket_addr( &self ) -> Option<SocketAddr> {
+        match self {
+            Self::Tcp( addr ) => Some( *addr ),
+            Self::Unix( _ ) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ListenAddr {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        match self {
+            Self::Tcp( addr ) => write!( f, "{addr}" ),
+            Self::Unix( path ) => write!( f, "{UNIX_PREFIX}{}", path.display() ),
+        }
+    }
+}
+
+impl FromStr for ListenAddr {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_prefix( UNIX_PREFIX ) {
+            Some( path ) if !path.is_empty() => Ok( Self::Unix( PathBuf::from( path ) ) ),
+            Some( _ ) => Err( Error ),
+            None => s.parse().map( Self::Tcp ).map_err( |_| Error ),
+        }
+    }
+}
+
+impl From<SocketAddr> for ListenAddr {
+    fn from( addr: SocketAddr ) -> Self { Self::Tcp( addr ) }
+}
+
+impl Serialize for ListenAddr {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str( self )
+    }
+}
+
+impl<'de> Deserialize<'de> for ListenAddr {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let addr = String::deserialize( deserializer )?;
+        addr.parse().map_err( serde::de::Error::custom )
+    }
+}
@@ -1,2 +1,20 @@
+mod addrs;
+mod bounded_url;
+mod byte_size;
 mod directory_path;
-pub use directory_path::DirectoryPath;
+mod duration;
+mod file_path;
+mod http_token;
+mod listen_addrs;
+mod origin;
+mod ratio;
+pub use addrs::{Addrs, Error as AddrsError, SocketAddrs};
+pub use bounded_url::{BoundedUrl, Error as BoundedUrlError, Http, Https, UrlSchemes};
+pub use byte_size::{ByteSize, Error as ByteSizeError};
+pub use directory_path::{DirectoryAccess, DirectoryPath, Error as DirectoryPathError, Readable, Writable};
+pub use duration::{Duration, Error as DurationError};
+pub use file_path::{Error as FilePathError, FilePath};
+pub use http_token::{Error as HttpTokenError, HttpToken};
+pub use listen_addrs::{Error as ListenAddrError, ListenAddr, ListenAddrs};
+pub use origin::{Error as OriginError, Origin};
+pub use ratio::{Error as RatioError, Ratio};
@@ -0,0 +1,133 @@
+use std::{fmt::Debug, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+/// The origin allowing any other one.
+const ANY_ORIGIN: &str = "*";
+
+/// Prefix of the host of the origins allowing the subdomains of a domain.
+const SUBDOMAINS_PREFIX: &str = "*.";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The origin is not valid.
+    #[error(
+        "The origin is not valid: {0}. Expected *, an origin such as https://example.com, or https://*.example.com for \
+         its subdomains."
+    )]
+    Invalid( String ),
+}
+
+/// An origin allowed to make cross-origin requests.
+///
+/// It is either any origin with `*`, an exact origin such as `https://example.com:8080`, or the subdomains of a domain
+/// such as `https://*.example.com`. Only the `http` and `https` schemes are supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Any,
+    Exact( String ),
+    Subdomains {
+        scheme: String,
+        domain: String,
+        port:   Option<u16>,
+    },
+}
+
+impl Origin {
+    /// Whether the `origin` of a request, e.g. the value of its `Origin` header, is allowed.
+    #[must_use]
+    pub fn matches( &self, origin: &str ) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact( exact ) => exact == origin,
+            Self::Subdomains { scheme, domain, port } => {
+                let Ok( url ) = Url::parse( origin ) else {
+                    return false;
+                };
+
+                url.scheme() == scheme
+                    && url.port() == *port
+                    && url
+                        .host_str()
+                        .and_then( |host| host.strip_suffix( domain.as_str() ) )
+                        .and_then( |subdomain| subdomain.strip_suffix( '.' ) )
+                        .is_some_and( |subdomain| !subdomain.is_empty() )
+            }
+        }
+    }
+
+    /// Whether any origin is allowed.
+    #[must_use]
+    pub const fn is_any( &self ) -> bool { matches!( self, Self::Any ) }
+}
+
+impl std::fmt::Display for Origin {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result {
+        match self {
+            Self::Any => write!( f, "{ANY_ORIGIN}" ),
+            Self::Exact( origin ) => write!( f, "{origin}" ),
+            Self::Subdomains { scheme, domain, port } => {
+                write!( f, "{scheme}://{SUBDOMAINS_PREFIX}{domain}" )?;
+                port.map_or( Ok( () ), |port| write!( f, ":{port}" ) )
+            }
+        }
+    }
+}
+
+impl FromStr for Origin {
+    type Err = Error;
+
+    fn from_str( s: &str ) -> Result<Self, Self::Err> {
+        if s == ANY_ORIGIN {
+            return Ok( Self::Any );
+        }
+
+        // The wildcard is not a valid host, the domain is validated without it.
+        let ( scheme, rest ) = s.split_once( "://" ).ok_or_else( || Error::Invalid( s.to_string() ) )?;
+        let ( is_subdomains, rest ) =
+            rest.strip_prefix( SUBDOMAINS_PREFIX ).map_or( ( false, rest ), |rest| ( true, rest ) );
+
+        let url = Url::parse( &format!( "{scheme}://{rest}" ) ).map_err( |_| Error::Invalid( s.to_string() ) )?;
+        let is_origin = matches!( url.scheme(), "http" | "https" )
+            && url.username().is_empty()
+            && url.password().is_none()
+            && url.path() == "/"
+            && url.query().is_none()
+            && url.fragment().is_none()
+            && !rest.ends_with( '/' );
+        let Some( host ) = url.host_str().filter( |_| is_origin ) else {
+            return Err( Error::Invalid( s.to_string() ) );
+        };
+
+        if !is_subdomains {
+            return Ok( Self::Exact( url.origin().ascii_serialization() ) );
+        }
+
+        Ok( Self::Subdomains {
+            scheme: url.scheme().to_string(),
+            domain: host.to_string(),
+            port:   url.port(),
+        } )
+    }
+}
+
+impl Serialize for Origin {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str( self )
+    }
+}
+
+impl<'de> Deserialize<'de> for Origin {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let origin = String::deserialize( deserializer )?;
+        origin.parse().map_err( serde::de::Error::custom )
+    }
+}
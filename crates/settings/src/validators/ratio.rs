@@ -0,0 +1,63 @@
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The ratio is not between 0 and 1, or is not a number.
+    #[error( "The ratio must be between 0 and 1: {0}" )]
+    OutOfRange( f64 ),
+}
+
+/// A ratio between 0 and 1 included, e.g. `0.25` for a quarter.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Ratio( f64 );
+
+impl Ratio {
+    /// The ratio of all of them.
+    pub const ALL: Self = Self( 1.0 );
+
+    #[must_use]
+    pub const fn as_f64( self ) -> f64 { self.0 }
+}
+
+impl std::fmt::Display for Ratio {
+    fn fmt( &self, f: &mut std::fmt::Formatter<'_> ) -> std::fmt::Result { write!( f, "{}", self.0 ) }
+}
+
+impl TryFrom<f64> for Ratio {
+    type Error = Error;
+
+    fn try_from( value: f64 ) -> Result<Self, Self::Error> {
+        // A NaN is not in the range either.
+        if ( 0.0..=1.0 ).contains( &value ) {
+            Ok( Self( value ) )
+        } else {
+            Err( Error::OutOfRange( value ) )
+        }
+    }
+}
+
+impl From<Ratio> for f64 {
+    fn from( ratio: Ratio ) -> Self { ratio.0 }
+}
+
+impl Serialize for Ratio {
+    fn serialize<S>( &self, serializer: S ) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_f64( self.0 )
+    }
+}
+
+impl<'de> Deserialize<'de> for Ratio {
+    fn deserialize<D>( deserializer: D ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = f64::deserialize( deserializer )?;
+        Self::try_from( value ).map_err( serde::de::Error::custom )
+    }
+}
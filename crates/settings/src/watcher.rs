@@ -0,0 +1,265 @@
+//! Hot reloading of the settings.
+//!
+//! The [`ConfigWatcher`] watches the configuration files of a directory, such as the one returned by
+//! [`get_configs_dir_path`](crate::get_configs_dir_path). On change, it loads the settings again, usually by running
+//! the same [`FigmentExtractor`](crate::FigmentExtractor) pipeline as on startup, and publishes them through a
+//! [`watch`] channel. Invalid settings are logged and not published.
+//!
+//! Only the reloadable keys are applied. The other ones, such as the listen address, are only read on startup: their
+//! changes are logged as warnings and the published settings keep the values in use.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use std::path::Path;
+//! # #[derive(Default, serde::Serialize, serde::Deserialize)]
+//! # struct Configs { log_level: String }
+//! # fn load() -> Result<Configs, String> { Ok( Configs::default() ) }
+//! use settings::ConfigWatcher;
+//!
+//! let configs_dir = Path::new( "./configs/backend/" );
+//! let watcher = ConfigWatcher::spawn( configs_dir, load().unwrap(), &["log_level"], load ).unwrap();
+//! let configs = watcher.subscribe();
+//! println!( "{}", configs.borrow().log_level );
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
+
+use crate::report;
+
+/// Time without new file events before reloading, as editors and renewals write the files in several steps.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis( 250 );
+
+/// Extension of the configuration files.
+const CONFIG_FILE_EXTENSION: &str = "toml";
+
+/// Error type for the function [`ConfigWatcher::spawn`].
+#[derive(Error, Debug)]
+#[error( "Failed to watch the configs directory {0}: {1}" )]
+pub struct WatchError( PathBuf, #[source] notify::Error );
+
+/// Error type of the merge of the reloaded settings with the ones in use.
+#[derive(Error, Debug)]
+enum MergeError {
+    #[error( "Failed to serialize the settings: {0}" )]
+    Serialize( #[from] toml::ser::Error ),
+    #[error( "Failed to deserialize the merged settings: {0}" )]
+    Deserialize( #[from] toml::de::Error ),
+}
+
+/// Watcher of the configuration files, publishing the reloaded settings. The files stop being watched once it is
+/// dropped.
+pub struct ConfigWatcher<T> {
+    receiver: watch::Receiver<Arc<T>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl<T> ConfigWatcher<T>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    /// Starts watching the `configs_dir`.
+    ///
+    /// # Arguments
+    ///
+    /// * `configs_dir` - The directory of the configuration files.
+    /// * `initial` - The settings in use.
+    /// * `reloadable_keys` - The dotted keys, e.g. `logger.log_level`, that can change at runtime. A key also covers
+    ///   the keys nested in it.
+    /// * `load` - Loads and validates the settings.
+    ///
+    /// # Errors
+    ///
+    /// If the directory can't be watched.
+    pub fn spawn<E, F>(
+        configs_dir: &Path,
+        initial: T,
+        reloadable_keys: &'static [&'static str],
+        load: F,
+    ) -> Result<Self, WatchError>
+    where
+        E: Debug,
+        F: Fn() -> Result<T, E> + Send + 'static,
+    {
+        let ( event_sender, event_receiver ) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher( event_sender )
+            .map_err( |err| WatchError( configs_dir.to_path_buf(), err ) )?;
+        watcher
+            .watch( configs_dir, RecursiveMode::NonRecursive )
+            .map_err( |err| WatchError( configs_dir.to_path_buf(), err ) )?;
+
+        let ( sender, receiver ) = watch::channel( Arc::new( initial ) );
+        let configs_dir = configs_dir.to_path_buf();
+
+        // The loop ends when the watcher is dropped, as it holds the events sender.
+        thread::spawn( move || {
+            while wait_for_change( &event_receiver, is_config_change ) {
+                let reloaded = match load() {
+                    Ok( reloaded ) => reloaded,
+                    Err( err ) => {
+                        tracing::error!(
+                            "Invalid settings in {}, keeping the ones in use: {err:?}",
+                            configs_dir.display()
+                        );
+                        continue;
+                    }
+                };
+
+                let current = Arc::clone( &sender.borrow() );
+                match merge_reloadable( current.as_ref(), &reloaded, reloadable_keys ) {
+                    Ok( Some( merged ) ) => {
+                        sender.send_replace( Arc::new( merged ) );
+                        tracing::info!( "Reloaded the settings from {}.", configs_dir.display() );
+                    }
+                    Ok( None ) => tracing::debug!( "No reloadable settings changed in {}.", configs_dir.display() ),
+                    Err( err ) => {
+                        tracing::error!( "Failed to reload the settings from {}: {err}", configs_dir.display() );
+                    }
+                }
+            }
+        } );
+
+        Ok( Self {
+            receiver,
+            _watcher: watcher,
+        } )
+    }
+
+    /// A receiver of the settings, updated on every reload.
+    #[must_use]
+    pub fn subscribe( &self ) -> watch::Receiver<Arc<T>> { self.receiver.clone() }
+}
+
+/// Blocks until a change is received and debounced.
+///
+/// The change is an event for which `is_change` is true, followed by no other event within the debounce delay, as a
+/// file is often written in several steps. Returns `false` once the watcher sending the `events` is dropped.
+pub fn wait_for_change(
+    events: &mpsc::Receiver<notify::Result<Event>>,
+    is_change: impl Fn( &Event ) -> bool,
+) -> bool {
+    loop {
+        match events.recv() {
+            Ok( Ok( event ) ) if is_change( &event ) => break,
+            Ok( Ok( _ ) ) => {}
+            Ok( Err( err ) ) => tracing::warn!( "Failed to watch the files: {err}" ),
+            Err( _ ) => return false,
+        }
+    }
+
+    loop {
+        match events.recv_timeout( DEBOUNCE_DELAY ) {
+            Ok( _ ) => {}
+            Err( mpsc::RecvTimeoutError::Timeout ) => return true,
+            Err( mpsc::RecvTimeoutError::Disconnected ) => return false,
+        }
+    }
+}
+
+/// Whether the event creates, modifies or removes a file.
+#[must_use]
+pub const fn is_file_change( event: &Event ) -> bool {
+    matches!( event.kind, EventKind::Create( _ ) | EventKind::Modify( _ ) | EventKind::Remove( _ ) )
+}
+
+fn is_config_change( event: &Event ) -> bool {
+    is_file_change( event )
+        && event
+            .paths
+            .iter()
+            .any( |path| path.extension().is_some_and( |extension| extension == CONFIG_FILE_EXTENSION ) )
+}
+
+/// Applies the changes of the reloadable keys to the settings in use, and warns about the other ones. Returns [`None`]
+/// if no reloadable key changed.
+fn merge_reloadable<T>( current: &T, reloaded: &T, reloadable_keys: &[&str] ) -> Result<Option<T>, MergeError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let current_table = toml::Table::try_from( current )?;
+    let mut merged_table = toml::Table::try_from( reloaded )?;
+
+    let current_values = flatten( &current_table );
+    let reloaded_values = flatten( &merged_table );
+
+    let mut keys: Vec<&String> = current_values.keys().chain( reloaded_values.keys() ).collect();
+    keys.sort();
+    keys.dedup();
+
+    let mut is_changed = false;
+    for key in keys {
+        let current_value = current_values.get( key );
+        if current_value == reloaded_values.get( key ) {
+            continue;
+        }
+
+        if is_reloadable( key, reloadable_keys ) {
+            is_changed = true;
+        } else {
+            tracing::warn!( "The setting {key} can't be reloaded, it will be applied on the next restart." );
+            set_value( &mut merged_table, key, current_value.cloned() );
+        }
+    }
+
+    if !is_changed {
+        return Ok( None );
+    }
+
+    Ok( Some( toml::Value::Table( merged_table ).try_into()? ) )
+}
+
+fn flatten( table: &toml::Table ) -> BTreeMap<String, toml::Value> {
+    let mut values = BTreeMap::new();
+    report::flatten( "", table, &mut |key, value| {
+        values.insert( key, value.clone() );
+    } );
+
+    values
+}
+
+fn is_reloadable( key: &str, reloadable_keys: &[&str] ) -> bool {
+    reloadable_keys.iter().any( |reloadable_key| {
+        key.strip_prefix( reloadable_key )
+            .is_some_and( |rest| rest.is_empty() || rest.starts_with( '.' ) )
+    } )
+}
+
+/// Sets the value of the dotted `key`, or removes it if the value is [`None`].
+fn set_value( table: &mut toml::Table, key: &str, value: Option<toml::Value> ) {
+    let mut segments: Vec<&str> = key.split( '.' ).collect();
+    let Some( leaf ) = segments.pop() else {
+        return;
+    };
+
+    let mut table = table;
+    for parent in segments {
+        let entry = table
+            .entry( parent )
+            .or_insert_with( || toml::Value::Table( toml::Table::new() ) );
+        let Some( nested ) = entry.as_table_mut() else {
+            return;
+        };
+        table = nested;
+    }
+
+    match value {
+        Some( value ) => {
+            table.insert( leaf.to_string(), value );
+        }
+        None => {
+            table.remove( leaf );
+        }
+    }
+}
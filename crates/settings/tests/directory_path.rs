@@ -0,0 +1,75 @@
+use std::{fs, path::PathBuf};
+
+use settings::validators::{DirectoryPath, DirectoryPathError, Readable, Writable};
+
+fn temp_path( name: &str ) -> PathBuf {
+    let path = std::env::temp_dir().join( format!( "settings-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &path );
+    path
+}
+
+#[test]
+fn missing_writable_directory_is_created() {
+    let path = temp_path( "writable" ).join( "logs" );
+
+    let dir = DirectoryPath::<Writable>::try_from( path.clone() ).unwrap();
+
+    assert!( dir.as_ref().is_dir() );
+    assert_eq!( fs::read_dir( &path ).unwrap().count(), 0 );
+    fs::remove_dir_all( path.parent().unwrap() ).unwrap();
+}
+
+#[test]
+fn missing_readable_directory_fails() {
+    let path = temp_path( "readable" );
+
+    let result = DirectoryPath::<Readable>::try_from( path.clone() );
+
+    assert!( matches!( result, Err( DirectoryPathError::DoesNotExist( _ ) ) ) );
+    assert!( !path.exists() );
+}
+
+#[test]
+fn file_is_not_a_directory() {
+    let path = temp_path( "file" );
+    fs::write( &path, "" ).unwrap();
+
+    let result = DirectoryPath::<Writable>::try_from( path.clone() );
+
+    assert!( matches!( result, Err( DirectoryPathError::NotADirectory( _ ) ) ) );
+    fs::remove_file( path ).unwrap();
+}
+
+#[test]
+fn deserialization_does_not_access_the_file_system() {
+    let path = temp_path( "deserialization" );
+    let toml = format!( "readable = {0:?}\nwritable = {0:?}", path.display().to_string() );
+    let table = toml::from_str::<toml::Table>( &toml ).unwrap();
+
+    let readable: DirectoryPath<Readable> = table["readable"].clone().try_into().unwrap();
+    let writable: DirectoryPath<Writable> = table["writable"].clone().try_into().unwrap();
+
+    assert_eq!( readable.as_ref(), path );
+    assert_eq!( writable.as_ref(), path );
+    assert!( !path.exists() );
+}
+
+#[test]
+fn ensure_creates_the_missing_writable_directory() {
+    let path = temp_path( "ensure-writable" );
+    let mut dir = DirectoryPath::<Writable>::new_unchecked( path.clone() );
+
+    dir.ensure( false ).unwrap();
+
+    assert!( path.is_dir() );
+    fs::remove_dir_all( path ).unwrap();
+}
+
+#[test]
+fn ensure_fails_on_the_missing_readable_directory() {
+    let mut dir = DirectoryPath::<Readable>::new_unchecked( temp_path( "ensure-readable" ) );
+
+    let result = dir.ensure( false );
+
+    assert!( matches!( result, Err( DirectoryPathError::DoesNotExist( _ ) ) ) );
+}
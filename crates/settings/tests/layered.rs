@@ -0,0 +1,86 @@
+use std::{fs, path::PathBuf};
+
+use figment::Jail;
+use serde::{Deserialize, Serialize};
+use settings::{LayeredSettings, Layers, Profiles, RuntimeEnvironment, Secret};
+
+#[derive(Serialize, Deserialize, LayeredSettings)]
+pub struct ServerConfigs {
+    /// Set the listen port.
+    #[settings( cli( short = 'p', long = "port" ) )]
+    port:    u16,
+    #[settings( cli( long = "server-token", ty = String ) )]
+    token:   Secret<String>,
+    workers: usize,
+}
+
+impl Default for ServerConfigs {
+    fn default() -> Self {
+        Self {
+            port:    5555,
+            token:   Secret::new( "default".to_string() ),
+            workers: 4,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, LayeredSettings)]
+#[settings( section = "db", file = "database.toml", cli_args = DatabaseArgs )]
+pub struct DatabaseConfigs {
+    #[settings( cli )]
+    pool_size: u32,
+}
+
+fn create_configs_dir( name: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "settings-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+    dir
+}
+
+#[test]
+fn section_names_come_from_the_struct_or_the_attribute() {
+    assert_eq!( ServerConfigs::SECTION, "server" );
+    assert_eq!( ServerConfigs::FILE_NAME, "server.toml" );
+    assert_eq!( DatabaseConfigs::SECTION, "db" );
+    assert_eq!( DatabaseConfigs::FILE_NAME, "database.toml" );
+    assert_eq!( DatabaseArgs::default().pool_size, None );
+}
+
+#[test]
+#[allow( clippy::result_large_err )]
+fn section_is_loaded_from_the_file_env_and_cli() {
+    // The jail restores the environment variables, and runs the tests changing them one at a time.
+    Jail::expect_with( |jail| {
+        let dir = create_configs_dir( "layered" );
+        fs::write(
+            dir.join( "server.toml" ),
+            "[default]\nport = 6000\nworkers = 8\n[production]\nworkers = 16\n",
+        )
+        .unwrap();
+        jail.set_env( "LAYERED_SERVER_WORKERS", "32" );
+
+        let cli_args = CliArgsServer {
+            port:  Some( 7000 ),
+            token: Some( "from-cli".to_string() ),
+        };
+        let run_env = RuntimeEnvironment::Production;
+        let layers = Layers {
+            configs_dir: &dir,
+            env_prefix:  "LAYERED",
+            secrets_dir: &dir.join( "secrets" ),
+            profiles:    &Profiles::default(),
+            run_env:     Some( &run_env ),
+        };
+        let ( configs, report ) = ServerConfigs::load( &layers, &cli_args ).unwrap();
+
+        assert_eq!( configs.port, 7000 );
+        assert_eq!( configs.token.expose_secret(), "from-cli" );
+        assert_eq!( configs.workers, 32 );
+        let origins: Vec<String> = report.entries().iter().map( |entry| entry.origin.to_string() ).collect();
+        assert!( origins.contains( &"env LAYERED_SERVER_WORKERS".to_string() ) );
+
+        fs::remove_dir_all( dir ).unwrap();
+        Ok( () )
+    } );
+}
@@ -0,0 +1,85 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use settings::{FigmentExtractor, ProfileError, Profiles, RuntimeEnvironment};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TestConfigs {
+    port:    u16,
+    workers: usize,
+}
+
+impl FigmentExtractor<'_, Self> for TestConfigs {}
+
+fn create_configs_dir( name: &str, profiles: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "settings-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+    fs::write( dir.join( settings::PROFILES_FILE_NAME ), profiles ).unwrap();
+    dir
+}
+
+#[test]
+fn runtime_environments_are_parsed_without_fallback() {
+    assert_eq!( "prod".parse::<RuntimeEnvironment>().unwrap(), RuntimeEnvironment::Production );
+    assert_eq!( "Staging".parse::<RuntimeEnvironment>().unwrap(), RuntimeEnvironment::Staging );
+    assert_eq!( "test".parse::<RuntimeEnvironment>().unwrap(), RuntimeEnvironment::Test );
+    assert_eq!( "alice".parse::<RuntimeEnvironment>().unwrap(), RuntimeEnvironment::Custom( "alice".to_string() ) );
+
+    assert!( matches!( "Alice Smith".parse::<RuntimeEnvironment>(), Err( ProfileError::InvalidName( _ ) ) ) );
+    assert!( matches!( "default".parse::<RuntimeEnvironment>(), Err( ProfileError::InvalidName( _ ) ) ) );
+}
+
+#[test]
+fn profiles_extend_each_other() {
+    let dir = create_configs_dir(
+        "profiles",
+        "[staging]\nextends = \"production\"\n[alice]\nextends = \"staging\"\n[bob]\n",
+    );
+    let profiles = Profiles::load( &dir ).unwrap();
+
+    let alice = RuntimeEnvironment::Custom( "alice".to_string() );
+    assert_eq!(
+        profiles.resolve( &alice ).unwrap(),
+        vec![RuntimeEnvironment::Production, RuntimeEnvironment::Staging, alice]
+    );
+    assert_eq!( profiles.resolve( &RuntimeEnvironment::Test ).unwrap(), vec![RuntimeEnvironment::Test] );
+    assert!( profiles.resolve( &RuntimeEnvironment::Custom( "bob".to_string() ) ).is_ok() );
+
+    let unknown = RuntimeEnvironment::Custom( "prodution".to_string() );
+    assert!( matches!( profiles.resolve( &unknown ), Err( ProfileError::Unknown( _ ) ) ) );
+
+    fs::remove_dir_all( dir ).unwrap();
+}
+
+#[test]
+fn profiles_extending_each_other_in_a_loop_fail() {
+    let dir = create_configs_dir( "profiles-cycle", "[staging]\nextends = \"test\"\n[test]\nextends = \"staging\"\n" );
+    let profiles = Profiles::load( &dir ).unwrap();
+
+    assert!( matches!( profiles.resolve( &RuntimeEnvironment::Staging ), Err( ProfileError::Cycle( _ ) ) ) );
+    fs::remove_dir_all( dir ).unwrap();
+}
+
+#[test]
+fn extended_profile_values_are_overridden() {
+    let dir = create_configs_dir( "profiles-extract", "[staging]\nextends = \"production\"\n" );
+    let file = dir.join( "server.toml" );
+    fs::write(
+        &file,
+        "[default]\nport = 5555\nworkers = 2\n[production]\nport = 80\nworkers = 16\n[staging]\nworkers = 4\n",
+    )
+    .unwrap();
+    let profiles = Profiles::load( &dir ).unwrap();
+
+    let configs = TestConfigs::extract::<()>()
+        .env( &RuntimeEnvironment::Staging )
+        .profiles( &profiles )
+        .file( file )
+        .call()
+        .unwrap();
+
+    assert_eq!( configs.port, 80 );
+    assert_eq!( configs.workers, 4 );
+    fs::remove_dir_all( dir ).unwrap();
+}
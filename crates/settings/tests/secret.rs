@@ -0,0 +1,147 @@
+use std::{fs, path::PathBuf};
+
+use figment::Jail;
+use serde::{Deserialize, Serialize};
+use settings::{FigmentExtractor, Masked, Origin, Secret};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TestConfigs {
+    url:          Secret<String>,
+    password:     Secret<String>,
+    port:         u16,
+    pool_size:    u32,
+    do_migration: bool,
+}
+
+impl Default for TestConfigs {
+    fn default() -> Self {
+        Self {
+            url:          Secret::new( "postgres://default".to_string() ),
+            password:     Secret::new( "default-password".to_string() ),
+            port:         5432,
+            pool_size:    10,
+            do_migration: false,
+        }
+    }
+}
+
+impl FigmentExtractor<'_, Self> for TestConfigs {}
+
+fn create_secrets_dir( name: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "settings-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+    dir
+}
+
+#[test]
+#[allow( clippy::result_large_err )]
+fn secrets_are_read_from_the_secrets_dir_and_file_env_variables() {
+    Jail::expect_with( |jail| {
+        let dir = create_secrets_dir( "secrets" );
+        fs::write( dir.join( "secret_test_database_url" ), "postgres://app:s3cret@db/photos\n" ).unwrap();
+        fs::write( dir.join( "secret_test_database_password" ), "from-dir" ).unwrap();
+        fs::write( dir.join( "password" ), "from-env-file\n" ).unwrap();
+        jail.set_env( "SECRET_TEST_DATABASE_PASSWORD_FILE", dir.join( "password" ).display() );
+
+        let ( configs, report ) = TestConfigs::extract::<()>()
+            .env_prefix( "SECRET_TEST_DATABASE_" )
+            .secrets_dir( dir.clone() )
+            .call_with_report()
+            .unwrap();
+
+        assert_eq!( configs.url.expose_secret(), "postgres://app:s3cret@db/photos" );
+        // The `_FILE` environment variables take precedence over the secrets directory.
+        assert_eq!( configs.password.expose_secret(), "from-env-file" );
+        assert_eq!( configs.port, 5432 );
+
+        let url_entry = report.entries().iter().find( |entry| entry.key == "url" ).unwrap();
+        assert_eq!( url_entry.origin, Origin::File( dir.join( "secret_test_database_url" ) ) );
+        assert!( !report.to_toml().contains( "s3cret" ) );
+        assert!( !format!( "{configs:?}" ).contains( "s3cret" ) );
+
+        fs::remove_dir_all( &dir ).unwrap();
+        Ok( () )
+    } );
+}
+
+#[test]
+#[allow( clippy::result_large_err )]
+fn secret_files_are_parsed_like_the_env_variables() {
+    Jail::expect_with( |_| {
+        let dir = create_secrets_dir( "typed-secrets" );
+        fs::write( dir.join( "secret_test_typed_pool_size" ), "32\n" ).unwrap();
+        fs::write( dir.join( "secret_test_typed_do_migration" ), "true\n" ).unwrap();
+        // As in the environment variables, a string reading as a number is quoted.
+        fs::write( dir.join( "secret_test_typed_password" ), "\"123456\"\n" ).unwrap();
+
+        let configs = TestConfigs::extract::<()>()
+            .env_prefix( "SECRET_TEST_TYPED_" )
+            .secrets_dir( dir.clone() )
+            .call()
+            .unwrap();
+
+        assert_eq!( configs.pool_size, 32 );
+        assert!( configs.do_migration );
+        assert_eq!( configs.password.expose_secret(), "123456" );
+
+        fs::remove_dir_all( &dir ).unwrap();
+        Ok( () )
+    } );
+}
+
+#[test]
+fn default_secrets_are_kept_when_filling_missing_values() {
+    let ( configs, report ) = TestConfigs::extract::<()>().call_with_report().unwrap();
+
+    assert_eq!( configs.password.expose_secret(), "default-password" );
+    assert!( report.to_toml().contains( "password = \"[REDACTED]\" # default" ) );
+}
+
+#[test]
+#[allow( clippy::result_large_err )]
+fn missing_secret_file_fails_the_extraction() {
+    Jail::expect_with( |jail| {
+        jail.set_env( "SECRET_TEST_MISSING_URL_FILE", "/nonexistent/secret" );
+
+        let result = TestConfigs::extract::<()>().env_prefix( "SECRET_TEST_MISSING_" ).call();
+
+        assert!( result.is_err() );
+        Ok( () )
+    } );
+}
+
+#[test]
+fn secrets_are_serialized_with_their_value_unless_masked() {
+    let configs = TestConfigs::default();
+
+    let exposed = toml::to_string( &configs ).unwrap();
+    assert!( exposed.contains( "password = \"default-password\"" ) );
+
+    let masked = toml::to_string( &Masked( &configs ) ).unwrap();
+    assert!( masked.contains( "password = \"[REDACTED]\"" ) );
+    assert!( masked.contains( "port = 5432" ) );
+    assert!( !masked.contains( "default-password" ) );
+}
+
+#[test]
+fn nested_secrets_are_masked() {
+    #[derive(Serialize)]
+    struct NestedConfigs {
+        database: TestConfigs,
+        token:    Option<Secret<String>>,
+        tokens:   Vec<Secret<String>>,
+    }
+    let configs = NestedConfigs {
+        database: TestConfigs::default(),
+        token:    Some( Secret::new( "hunter2".to_string() ) ),
+        tokens:   vec![Secret::new( "hunter3".to_string() )],
+    };
+
+    let masked = toml::to_string( &Masked( &configs ) ).unwrap();
+
+    assert!( !masked.contains( "default-password" ) );
+    assert!( !masked.contains( "hunter" ) );
+    assert!( masked.contains( "token = \"[REDACTED]\"" ) );
+    assert!( masked.contains( "tokens = [\"[REDACTED]\"]" ) );
+}
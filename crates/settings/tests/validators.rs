@@ -0,0 +1,151 @@
+use std::{fs, net::SocketAddr, path::PathBuf};
+
+use serde::Deserialize;
+use settings::validators::{
+    BoundedUrl, ByteSize, Duration, FilePath, HttpToken, Https, ListenAddr, ListenAddrs, Origin, Ratio, SocketAddrs,
+};
+
+#[derive(Debug, Deserialize)]
+struct TestConfigs {
+    body_limit: ByteSize,
+    timeout:    Duration,
+    upstream:   BoundedUrl,
+    listeners:  SocketAddrs,
+}
+
+#[test]
+fn byte_sizes_are_parsed_with_units() {
+    assert_eq!( "20MiB".parse::<ByteSize>().unwrap().as_u64(), 20 * 1024 * 1024 );
+    assert_eq!( "1.5 GB".parse::<ByteSize>().unwrap().as_u64(), 1_500_000_000 );
+    assert_eq!( "512".parse::<ByteSize>().unwrap().as_u64(), 512 );
+    assert_eq!( "2kib".parse::<ByteSize>().unwrap().to_string(), "2KiB" );
+
+    assert!( "20 parsecs".parse::<ByteSize>().is_err() );
+    assert!( "MiB".parse::<ByteSize>().is_err() );
+    assert!( "20000000TiB".parse::<ByteSize>().is_err() );
+}
+
+#[test]
+fn durations_are_parsed_with_units() {
+    assert_eq!( "30m".parse::<Duration>().unwrap().as_std().as_secs(), 30 * 60 );
+    assert_eq!( "1h 30m".parse::<Duration>().unwrap().to_string(), "1h30m" );
+    assert_eq!( "500ms".parse::<Duration>().unwrap().as_std().as_millis(), 500 );
+
+    assert!( "30".parse::<Duration>().is_err() );
+    assert!( "30 minutes".parse::<Duration>().is_err() );
+    assert!( "".parse::<Duration>().is_err() );
+}
+
+#[test]
+fn ratios_are_between_zero_and_one() {
+    assert_eq!( Ratio::try_from( 0.25 ).unwrap().as_f64(), 0.25 );
+    assert!( Ratio::try_from( 0.0 ).is_ok() );
+    assert!( Ratio::try_from( 1.0 ).is_ok() );
+
+    assert!( Ratio::try_from( 1.5 ).is_err() );
+    assert!( Ratio::try_from( -0.1 ).is_err() );
+    assert!( Ratio::try_from( f64::NAN ).is_err() );
+    assert!( toml::Value::Float( 2.0 ).try_into::<Ratio>().is_err() );
+}
+
+#[test]
+fn urls_are_restricted_by_scheme() {
+    assert!( "http://localhost:5555".parse::<BoundedUrl>().is_ok() );
+    assert!( "ftp://localhost".parse::<BoundedUrl>().is_err() );
+    assert!( "http://localhost".parse::<BoundedUrl<Https>>().is_err() );
+}
+
+#[test]
+fn socket_addrs_are_distinct_and_not_empty() {
+    let addrs: SocketAddrs = "127.0.0.1:8080, [::1]:8080".parse().unwrap();
+    let expected: Vec<SocketAddr> = vec!["127.0.0.1:8080".parse().unwrap(), "[::1]:8080".parse().unwrap()];
+    assert_eq!( addrs.as_ref(), expected.as_slice() );
+
+    assert!( "".parse::<SocketAddrs>().is_err() );
+    assert!( "127.0.0.1:8080,127.0.0.1:8080".parse::<SocketAddrs>().is_err() );
+}
+
+#[test]
+fn listen_addrs_are_tcp_or_unix_sockets() {
+    let addrs: ListenAddrs = "0.0.0.0:8080, [::]:8080, unix:/run/app.sock".parse().unwrap();
+    let expected = vec![
+        ListenAddr::Tcp( "0.0.0.0:8080".parse().unwrap() ),
+        ListenAddr::Tcp( "[::]:8080".parse().unwrap() ),
+        ListenAddr::Unix( PathBuf::from( "/run/app.sock" ) ),
+    ];
+    assert_eq!( addrs.as_ref(), expected.as_slice() );
+    assert_eq!( addrs.to_string(), "0.0.0.0:8080, [::]:8080, unix:/run/app.sock" );
+
+    assert!( "unix:".parse::<ListenAddrs>().is_err() );
+    assert!( "localhost".parse::<ListenAddrs>().is_err() );
+    assert!( "unix:/run/app.sock,unix:/run/app.sock".parse::<ListenAddrs>().is_err() );
+}
+
+#[test]
+fn origins_are_exact_or_wildcard_subdomains() {
+    let exact: Origin = "https://example.com:443".parse().unwrap();
+    assert_eq!( exact.to_string(), "https://example.com" );
+    assert!( exact.matches( "https://example.com" ) );
+    assert!( !exact.matches( "http://example.com" ) );
+
+    let subdomains: Origin = "https://*.example.com".parse().unwrap();
+    assert_eq!( subdomains.to_string(), "https://*.example.com" );
+    assert!( subdomains.matches( "https://app.example.com" ) );
+    assert!( subdomains.matches( "https://a.b.example.com" ) );
+    assert!( !subdomains.matches( "https://example.com" ) );
+    assert!( !subdomains.matches( "https://app.badexample.com" ) );
+    assert!( !subdomains.matches( "https://app.example.com:8443" ) );
+
+    assert!( "*".parse::<Origin>().unwrap().matches( "http://localhost:5556" ) );
+    assert!( "https://example.com/path".parse::<Origin>().is_err() );
+    assert!( "example.com".parse::<Origin>().is_err() );
+    assert!( "ftp://example.com".parse::<Origin>().is_err() );
+}
+
+#[test]
+fn http_tokens_are_methods_or_header_names() {
+    assert_eq!( "content-type".parse::<HttpToken>().unwrap().as_str(), "content-type" );
+    assert!( "GET".parse::<HttpToken>().is_ok() );
+    assert!( "*".parse::<HttpToken>().is_ok() );
+
+    assert!( "".parse::<HttpToken>().is_err() );
+    assert!( "x header".parse::<HttpToken>().is_err() );
+}
+
+#[test]
+fn file_path_must_be_an_existing_file() {
+    let path = std::env::temp_dir().join( format!( "settings-tests-file-path-{}", std::process::id() ) );
+    assert!( FilePath::try_from( path.clone() ).is_err() );
+
+    fs::write( &path, "cert" ).unwrap();
+    assert!( FilePath::try_from( path.clone() ).is_ok() );
+    assert!( FilePath::try_from( std::env::temp_dir() ).is_err() );
+    fs::remove_file( path ).unwrap();
+}
+
+#[test]
+fn configs_are_validated_on_deserialization() {
+    let configs: TestConfigs = toml::from_str(
+        r#"
+        body_limit = 1048576
+        timeout = "10s"
+        upstream = "https://example.com"
+        listeners = ["0.0.0.0:80", "[::]:80"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!( configs.body_limit.to_string(), "1MiB" );
+    assert_eq!( configs.timeout.as_std().as_secs(), 10 );
+    assert_eq!( configs.upstream.as_ref().scheme(), "https" );
+    assert_eq!( configs.listeners.iter().count(), 2 );
+
+    let result = toml::from_str::<TestConfigs>(
+        r#"
+        body_limit = "1MiB"
+        timeout = "10 years"
+        upstream = "https://example.com"
+        listeners = "0.0.0.0:80"
+        "#,
+    );
+    assert!( result.unwrap_err().to_string().contains( "unknown unit" ) );
+}
@@ -0,0 +1,64 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use settings::{ConfigWatcher, FigmentExtractor};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TestConfigs {
+    log_level: String,
+    port:      u16,
+}
+
+impl Default for TestConfigs {
+    fn default() -> Self {
+        Self {
+            log_level: "info".to_string(),
+            port:      5555,
+        }
+    }
+}
+
+impl FigmentExtractor<'_, Self> for TestConfigs {}
+
+fn create_configs_dir( name: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "settings-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+    dir
+}
+
+fn load( configs_dir: &Path ) -> Result<TestConfigs, settings::FigmentExtractionFailedError> {
+    TestConfigs::extract::<()>().file( configs_dir.join( "app.toml" ) ).call()
+}
+
+#[test]
+fn reloadable_settings_are_published_and_the_others_kept() {
+    let dir = create_configs_dir( "watcher" );
+    fs::write( dir.join( "app.toml" ), "[default]\nlog_level = \"info\"\nport = 5555\n" ).unwrap();
+
+    let reload_dir = dir.clone();
+    let watcher =
+        ConfigWatcher::spawn( &dir, load( &dir ).unwrap(), &["log_level"], move || load( &reload_dir ) ).unwrap();
+    let mut configs = watcher.subscribe();
+
+    fs::write( dir.join( "app.toml" ), "[default]\nlog_level = \"debug\"\nport = 6000\n" ).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs( 5 );
+    while !configs.has_changed().unwrap() {
+        assert!( Instant::now() < deadline, "The settings were not reloaded." );
+        thread::sleep( Duration::from_millis( 50 ) );
+    }
+
+    let reloaded = configs.borrow_and_update();
+    assert_eq!( reloaded.log_level, "debug" );
+    // The port is not reloadable, the value in use is kept.
+    assert_eq!( reloaded.port, 5555 );
+    drop( reloaded );
+
+    fs::remove_dir_all( &dir ).unwrap();
+}
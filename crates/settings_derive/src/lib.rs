@@ -0,0 +1,241 @@
+//! Derive macro of the `settings::LayeredSettings` trait.
+//!
+//! A settings section is declared once, as a struct of configs. The macro generates from it the command line arguments
+//! struct, the `FigmentExtractor` implementation, and the section name from which the configuration file and the
+//! environment variables prefix are taken.
+//!
+//! # Attributes
+//!
+//! On the struct, all optional:
+//!
+//! - `section = "server"` - The name of the section. Defaults to the struct name without the `Configs` suffix, in
+//!   snake case.
+//! - `file = "server.toml"` - The configuration file of the section. Defaults to the section with the `toml` extension.
+//! - `cli_args = CliArgsServer` - The name of the command line arguments struct. Defaults to `CliArgs` followed by the
+//!   struct name without the `Configs` suffix.
+//!
+//! On the fields, `cli( ... )` exposes the field as a command line argument. The arguments are forwarded to the clap
+//! `#[arg( ... )]` attribute, except `ty = Type`, which sets the type parsed from the command line when the field type
+//! can't be, e.g. a `Secret<String>` read as a `String`. The doc comment of the field is the help of the argument.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! #[derive(Default, Serialize, Deserialize, LayeredSettings)]
+//! pub struct ServerConfigs {
+//!     /// Set the listen addresses.
+//!     #[settings( cli( short = 'a', long = "addr" ) )]
+//!     pub listen_addrs: ListenAddrs,
+//!     pub metrics:      MetricsSubconfig,
+//! }
+//! ```
+#![deny( clippy::all )]
+#![warn( clippy::pedantic )]
+#![warn( clippy::nursery )]
+#![warn( clippy::complexity )]
+#![warn( clippy::perf )]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parenthesized, parse_macro_input, punctuated::Punctuated, Attribute, Data, DeriveInput, Fields, Ident, LitStr, Meta,
+    Token, Type,
+};
+
+/// Suffix removed from the struct name to get the section name.
+const CONFIGS_SUFFIX: &str = "Configs";
+
+/// Derives `settings::LayeredSettings` and `settings::FigmentExtractor`, along with the command line arguments struct.
+#[proc_macro_derive( LayeredSettings, attributes( settings ) )]
+pub fn derive_layered_settings( input: TokenStream ) -> TokenStream {
+    let input = parse_macro_input!( input as DeriveInput );
+    expand( &input ).unwrap_or_else( syn::Error::into_compile_error ).into()
+}
+
+fn expand( input: &DeriveInput ) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err( syn::Error::new_spanned( &input.generics, "Generic settings are not supported." ) );
+    }
+
+    let Data::Struct( data ) = &input.data else {
+        return Err( syn::Error::new_spanned( input, "The settings must be a struct." ) );
+    };
+    let Fields::Named( fields ) = &data.fields else {
+        return Err( syn::Error::new_spanned( &data.fields, "The settings must have named fields." ) );
+    };
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let options = StructOptions::parse( input )?;
+
+    let cli_fields = fields
+        .named
+        .iter()
+        .map( |field| {
+            let Some( args ) = cli_args( &field.attrs )? else {
+                return Ok( None );
+            };
+            let ident = field.ident.as_ref().expect( "The fields are named." );
+            Ok( Some( args.into_field( ident, &field.ty, docs( &field.attrs ) ) ) )
+        } )
+        .collect::<syn::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten();
+
+    let cli_name = &options.cli_args;
+    let cli_doc = format!( "Command line arguments of the [`{name}`]." );
+    let section = &options.section;
+    let file = &options.file;
+
+    Ok( quote! {
+        #[doc = #cli_doc]
+        #[derive(
+            ::clap::Args,
+            ::std::fmt::Debug,
+            ::std::clone::Clone,
+            ::std::default::Default,
+            ::serde::Serialize,
+            ::serde::Deserialize,
+        )]
+        #vis struct #cli_name {
+            #( #cli_fields )*
+        }
+
+        impl ::settings::FigmentExtractor<'_, Self> for #name {}
+
+        impl ::settings::LayeredSettings for #name {
+            type CliArgs = #cli_name;
+
+            const FILE_NAME: &'static str = #file;
+            const SECTION: &'static str = #section;
+        }
+    } )
+}
+
+/// The options of the `settings` attribute of the struct.
+struct StructOptions {
+    section:  String,
+    file:     String,
+    cli_args: Ident,
+}
+
+impl StructOptions {
+    fn parse( input: &DeriveInput ) -> syn::Result<Self> {
+        let name = input.ident.to_string();
+        let short_name = name.strip_suffix( CONFIGS_SUFFIX ).unwrap_or( &name );
+
+        let mut section = None;
+        let mut file = None;
+        let mut cli_args = None;
+        for attr in input.attrs.iter().filter( |attr| attr.path().is_ident( "settings" ) ) {
+            attr.parse_nested_meta( |meta| {
+                if meta.path.is_ident( "section" ) {
+                    section = Some( meta.value()?.parse::<LitStr>()?.value() );
+                } else if meta.path.is_ident( "file" ) {
+                    file = Some( meta.value()?.parse::<LitStr>()?.value() );
+                } else if meta.path.is_ident( "cli_args" ) {
+                    cli_args = Some( meta.value()?.parse::<Ident>()? );
+                } else {
+                    return Err( meta.error( "Expected `section`, `file` or `cli_args`." ) );
+                }
+
+                Ok( () )
+            } )?;
+        }
+
+        let section = section.unwrap_or_else( || to_snake_case( short_name ) );
+        Ok( Self {
+            file: file.unwrap_or_else( || format!( "{section}.toml" ) ),
+            cli_args: cli_args.unwrap_or_else( || format_ident!( "CliArgs{short_name}" ) ),
+            section,
+        } )
+    }
+}
+
+/// The arguments of the `cli` option of a field.
+struct CliArgs {
+    args: Punctuated<Meta, Token![,]>,
+    ty:   Option<Type>,
+}
+
+impl CliArgs {
+    fn into_field( self, ident: &Ident, field_ty: &Type, docs: Vec<&Attribute> ) -> TokenStream2 {
+        let ty = self.ty.as_ref().unwrap_or( field_ty );
+        let has_name = self.args.iter().any( |arg| arg.path().is_ident( "long" ) || arg.path().is_ident( "short" ) );
+        let has_help = self.args.iter().any( |arg| arg.path().is_ident( "help" ) );
+
+        // Without a name the argument would be positional.
+        let mut args = self.args.into_iter().map( ToTokens::into_token_stream ).collect::<Vec<_>>();
+        if !has_name {
+            args.insert( 0, quote!( long ) );
+        }
+        let docs = if has_help { Vec::new() } else { docs };
+
+        quote! {
+            #( #docs )*
+            #[arg( #( #args ),* )]
+            #[serde( skip_serializing_if = "Option::is_none" )]
+            // clap only recognizes the optional arguments by a bare `Option`.
+            #ident: Option<#ty>,
+        }
+    }
+}
+
+/// Parses the `cli` option of the `settings` attributes of a field, if any.
+fn cli_args( attrs: &[Attribute] ) -> syn::Result<Option<CliArgs>> {
+    let mut cli_args = None;
+    for attr in attrs.iter().filter( |attr| attr.path().is_ident( "settings" ) ) {
+        attr.parse_nested_meta( |meta| {
+            if !meta.path.is_ident( "cli" ) {
+                return Err( meta.error( "Expected `cli`." ) );
+            }
+
+            // A bare `cli` is a long argument named like the field.
+            let mut args = if meta.input.peek( syn::token::Paren ) {
+                let content;
+                parenthesized!( content in meta.input );
+                Punctuated::<Meta, Token![,]>::parse_terminated( &content )?
+            } else {
+                Punctuated::new()
+            };
+
+            // The type is not a clap argument, it is taken out of the list.
+            let mut ty = None;
+            args = args
+                .into_iter()
+                .filter_map( |arg| match arg {
+                    Meta::NameValue( name_value ) if name_value.path.is_ident( "ty" ) => {
+                        ty = Some( syn::parse2::<Type>( name_value.value.into_token_stream() ) );
+                        None
+                    }
+                    arg => Some( arg ),
+                } )
+                .collect();
+
+            cli_args = Some( CliArgs {
+                args,
+                ty: ty.transpose()?,
+            } );
+            Ok( () )
+        } )?;
+    }
+
+    Ok( cli_args )
+}
+
+fn docs( attrs: &[Attribute] ) -> Vec<&Attribute> {
+    attrs.iter().filter( |attr| attr.path().is_ident( "doc" ) ).collect()
+}
+
+fn to_snake_case( name: &str ) -> String {
+    let mut snake_case = String::with_capacity( name.len() + 4 );
+    for ( index, character ) in name.char_indices() {
+        if character.is_uppercase() && index > 0 {
+            snake_case.push( '_' );
+        }
+        snake_case.extend( character.to_lowercase() );
+    }
+
+    snake_case
+}
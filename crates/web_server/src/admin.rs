@@ -0,0 +1,13 @@
+//! Admin routes of the web server, authenticated by the [`admin::require_admin_token`] middleware.
+
+use axum::{middleware, Extension, Router};
+use monitoring::log_filter;
+pub use server_common::admin::AdminToken;
+use server_common::admin;
+
+pub fn create_route( admin_token: AdminToken ) -> Router {
+    Router::new()
+        .merge( log_filter::create_log_filter_route() )
+        .route_layer( middleware::from_fn( admin::require_admin_token ) )
+        .layer( Extension( admin_token ) )
+}
@@ -1,40 +1,59 @@
-use axum::{body::Body, extract::State, http::Request, response::IntoResponse, routing::get_service, Router};
-use hyper::{client::HttpConnector, Uri};
+use axum::{routing::get_service, Router};
 use leptos::LeptosOptions;
-use settings::validators;
+use monitoring::prometheus;
 use tower_http::{
-    compression::CompressionLayer,
     cors::CorsLayer,
     services::{ServeDir, ServeFile},
 };
-use url::Url;
 
+use crate::{
+    admin,
+    assets::{self, AssetManifest},
+    compression, health, proxy,
+    security_headers::{self, SecurityHeaders},
+    settings,
+    shutdown::Shutdown,
+};
 #[cfg( feature = "ssr" )]
 use crate::ssr;
 
-#[derive(Debug, Clone)]
-struct ReverseProxyState {
-    client:  hyper::client::Client<HttpConnector, Body>,
-    api_url: Url,
-}
-
 #[derive(Debug, Clone)]
 struct EraseState;
 
+/// The settings and the shared state of the main router.
+pub struct AppContext<'a> {
+    /// The server settings, e.g. the directories of the files and the URL of the backend.
+    pub settings:         &'a settings::ServerConfigs,
+    pub leptos_options:   LeptosOptions,
+    pub shutdown:         Shutdown,
+    /// The fingerprinted static files, linked by the pages.
+    pub asset_manifest:   AssetManifest,
+    /// The CORS layer, following the reloaded settings.
+    pub cors_layer:       CorsLayer,
+    pub security_headers: SecurityHeaders,
+}
+
 /// Create the main router.
-pub async fn create(
-    static_dir: validators::DirectoryPath,
-    assets_dir: validators::DirectoryPath,
-    api_url: Url,
-    _leptos_options: LeptosOptions,
-) -> Router {
+pub async fn create( context: AppContext<'_> ) -> Router {
+    let AppContext {
+        settings,
+        leptos_options: _leptos_options,
+        shutdown,
+        asset_manifest,
+        cors_layer,
+        security_headers,
+    } = context;
+    let assets_dir = &settings.assets_dir;
+
     // Main router.
     let mut app = Router::new();
 
     // Shared state.
-    let reverse_proxy_state = ReverseProxyState {
-        client: hyper::Client::builder().build( HttpConnector::new() ),
-        api_url,
+    let proxy_state = proxy::ProxyState::new( settings.proxy_url.clone(), &settings.proxy );
+    let health_state = health::HealthState {
+        client: proxy_state.client.clone(),
+        api_url: proxy_state.api_url.clone(),
+        shutdown,
     };
 
     // Robot.txt file get service.
@@ -46,56 +65,64 @@ pub async fn create(
     let favicon_file = get_service( ServeFile::new( favicon_path ) );
 
     // Static files directory get service.
-    let serve_static_dir = get_service( ServeDir::new( static_dir ).precompressed_br() );
+    let serve_static_dir = get_service( compression::precompressed( ServeDir::new( &settings.static_dir ) ) );
 
     // Assets files directory get service.
-    let serve_assets_dir = get_service( ServeDir::new( &assets_dir ).precompressed_br() );
+    let serve_assets_dir = get_service( compression::precompressed( ServeDir::new( assets_dir ) ) );
 
-    // Routes.
-    app = app
+    // Files routes, the fingerprinted ones cached as immutable and the others revalidated with their `ETag`.
+    let files = Router::new()
         .route( "/robots.txt", robots_file )
         .route( "/favicon.ico", favicon_file )
-        .nest_service( "/static", serve_static_dir )
+        .nest_service( assets::STATIC_URL_PATH, serve_static_dir )
         .nest_service( "/assets", serve_assets_dir );
+    app = app.merge( assets::middleware_cache_control( files, &asset_manifest, &settings.cache ) );
+
+    // The server functions reach the backend over HTTP, with the client of the reverse proxy.
+    #[cfg( feature = "ssr" )]
+    let backend_api = ssr::create_backend_api( proxy_state.clone() );
 
     // API Route handled by reverse proxy.
-    let api_router = Router::new()
-        .fallback( api_reverse_proxy_handler )
-        .with_state( reverse_proxy_state );
-    app = app.nest( "/api", api_router );
+    app = app.nest( "/api", proxy::create_route( proxy_state ) );
 
-    // Server side rendering.
+    // Admin routes, such as the runtime log filter.
+    app = app.nest( "/admin", admin::create_route( admin::AdminToken( settings.admin_token.clone() ) ) );
+
+    // Liveness and readiness probes.
+    app = app.nest( "/health", health::create_route( health_state ) );
+
+    // Content-Security-Policy violation reports.
+    if security_headers.content_security_policy().is_some_and( |csp| csp.is_reported() ) {
+        app = app.merge( security_headers::create_csp_report_route() );
+    }
+
+    // Server side rendering, sending the Content-Security-Policy with the nonce of each page.
+    #[cfg( feature = "ssr" )]
+    let page_context = ssr::PageContext {
+        csp: security_headers.content_security_policy().cloned(),
+        asset_manifest,
+        backend_api,
+    };
     #[cfg( feature = "ssr" )]
-    let mut app: Router = ssr::leptos_routes( app, _leptos_options ).await;
+    let mut app: Router = ssr::leptos_routes( app, _leptos_options, page_context ).await;
+
+    // Prometheus metrics, on the main router unless a dedicated listener is configured.
+    if settings.metrics.is_exposed && settings.metrics.sock_addr.is_none() {
+        app = app.merge( prometheus::create_metrics_route() );
+    }
+    app = prometheus::middleware_http_metrics( app );
 
     // Http tracing logs middleware layer.
     app = crate::logger::middleware_http_tracing( app );
 
-    // Compression.
-    app = app.layer( CompressionLayer::new().br( true ).no_gzip().no_deflate() );
+    // Security headers.
+    app = security_headers::middleware_security_headers( app, security_headers );
+
+    // Compression, negotiated with each request.
+    app = compression::middleware_compression( app, &settings.compression );
 
     // Cors.
-    app = app.layer( CorsLayer::permissive() );
+    app = app.layer( cors_layer );
 
     app
 }
-
-/// Reverse proxy requests to the API on the backend.
-async fn api_reverse_proxy_handler(
-    State( state ): State<ReverseProxyState>,
-    mut req: Request<Body>,
-) -> axum::response::Response {
-    let path_query = req
-        .uri()
-        .path_and_query()
-        .map_or_else( || req.uri().path(), |v| v.as_str() );
-
-    *req.uri_mut() = Uri::builder()
-        .scheme( state.api_url.scheme() )
-        .authority( state.api_url.authority() )
-        .path_and_query( path_query )
-        .build()
-        .unwrap();
-
-    state.client.request( req ).await.unwrap().into_response()
-}
@@ -0,0 +1,175 @@
+//! Caching of the static files and the assets.
+//!
+//! The release build renames the files of the static directory after a hash of their content, e.g. `frontend.css` to
+//! `frontend.3f2a9c1e07d4b6a5.css`, and records their names in its `manifest.json`. The pages link the fingerprinted
+//! URLs of the [`AssetManifest`], which are cached as immutable. The other files keep a short max-age, after which the
+//! browsers revalidate them with their `ETag`.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    path::{Path, PathBuf},
+};
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+pub use common::assets::AssetManifest;
+use thiserror::Error;
+
+use crate::settings;
+
+/// URL path of the static files directory.
+pub const STATIC_URL_PATH: &str = "/static";
+
+/// Name of the manifest of the fingerprinted files, in the static files directory.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// `Cache-Control` of the fingerprinted files, cached for a year.
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The manifest can't be read.
+    #[error( "Failed to read the asset manifest: {0}" )]
+    Read( PathBuf, #[source] io::Error ),
+    /// The manifest is not a JSON object of the file names and their fingerprinted names.
+    #[error( "The asset manifest is not valid: {0}" )]
+    Invalid( PathBuf, #[source] serde_json::Error ),
+}
+
+/// Loads the manifest of the `static_dir`. Without manifest, e.g. in development, no file is fingerprinted.
+///
+/// # Errors
+///
+/// If the manifest exists but can't be read or parsed.
+pub fn load_manifest( static_dir: &Path ) -> Result<AssetManifest, Error> {
+    let manifest_path = static_dir.join( MANIFEST_FILE_NAME );
+    let content = match fs::read( &manifest_path ) {
+        Ok( content ) => content,
+        Err( err ) if err.kind() == io::ErrorKind::NotFound => return Ok( AssetManifest::default() ),
+        Err( err ) => return Err( Error::Read( manifest_path, err ) ),
+    };
+    let files: HashMap<String, String> =
+        serde_json::from_slice( &content ).map_err( |err| Error::Invalid( manifest_path, err ) )?;
+
+    Ok( AssetManifest::new( STATIC_URL_PATH, files ) )
+}
+
+/// The output name of the Leptos javascript and wasm files, fingerprinted together.
+#[must_use]
+pub fn fingerprinted_output_name( asset_manifest: &AssetManifest, output_name: &str ) -> String {
+    let js_url = format!( "{STATIC_URL_PATH}/{output_name}.js" );
+    asset_manifest
+        .url( &js_url )
+        .strip_prefix( STATIC_URL_PATH )
+        .and_then( |js_url| js_url.strip_prefix( '/' ) )
+        .and_then( |js_file| js_file.strip_suffix( ".js" ) )
+        .unwrap_or( output_name )
+        .to_string()
+}
+
+#[derive(Debug, Clone)]
+struct CacheControl {
+    asset_manifest:      AssetManifest,
+    /// `Cache-Control` of the files that are not fingerprinted.
+    revalidated_cache_control: Option<HeaderValue>,
+}
+
+impl CacheControl {
+    /// Creates the headers from the settings, whose `Cache-Control` is validated when they are loaded.
+    fn new( asset_manifest: &AssetManifest, cache_settings: &settings::CacheSubconfig ) -> Self {
+        Self {
+            asset_manifest:            asset_manifest.clone(),
+            revalidated_cache_control: HeaderValue::from_str( &cache_settings.revalidated_cache_control() ).ok(),
+        }
+    }
+}
+
+/// Adds the `Cache-Control` of the files served by the `router`, and the `ETag` of the ones that are not fingerprinted.
+pub fn middleware_cache_control<T>(
+    router: Router<T>,
+    asset_manifest: &AssetManifest,
+    cache_settings: &settings::CacheSubconfig,
+) -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let cache_control = CacheControl::new( asset_manifest, cache_settings );
+    router.layer( middleware::from_fn_with_state( cache_control, add_cache_headers ) )
+}
+
+async fn add_cache_headers<B>(
+    State( cache_control ): State<CacheControl>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let is_fingerprinted = cache_control.asset_manifest.is_fingerprinted( request.uri().path() );
+    let if_none_match = request.headers().get( header::IF_NONE_MATCH ).cloned();
+
+    let mut response = next.run( request ).await;
+    if !response.status().is_success() && response.status() != StatusCode::NOT_MODIFIED {
+        return response;
+    }
+
+    if is_fingerprinted {
+        response.headers_mut().insert( header::CACHE_CONTROL, HeaderValue::from_static( IMMUTABLE_CACHE_CONTROL ) );
+        return response;
+    }
+
+    if let Some( revalidated_cache_control ) = cache_control.revalidated_cache_control {
+        response.headers_mut().insert( header::CACHE_CONTROL, revalidated_cache_control );
+    }
+    let Some( etag ) = entity_tag( response.headers() ) else {
+        return response;
+    };
+
+    if if_none_match.is_some_and( |if_none_match| matches_entity_tag( &if_none_match, &etag ) ) {
+        let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+        for name in [header::CACHE_CONTROL, header::LAST_MODIFIED] {
+            if let Some( value ) = response.headers_mut().remove( &name ) {
+                not_modified.headers_mut().insert( name, value );
+            }
+        }
+        not_modified.headers_mut().insert( header::ETAG, etag );
+
+        return not_modified;
+    }
+
+    response.headers_mut().insert( header::ETAG, etag );
+
+    response
+}
+
+/// A weak `ETag` of a file, from its modification date, its size and its encoding. It is weak as the response may still
+/// be compressed.
+fn entity_tag( headers: &HeaderMap ) -> Option<HeaderValue> {
+    let last_modified = headers.get( header::LAST_MODIFIED )?;
+    let content_length = headers.get( header::CONTENT_LENGTH )?;
+
+    let mut hasher = DefaultHasher::new();
+    last_modified.as_bytes().hash( &mut hasher );
+    content_length.as_bytes().hash( &mut hasher );
+    headers.get( header::CONTENT_ENCODING ).map( HeaderValue::as_bytes ).hash( &mut hasher );
+
+    HeaderValue::from_str( &format!( "W/\"{:016x}\"", hasher.finish() ) ).ok()
+}
+
+/// Whether the `If-None-Match` of the request matches the `etag`, with the weak comparison.
+fn matches_entity_tag( if_none_match: &HeaderValue, etag: &HeaderValue ) -> bool {
+    let Ok( if_none_match ) = if_none_match.to_str() else {
+        return false;
+    };
+    let etag = etag.to_str().unwrap_or_default().trim_start_matches( "W/" );
+
+    if_none_match
+        .split( ',' )
+        .map( str::trim )
+        .any( |tag| tag == "*" || tag.trim_start_matches( "W/" ) == etag )
+}
@@ -1,11 +1,16 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+use ::settings::validators::ListenAddr;
 use clap::Parser;
 use error_stack::{Report, ResultExt};
 use leptos::{leptos_config::Env, LeptosOptions};
-use web_server::{logger, settings, Error};
+use web_server::{assets, logger, settings, Error};
 
-fn main() -> Result<(), Report<Error>> {
+#[tokio::main]
+async fn main() -> Result<(), Report<Error>> {
     // Parse the command line arguments.
-    let cli_args = settings::CliArgs::parse();
+    let mut cli_args = settings::CliArgs::parse();
+    let command = cli_args.command.take();
 
     // Get the environment prefix for the settings variables.
     let env_prefix = cli_args
@@ -23,27 +28,54 @@ fn main() -> Result<(), Report<Error>> {
     .change_context( Error::InvalidConfigsDir )?;
 
     // Initialize settings variables.
-    let configs =
+    let mut configs =
         settings::init( configs_dir.as_path(), &env_prefix, &cli_args ).change_context( Error::SettingsInitFailed )?;
 
-    // Tracing logs.
-    let ( _maybe_stdio_writer_guard, _maybe_file_writer_guard ) =
-        logger::init( &configs.general.app_name, configs.logger );
+    // The config commands run before the logger, so their output is not mixed with the logs.
+    match command {
+        Some( settings::Command::CheckConfig ) => {
+            println!( "The settings in {} are valid.", configs_dir.display() );
+            return Ok( () );
+        }
+        Some( settings::Command::PrintConfig ) => {
+            print!( "{}", configs.report.to_masked_toml() );
+            return Ok( () );
+        }
+        None => {}
+    }
+
+    // The directories are only created and checked to run, e.g. the logs one, possibly asking the user for new ones.
+    configs.ensure_dirs( cli_args.is_interactive ).change_context( Error::SettingsInitFailed )?;
+
+    // Tracing logs, within the runtime as the traces are exported by a background task.
+    let ( maybe_stdio_writer_guard, maybe_file_writer_guard ) =
+        logger::init( &configs.general.app_name, configs.logger.clone() );
 
-    let static_dir = configs.server.static_dir.clone();
-    let static_dir = static_dir.as_ref().to_str().unwrap();
+    // Leptos only knows about TCP addresses, with only Unix domain sockets it keeps its default one.
+    let site_addr = configs.server.listen_addrs.iter().find_map( ListenAddr::as_socket_addr );
 
     let leptos_options = LeptosOptions {
         output_name:  configs.general.app_name.clone(),
         site_root:    String::from( "." ),
-        site_pkg_dir: String::from( static_dir ),
-        env:          Env::try_from( configs.general.run_env.to_string() ).unwrap(),
-        site_addr:    configs.server.sock_addr_v4.into(),
+        site_pkg_dir: assets::STATIC_URL_PATH.trim_start_matches( '/' ).to_string(),
+        env:          if configs.general.run_env.is_production() { Env::PROD } else { Env::DEV },
+        site_addr:    site_addr.unwrap_or( SocketAddr::from( ( Ipv4Addr::LOCALHOST, 3000 ) ) ),
         reload_port:  3001,
     };
 
+    // Reload the settings when the configuration files change.
+    let configs_watcher = settings::watch( &configs_dir, &env_prefix, cli_args, configs.clone() )
+        .change_context( Error::SettingsWatchFailed )?;
+
     tracing::info!( "Starting server for {}.", &configs.general.app_name );
-    web_server::init_server( configs.server, leptos_options ).change_context( Error::ServerInitFailed )?;
+    web_server::init_server( configs.server, configs.security_headers, leptos_options, configs_watcher.subscribe() )
+        .await
+        .change_context( Error::ServerInitFailed )?;
+
+    // Flush the traces not exported yet and the logs still buffered in the non-blocking writers.
+    logger::shutdown();
+    drop( maybe_stdio_writer_guard );
+    drop( maybe_file_writer_guard );
 
     Ok( () )
 }
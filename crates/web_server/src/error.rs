@@ -5,6 +5,9 @@ pub enum Error {
     /// Failed to setup the settings.
     #[error( "Failed to setup the settings." )]
     SettingsInitFailed,
+    /// Failed to watch the configuration files.
+    #[error( "Failed to watch the configuration files." )]
+    SettingsWatchFailed,
     /// The configs directory provided is invalid.
     #[error( "The configs directory provided is invalid." )]
     InvalidConfigsDir,
@@ -0,0 +1,113 @@
+//! Liveness and readiness endpoints of the web server.
+//!
+//! The readiness check also asks the backend readiness through the same client and url used by the reverse proxy, so
+//! it fails whenever the proxied API would fail.
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use axum::{body::Body, extract::State, http::Request, response::IntoResponse, routing::get, Router};
+use common::api::health::{Check, Report, Status};
+use server_common::health::{record_readiness, report_response};
+use url::Url;
+
+use crate::{proxy, shutdown::Shutdown};
+
+/// Maximum time the backend readiness check can take before it is considered failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs( 2 );
+
+#[derive(Debug, Clone)]
+pub struct HealthState {
+    pub client:   proxy::Client,
+    pub api_url:  Url,
+    pub shutdown: Shutdown,
+}
+
+pub fn create_route( state: HealthState ) -> Router {
+    Router::new()
+        .route( "/live", get( check_liveness ) )
+        .route( "/ready", get( check_readiness ) )
+        .with_state( state )
+}
+
+async fn check_liveness() -> impl IntoResponse { report_response( Report::new( BTreeMap::new() ) ) }
+
+async fn check_readiness( State( state ): State<HealthState> ) -> impl IntoResponse {
+    let mut checks = BTreeMap::new();
+
+    let shutdown_check = if state.shutdown.is_draining() {
+        failed_check( 0.0, "The server is draining its connections to shut down." )
+    } else {
+        Check {
+            status:     Status::Pass,
+            latency_ms: 0.0,
+            message:    None,
+        }
+    };
+    checks.insert( "shutdown".to_string(), shutdown_check );
+
+    let ( backend_check, backend_report ) = check_backend( &state ).await;
+    checks.insert( "backend".to_string(), backend_check );
+
+    // Expose the backend dependencies too, so alerts can point at the one failing.
+    if let Some( backend_report ) = backend_report {
+        for ( name, check ) in backend_report.checks {
+            checks.insert( format!( "backend.{name}" ), check );
+        }
+    }
+
+    let report = Report::new( checks );
+    record_readiness( &report );
+
+    report_response( report )
+}
+
+/// Asks the backend readiness through the reverse proxy client.
+async fn check_backend( state: &HealthState ) -> ( Check, Option<Report> ) {
+    let start = Instant::now();
+    let latency_ms = || start.elapsed().as_secs_f64() * 1000.0;
+
+    let request = match state.api_url.join( "/health/ready" ) {
+        Ok( url ) => Request::get( url.as_str() ).body( Body::empty() ),
+        Err( err ) => return ( failed_check( latency_ms(), &err.to_string() ), None ),
+    };
+    let mut request = match request {
+        Ok( request ) => request,
+        Err( err ) => return ( failed_check( latency_ms(), &err.to_string() ), None ),
+    };
+    crate::logger::inject_trace_context( request.headers_mut() );
+
+    let response = match tokio::time::timeout( CHECK_TIMEOUT, state.client.request( request ) ).await {
+        Ok( Ok( response ) ) => response,
+        Ok( Err( err ) ) => return ( failed_check( latency_ms(), &err.to_string() ), None ),
+        Err( _ ) => return ( failed_check( latency_ms(), &format!( "Timed out after {CHECK_TIMEOUT:?}." ) ), None ),
+    };
+
+    let status = response.status();
+    let backend_report = hyper::body::to_bytes( response.into_body() )
+        .await
+        .ok()
+        .and_then( |body| serde_json::from_slice::<Report>( &body ).ok() );
+
+    let check = if status.is_success() {
+        Check {
+            status:     Status::Pass,
+            latency_ms: latency_ms(),
+            message:    None,
+        }
+    } else {
+        failed_check( latency_ms(), &format!( "The backend answered with {status}." ) )
+    };
+
+    ( check, backend_report )
+}
+
+fn failed_check( latency_ms: f64, message: &str ) -> Check {
+    Check {
+        status: Status::Fail,
+        latency_ms,
+        message: Some( message.to_string() ),
+    }
+}
@@ -6,31 +6,42 @@
 #![warn( clippy::complexity )]
 #![warn( clippy::perf )]
 
-use std::net::SocketAddr;
-
 pub use error::Error;
 use error_stack::{Report, ResultExt};
 use leptos::LeptosOptions;
+use server_common::{compression, cors, listener, tls};
+pub use server_common::shutdown;
 use thiserror::Error;
 use tracing::instrument;
 
+pub mod assets;
+pub mod health;
 pub mod logger;
+pub mod proxy;
+pub mod security_headers;
 pub mod settings;
 
+mod admin;
 mod app;
 mod error;
 
 #[cfg( feature = "ssr" )]
-mod ssr;
+pub mod ssr;
 
 #[derive(Error, Debug)]
 pub enum InitServerError {
     /// Failed to bind on the provided address.
     #[error( "Failed to bind on the address: {0}" )]
-    AddressBindFailed( SocketAddr ),
+    AddressBindFailed( ::settings::validators::ListenAddr ),
     /// Failed to serve the server.
     #[error( "Failed to serve the server." )]
     ServerServeFailed,
+    /// Failed to load the TLS certificate.
+    #[error( "Failed to load the TLS certificate." )]
+    TlsLoadFailed,
+    /// Failed to load the manifest of the fingerprinted static files.
+    #[error( "Failed to load the asset manifest." )]
+    AssetManifestLoadFailed,
 }
 
 /// Initialize the server.
@@ -38,38 +49,147 @@ pub enum InitServerError {
 /// # Arguments
 ///
 /// * `server_settings` - The server settings [`ServerConfigs`].
-#[tokio::main]
+/// * `security_headers_settings` - The security headers of the responses.
+/// * `configs_receiver` - The settings reloaded when the configuration files change.
 #[instrument( name = "APP", err, skip_all )]
 pub async fn init_server(
     server_settings: settings::ServerConfigs,
-    leptos_options: LeptosOptions,
+    security_headers_settings: settings::SecurityHeadersConfigs,
+    mut leptos_options: LeptosOptions,
+    configs_receiver: settings::ConfigsReceiver,
 ) -> Result<(), Report<InitServerError>> {
     tracing::info!(
-        "Initializing server the with settings: [ sock_addr={}, proxy_url={}, static_dir={}, assets_dir={} ].",
-        server_settings.sock_addr_v4,
+        "Initializing server the with settings: [ listen_addrs={}, proxy_url={}, static_dir={}, assets_dir={} ].",
+        server_settings.listen_addrs,
         server_settings.proxy_url.as_str(),
         server_settings.static_dir,
         server_settings.assets_dir
     );
 
-    let app = app::create(
-        server_settings.static_dir,
-        server_settings.assets_dir,
-        server_settings.proxy_url,
+    // Stop accepting connections on SIGTERM/SIGINT after the pre-stop delay, and drain the in-flight ones until the
+    // drain timeout.
+    let shutdown = shutdown::Shutdown::listen( server_settings.pre_stop_delay.as_std() );
+    let drain_timeout = server_settings.drain_timeout.as_std();
+
+    // Apply the reloaded log level.
+    tokio::spawn( logger::reload_log_level( configs_receiver.clone() ) );
+
+    // The pages link the fingerprinted static files, including the Leptos javascript and wasm files.
+    let asset_manifest = assets::load_manifest( server_settings.static_dir.as_ref() )
+        .change_context( InitServerError::AssetManifestLoadFailed )?;
+    leptos_options.output_name = assets::fingerprinted_output_name( &asset_manifest, &leptos_options.output_name );
+
+    let app = app::create( app::AppContext {
+        settings:         &server_settings,
         leptos_options,
-    )
+        shutdown:         shutdown.clone(),
+        asset_manifest,
+        cors_layer:       cors::create_layer( configs_receiver, |configs| &configs.cors ),
+        security_headers: security_headers::SecurityHeaders::new( &security_headers_settings ),
+    } )
     .await;
 
-    let server = axum::Server::try_bind( &server_settings.sock_addr_v4.into() )
-        .change_context( InitServerError::AddressBindFailed( server_settings.sock_addr_v4.into() ) )?;
+    // Bind all the listen addresses before serving any, so a wrong one fails the start.
+    let mut listeners = Vec::new();
+    for listen_addr in &server_settings.listen_addrs {
+        let listener = listener::Listener::bind( listen_addr )
+            .change_context_lazy( || InitServerError::AddressBindFailed( listen_addr.clone() ) )?;
+        listeners.push( listener );
 
-    tracing::info!( "Server bound to http://{} successfully.", server_settings.sock_addr_v4 );
+        tracing::info!( "Server bound to {listen_addr} successfully." );
+    }
+
+    // The certificate is reloaded as long as the TLS acceptor is kept.
+    let _tls = server_settings
+        .tls
+        .as_ref()
+        .map( |tls_settings| bind_https( tls_settings, &shutdown, &mut listeners ) )
+        .transpose()?;
     tracing::info!( "Serving files..." );
 
-    server
-        .serve( app.into_make_service() )
-        .await
-        .change_context( InitServerError::ServerServeFailed )?;
+    // Dedicated prometheus metrics listener.
+    let metrics_settings = &server_settings.metrics;
+    if let Some( metrics_sock_addr ) = metrics_settings.sock_addr.filter( |_| metrics_settings.is_exposed ) {
+        let metrics_server = axum::Server::try_bind( &metrics_sock_addr )
+            .change_context( InitServerError::AddressBindFailed( metrics_sock_addr.into() ) )?;
+        let metrics_shutdown = shutdown.clone();
+
+        tokio::spawn( async move {
+            let metrics_app = monitoring::prometheus::create_metrics_route::<()>();
+
+            if let Err( err ) = metrics_server
+                .serve( metrics_app.into_make_service() )
+                .with_graceful_shutdown( metrics_shutdown.stop_accepting() )
+                .await
+            {
+                tracing::error!( "Metrics server failed: {err}" );
+            }
+        } );
+
+        tracing::info!( "Metrics server bound to http://{metrics_sock_addr} successfully." );
+    }
+
+    let is_h2c_enabled = server_settings.is_h2c_enabled;
+    let servers =
+        listeners.into_iter().map( |listener| listener.serve( app.clone(), shutdown.clone(), is_h2c_enabled ) );
+    let server = futures::future::try_join_all( servers );
+
+    let is_drained = tokio::select! {
+        result = server => {
+            result.change_context( InitServerError::ServerServeFailed )?;
+            true
+        }
+        () = shutdown.drain_deadline( drain_timeout ) => false,
+    };
+
+    if is_drained {
+        tracing::info!( "Server shut down gracefully." );
+    } else {
+        tracing::warn!(
+            "Server shut down after the drain timeout of {drain_timeout:?}, dropping the remaining connections."
+        );
+    }
 
     Ok( () )
 }
+
+/// Binds the HTTPS listeners, and the plain HTTP listener redirecting to them if any.
+fn bind_https(
+    tls_settings: &settings::TlsSubconfig,
+    shutdown: &shutdown::Shutdown,
+    listeners: &mut Vec<listener::Listener>,
+) -> Result<tls::Tls, Report<InitServerError>> {
+    let tls = tls::Tls::new( tls_settings ).change_context( InitServerError::TlsLoadFailed )?;
+
+    // The port the first HTTPS listener was bound to, the system choosing it for a port 0.
+    let mut https_port = None;
+    for sock_addr in &tls_settings.sock_addrs {
+        let listener = listener::Listener::bind_tls( sock_addr, tls.acceptor() )
+            .change_context_lazy( || InitServerError::AddressBindFailed( ( *sock_addr ).into() ) )?;
+        https_port = https_port.or_else( || listener.local_addr().map( |local_addr| local_addr.port() ) );
+        listeners.push( listener );
+
+        tracing::info!( "Server bound to https://{sock_addr} successfully." );
+    }
+
+    if let ( Some( redirect_sock_addr ), Some( https_port ) ) = ( tls_settings.redirect_sock_addr, https_port ) {
+        let redirect_server = axum::Server::try_bind( &redirect_sock_addr )
+            .change_context( InitServerError::AddressBindFailed( redirect_sock_addr.into() ) )?;
+        let redirect_app = tls::create_redirect_route( https_port );
+        let redirect_shutdown = shutdown.clone();
+
+        tokio::spawn( async move {
+            if let Err( err ) = redirect_server
+                .serve( redirect_app.into_make_service() )
+                .with_graceful_shutdown( redirect_shutdown.stop_accepting() )
+                .await
+            {
+                tracing::error!( "HTTPS redirect server failed: {err}" );
+            }
+        } );
+
+        tracing::info!( "HTTPS redirect server bound to http://{redirect_sock_addr} successfully." );
+    }
+
+    Ok( tls )
+}
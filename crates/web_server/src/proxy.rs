@@ -0,0 +1,469 @@
+//! Reverse proxy of the API requests to the backend.
+//!
+//! Connections to the backend are pooled and kept alive. Hop-by-hop headers are stripped in both directions and the
+//! `X-Forwarded-For`, `X-Forwarded-Proto` and `X-Forwarded-Host` headers of the proxied requests are set from the
+//! connection, replacing the ones sent by the client. A backend that can't be reached is answered with
+//! `502 Bad Gateway`, and one that takes too long with `504 Gateway Timeout`. The response bodies are streamed, so one
+//! that stalls or grows over the limit once the response has started is aborted instead.
+//!
+//! When the backend runs on the same host, it can be reached through its Unix domain socket instead of TCP, see
+//! [`settings::ProxySubconfig::unix_socket`].
+
+use std::{
+    error::Error as StdError,
+    io,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, OriginalUri, State},
+    http::{self, header, uri::Scheme, HeaderMap, HeaderName, HeaderValue, Request, Uri},
+    response::{IntoResponse, Response},
+    Extension, Json, Router,
+};
+use futures::{future::BoxFuture, StreamExt};
+use hyper::client::{
+    connect::{Connected, Connection},
+    HttpConnector,
+};
+use monitoring::logger::REQUEST_ID_HEADER;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpStream, UnixStream},
+};
+use url::Url;
+
+use crate::settings;
+
+/// Headers meaningful only for a single connection, which proxies must not forward ( RFC 9110 section 7.6.1 ).
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Interval of the TCP keep-alive probes on the connections to the backend.
+const TCP_KEEPALIVE: Duration = Duration::from_secs( 60 );
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+const X_FORWARDED_HOST: &str = "x-forwarded-host";
+
+/// Client with a pool of kept alive connections to the backend.
+pub type Client = hyper::client::Client<BackendConnector, Body>;
+
+/// Connects to the backend over TCP, or over its Unix domain socket.
+#[derive(Debug, Clone)]
+pub enum BackendConnector {
+    Tcp( HttpConnector ),
+    Unix {
+        path:            PathBuf,
+        connect_timeout: Duration,
+    },
+}
+
+impl tower::Service<Uri> for BackendConnector {
+    type Error = Box<dyn StdError + Send + Sync>;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+    type Response = BackendStream;
+
+    fn poll_ready( &mut self, cx: &mut Context<'_> ) -> Poll<Result<(), Self::Error>> {
+        match self {
+            Self::Tcp( connector ) => connector.poll_ready( cx ).map_err( Into::into ),
+            Self::Unix { .. } => Poll::Ready( Ok( () ) ),
+        }
+    }
+
+    fn call( &mut self, uri: Uri ) -> Self::Future {
+        match self {
+            Self::Tcp( connector ) => {
+                let connecting = connector.call( uri );
+                Box::pin( async move { Ok( BackendStream::Tcp( connecting.await? ) ) } )
+            }
+            Self::Unix { path, connect_timeout } => {
+                let path = path.clone();
+                let connect_timeout = *connect_timeout;
+
+                // The timeout is an io error, like the one of the TCP connector, so it is answered the same way.
+                Box::pin( async move {
+                    let stream = tokio::time::timeout( connect_timeout, UnixStream::connect( path ) )
+                        .await
+                        .map_err( |_| io::Error::from( io::ErrorKind::TimedOut ) )??;
+                    Ok( BackendStream::Unix( stream ) )
+                } )
+            }
+        }
+    }
+}
+
+/// A connection to the backend, see [`BackendConnector`].
+#[derive(Debug)]
+pub enum BackendStream {
+    Tcp( TcpStream ),
+    Unix( UnixStream ),
+}
+
+impl Connection for BackendStream {
+    fn connected( &self ) -> Connected {
+        match self {
+            Self::Tcp( stream ) => stream.connected(),
+            Self::Unix( _ ) => Connected::new(),
+        }
+    }
+}
+
+impl AsyncRead for BackendStream {
+    fn poll_read( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_> ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_read( cx, buf ),
+            Self::Unix( stream ) => Pin::new( stream ).poll_read( cx, buf ),
+        }
+    }
+}
+
+impl AsyncWrite for BackendStream {
+    fn poll_write( self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8] ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_write( cx, buf ),
+            Self::Unix( stream ) => Pin::new( stream ).poll_write( cx, buf ),
+        }
+    }
+
+    fn poll_flush( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_flush( cx ),
+            Self::Unix( stream ) => Pin::new( stream ).poll_flush( cx ),
+        }
+    }
+
+    fn poll_shutdown( self: Pin<&mut Self>, cx: &mut Context<'_> ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Tcp( stream ) => Pin::new( stream ).poll_shutdown( cx ),
+            Self::Unix( stream ) => Pin::new( stream ).poll_shutdown( cx ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProxyState {
+    pub client:             Client,
+    pub api_url:            Url,
+    pub connect_timeout:    Duration,
+    pub read_timeout:       Duration,
+    pub max_body_bytes:     u64,
+    pub max_response_bytes: u64,
+}
+
+impl ProxyState {
+    pub fn new( api_url: Url, proxy_settings: &settings::ProxySubconfig ) -> Self {
+        let connect_timeout = proxy_settings.connect_timeout.as_std();
+
+        let connector = match &proxy_settings.unix_socket {
+            Some( path ) => BackendConnector::Unix {
+                path: path.clone(),
+                connect_timeout,
+            },
+            None => {
+                let mut connector = HttpConnector::new();
+                connector.set_connect_timeout( Some( connect_timeout ) );
+                connector.set_keepalive( Some( TCP_KEEPALIVE ) );
+                connector.set_nodelay( true );
+                BackendConnector::Tcp( connector )
+            }
+        };
+
+        let client = hyper::Client::builder()
+            .pool_idle_timeout( proxy_settings.pool_idle_timeout.as_std() )
+            .pool_max_idle_per_host( proxy_settings.pool_max_idle_per_host )
+            .build( connector );
+
+        Self {
+            client,
+            api_url,
+            connect_timeout,
+            read_timeout:       proxy_settings.read_timeout.as_std(),
+            max_body_bytes:     proxy_settings.max_body_size.as_u64(),
+            max_response_bytes: proxy_settings.max_response_size.as_u64(),
+        }
+    }
+
+    /// Gets a resource of the backend, e.g. for the server functions, with the timeouts and body limit of the proxy.
+    /// The whole response, body included, must be read within the read timeout. The id of the request being handled
+    /// is forwarded, as the proxied requests do, so the logs of both can be correlated.
+    ///
+    /// # Errors
+    ///
+    /// If the backend can't be reached, takes too long or answers with a body larger than the limit.
+    pub async fn get( &self, path_query: &str, request_id: Option<&str> ) -> Result<Response<Bytes>, Error> {
+        let uri = Uri::builder()
+            .scheme( self.api_url.scheme() )
+            .authority( self.api_url.authority() )
+            .path_and_query( path_query )
+            .build()?;
+        let mut req = Request::get( uri ).body( Body::empty() )?;
+        if let Some( request_id ) = request_id.and_then( |request_id| HeaderValue::from_str( request_id ).ok() ) {
+            req.headers_mut().insert( REQUEST_ID_HEADER, request_id );
+        }
+        crate::logger::inject_trace_context( req.headers_mut() );
+
+        let deadline = tokio::time::Instant::now() + self.read_timeout;
+        let response = match tokio::time::timeout_at( deadline, self.client.request( req ) ).await {
+            Ok( Ok( response ) ) => response,
+            Ok( Err( err ) ) => return Err( classify_client_error( err, self ) ),
+            Err( _ ) => return Err( Error::BackendTimeout( self.read_timeout ) ),
+        };
+
+        let ( parts, body ) = response.into_parts();
+        let body = match tokio::time::timeout_at(
+            deadline,
+            hyper::body::to_bytes( limit_body( body, self.max_body_bytes, Error::ResponseTooLarge ) ),
+        )
+        .await
+        {
+            Ok( Ok( body ) ) => body,
+            Ok( Err( err ) ) => return Err( classify_client_error( err, self ) ),
+            Err( _ ) => return Err( Error::BackendTimeout( self.read_timeout ) ),
+        };
+
+        Ok( Response::from_parts( parts, body ) )
+    }
+}
+
+pub fn create_route( state: ProxyState ) -> Router {
+    Router::new().fallback( api_reverse_proxy_handler ).with_state( state )
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error( "Failed to build the backend uri: {0}" )]
+    InvalidUri( #[from] http::Error ),
+    #[error( "The request body is larger than the limit of {0} bytes." )]
+    BodyTooLarge( u64 ),
+    #[error( "The backend response body is larger than the limit of {0} bytes." )]
+    ResponseTooLarge( u64 ),
+    #[error( "Failed to reach the backend: {0}" )]
+    BackendUnreachable( #[from] hyper::Error ),
+    #[error( "The backend took more than {0:?} to connect or respond." )]
+    BackendTimeout( Duration ),
+}
+
+impl IntoResponse for Error {
+    fn into_response( self ) -> Response {
+        let status = match self {
+            Self::InvalidUri( _ ) | Self::BackendUnreachable( _ ) | Self::ResponseTooLarge( _ ) => {
+                http::StatusCode::BAD_GATEWAY
+            }
+            Self::BodyTooLarge( _ ) => http::StatusCode::PAYLOAD_TOO_LARGE,
+            Self::BackendTimeout( _ ) => http::StatusCode::GATEWAY_TIMEOUT,
+        };
+
+        if status.is_server_error() {
+            tracing::error!( "{self}" );
+        } else {
+            tracing::warn!( "{self}" );
+        }
+
+        let response_body = common::api::ErrorResponseBody {
+            message: status.canonical_reason().unwrap_or_default().to_string(),
+        };
+
+        ( status, Json( response_body ) ).into_response()
+    }
+}
+
+/// Reverse proxy requests to the API on the backend.
+///
+/// The request id header set by the tracing middleware is forwarded with the other headers, and the trace context of
+/// the current span is injected so the backend continues the same trace. The response body is streamed to the client,
+/// within the read timeout between two chunks and the response size limit.
+async fn api_reverse_proxy_handler(
+    State( state ): State<ProxyState>,
+    OriginalUri( original_uri ): OriginalUri,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    scheme: Option<Extension<Scheme>>,
+    mut req: Request<Body>,
+) -> Result<Response, Error> {
+    let content_length = req
+        .headers()
+        .get( header::CONTENT_LENGTH )
+        .and_then( |value| value.to_str().ok() )
+        .and_then( |value| value.parse::<u64>().ok() );
+    if content_length.is_some_and( |length| length > state.max_body_bytes ) {
+        return Err( Error::BodyTooLarge( state.max_body_bytes ) );
+    }
+
+    // The original uri keeps the `/api` prefix stripped by the nested router, which the backend routes expect.
+    let path_query = original_uri
+        .path_and_query()
+        .map_or_else( || original_uri.path(), |v| v.as_str() );
+
+    *req.uri_mut() = Uri::builder()
+        .scheme( state.api_url.scheme() )
+        .authority( state.api_url.authority() )
+        .path_and_query( path_query )
+        .build()?;
+
+    // The client sets the host of the backend, the original one is kept in the forwarding headers.
+    let host = req.headers_mut().remove( header::HOST );
+    remove_hop_by_hop_headers( req.headers_mut() );
+    let client_ip = connect_info.map( |ConnectInfo( addr )| addr.ip() );
+    let scheme = scheme.map_or( Scheme::HTTP, |Extension( scheme )| scheme );
+    add_forwarding_headers( req.headers_mut(), client_ip, &scheme, host );
+    crate::logger::inject_trace_context( req.headers_mut() );
+
+    let max_body_bytes = state.max_body_bytes;
+    let req = req.map( |body| limit_body( body, max_body_bytes, Error::BodyTooLarge ) );
+
+    let mut response = match tokio::time::timeout( state.read_timeout, state.client.request( req ) ).await {
+        Ok( Ok( response ) ) => response,
+        Ok( Err( err ) ) => return Err( classify_client_error( err, &state ) ),
+        Err( _ ) => return Err( Error::BackendTimeout( state.read_timeout ) ),
+    };
+
+    let content_length = response
+        .headers()
+        .get( header::CONTENT_LENGTH )
+        .and_then( |value| value.to_str().ok() )
+        .and_then( |value| value.parse::<u64>().ok() );
+    if content_length.is_some_and( |length| length > state.max_response_bytes ) {
+        return Err( Error::ResponseTooLarge( state.max_response_bytes ) );
+    }
+
+    remove_hop_by_hop_headers( response.headers_mut() );
+    let response = response.map( |body| stream_response_body( body, state.max_response_bytes, state.read_timeout ) );
+
+    Ok( response.into_response() )
+}
+
+/// Removes the hop-by-hop headers, including the ones listed in the `Connection` header.
+fn remove_hop_by_hop_headers( headers: &mut HeaderMap ) {
+    let connection_headers: Vec<HeaderName> = headers
+        .get_all( header::CONNECTION )
+        .iter()
+        .filter_map( |value| value.to_str().ok() )
+        .flat_map( |value| value.split( ',' ) )
+        .filter_map( |name| HeaderName::from_bytes( name.trim().as_bytes() ).ok() )
+        .collect();
+
+    for name in connection_headers {
+        headers.remove( name );
+    }
+
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove( name );
+    }
+}
+
+/// Sets `X-Forwarded-For`, `X-Forwarded-Proto` and `X-Forwarded-Host` from the connection, replacing the ones sent by
+/// the client. This server faces the clients, so the values they send can't be trusted.
+fn add_forwarding_headers(
+    headers: &mut HeaderMap,
+    client_ip: Option<IpAddr>,
+    scheme: &Scheme,
+    host: Option<HeaderValue>,
+) {
+    headers.remove( X_FORWARDED_FOR );
+    headers.remove( X_FORWARDED_PROTO );
+    headers.remove( X_FORWARDED_HOST );
+
+    // No client address over a Unix domain socket.
+    if let Some( client_ip ) = client_ip {
+        if let Ok( forwarded_for ) = HeaderValue::from_str( &client_ip.to_string() ) {
+            headers.insert( X_FORWARDED_FOR, forwarded_for );
+        }
+    }
+
+    if let Ok( scheme ) = HeaderValue::from_str( scheme.as_str() ) {
+        headers.insert( X_FORWARDED_PROTO, scheme );
+    }
+
+    if let Some( host ) = host {
+        headers.insert( X_FORWARDED_HOST, host );
+    }
+}
+
+/// Streams the body, failing with `too_large` once more than `max_bytes` went through. This also covers bodies sent
+/// without a `Content-Length` header.
+fn limit_body( body: Body, max_bytes: u64, too_large: fn( u64 ) -> Error ) -> Body {
+    let mut received_bytes = 0;
+
+    Body::wrap_stream( body.map( move |chunk| -> Result<Bytes, Box<dyn StdError + Send + Sync>> {
+        let chunk = chunk?;
+        received_bytes += chunk.len() as u64;
+
+        if received_bytes > max_bytes {
+            return Err( Box::new( too_large( max_bytes ) ) );
+        }
+
+        Ok( chunk )
+    } ) )
+}
+
+/// Streams the response body of the backend, failing once more than `max_bytes` went through or when the backend
+/// sends nothing for `read_timeout`. The response has started, so a failure aborts it for the client not to take a
+/// truncated body for a whole one.
+fn stream_response_body( body: Body, max_bytes: u64, read_timeout: Duration ) -> Body {
+    let chunks = futures::stream::unfold( Some( ( body, 0 ) ), move |state| async move {
+        let ( mut body, mut received_bytes ) = state?;
+
+        let chunk = match tokio::time::timeout( read_timeout, body.next() ).await {
+            Ok( None ) => return None,
+            Ok( Some( Ok( chunk ) ) ) => {
+                received_bytes += chunk.len() as u64;
+                if received_bytes > max_bytes {
+                    Err( Error::ResponseTooLarge( max_bytes ) )
+                } else {
+                    Ok( chunk )
+                }
+            }
+            Ok( Some( Err( err ) ) ) => Err( Error::BackendUnreachable( err ) ),
+            Err( _ ) => Err( Error::BackendTimeout( read_timeout ) ),
+        };
+
+        match chunk {
+            Ok( chunk ) => Some( ( Ok( chunk ), Some( ( body, received_bytes ) ) ) ),
+            Err( err ) => {
+                tracing::error!( "Aborted the response of the backend: {err}" );
+                Some( ( Err( err ), None ) )
+            }
+        }
+    } );
+
+    Body::wrap_stream( chunks )
+}
+
+/// Finds out why the client failed from the error sources: an oversized body, a connection timeout or any other
+/// failure to reach the backend.
+fn classify_client_error( err: hyper::Error, state: &ProxyState ) -> Error {
+    let mut source = err.source();
+
+    while let Some( cause ) = source {
+        match cause.downcast_ref::<Error>() {
+            Some( Error::BodyTooLarge( max_bytes ) ) => return Error::BodyTooLarge( *max_bytes ),
+            Some( Error::ResponseTooLarge( max_bytes ) ) => return Error::ResponseTooLarge( *max_bytes ),
+            _ => {}
+        }
+
+        if cause
+            .downcast_ref::<io::Error>()
+            .is_some_and( |io_err| io_err.kind() == io::ErrorKind::TimedOut )
+        {
+            return Error::BackendTimeout( state.connect_timeout );
+        }
+
+        source = cause.source();
+    }
+
+    Error::BackendUnreachable( err )
+}
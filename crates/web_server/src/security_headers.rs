@@ -0,0 +1,266 @@
+//! Security headers of the responses: `Content-Security-Policy`, `Strict-Transport-Security`, `X-Frame-Options`,
+//! `X-Content-Type-Options`, `Referrer-Policy` and `Permissions-Policy`.
+//!
+//! The pages rendered by Leptos send a policy allowing the nonce of the request, which Leptos adds to its hydration
+//! scripts and the components to their inline styles. The other responses get the policy without nonce. In report-only
+//! mode the policy is sent as `Content-Security-Policy-Report-Only`, and the browsers report the violations to
+//! [`CSP_REPORT_PATH`], where they are logged.
+//!
+//! The endpoint is public, so it only accepts the content types of the reports, small bodies, and a limited number of
+//! reports per second, to keep it from flooding the logs.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, FromRequest, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Request, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+
+use crate::settings;
+
+/// Path of the endpoint collecting the violations of the Content-Security-Policy.
+pub const CSP_REPORT_PATH: &str = "/csp-report";
+
+/// Name of the endpoint of the `report-to` directive, declared by the `Reporting-Endpoints` header.
+const CSP_REPORT_ENDPOINT: &str = "csp-endpoint";
+
+/// Directives whose sources include the nonce of the page.
+const NONCE_DIRECTIVES: [&str; 2] = ["script-src", "style-src"];
+
+/// Maximum size of a violation report, which is usually below 1 KiB.
+const MAX_REPORT_BYTES: usize = 8 * 1024;
+
+/// Maximum number of violation reports logged per second, from all the clients.
+const MAX_REPORTS_PER_SECOND: u32 = 10;
+
+/// Content types of the reports, sent by `report-uri` and by `report-to`.
+const REPORT_CONTENT_TYPES: [&str; 2] = ["application/csp-report", "application/reports+json"];
+
+/// The security headers added to the responses.
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    csp:     Option<ContentSecurityPolicy>,
+    headers: HeaderMap,
+}
+
+impl SecurityHeaders {
+    /// Creates the headers from the settings, whose values are validated when they are loaded.
+    #[must_use]
+    pub fn new( security_headers_settings: &settings::SecurityHeadersConfigs ) -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert( header::X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static( "nosniff" ) );
+        headers.insert(
+            header::X_FRAME_OPTIONS,
+            HeaderValue::from_static( security_headers_settings.frame_options.as_header_value() ),
+        );
+        insert_header( &mut headers, header::REFERRER_POLICY, &security_headers_settings.referrer_policy );
+        insert_header(
+            &mut headers,
+            HeaderName::from_static( "permissions-policy" ),
+            &security_headers_settings.permissions_policy,
+        );
+
+        if let Some( hsts_max_age ) = security_headers_settings.hsts_max_age {
+            let mut hsts = format!( "max-age={}", hsts_max_age.as_std().as_secs() );
+            if security_headers_settings.is_hsts_including_subdomains {
+                hsts.push_str( "; includeSubDomains" );
+            }
+            insert_header( &mut headers, header::STRICT_TRANSPORT_SECURITY, &hsts );
+        }
+
+        let csp_header_name = match security_headers_settings.csp_mode {
+            settings::CspMode::Enforced => Some( header::CONTENT_SECURITY_POLICY ),
+            settings::CspMode::ReportOnly => Some( header::CONTENT_SECURITY_POLICY_REPORT_ONLY ),
+            settings::CspMode::Disabled => None,
+        };
+        let csp =
+            csp_header_name.map( |header_name| ContentSecurityPolicy::new( header_name, security_headers_settings ) );
+        if csp.as_ref().is_some_and( |csp| csp.is_reported ) {
+            insert_header(
+                &mut headers,
+                HeaderName::from_static( "reporting-endpoints" ),
+                &format!( "{CSP_REPORT_ENDPOINT}=\"{CSP_REPORT_PATH}\"" ),
+            );
+        }
+
+        Self { csp, headers }
+    }
+
+    /// The Content-Security-Policy, if it is enabled.
+    #[must_use]
+    pub const fn content_security_policy( &self ) -> Option<&ContentSecurityPolicy> { self.csp.as_ref() }
+}
+
+/// The Content-Security-Policy, enforced or only reported.
+#[derive(Debug, Clone)]
+pub struct ContentSecurityPolicy {
+    header_name:  HeaderName,
+    directives:   BTreeMap<String, Vec<String>>,
+    is_reported:  bool,
+    /// The policy of the responses without nonce.
+    header_value: Option<HeaderValue>,
+}
+
+impl ContentSecurityPolicy {
+    fn new( header_name: HeaderName, security_headers_settings: &settings::SecurityHeadersConfigs ) -> Self {
+        let mut csp = Self {
+            header_name,
+            directives: security_headers_settings.csp_directives.clone(),
+            is_reported: security_headers_settings.is_csp_reported,
+            header_value: None,
+        };
+        csp.header_value = HeaderValue::from_str( &csp.to_policy( None ) ).ok();
+
+        csp
+    }
+
+    /// `Content-Security-Policy`, or `Content-Security-Policy-Report-Only` in report-only mode.
+    #[must_use]
+    pub const fn header_name( &self ) -> &HeaderName { &self.header_name }
+
+    /// Whether the violations are reported to [`CSP_REPORT_PATH`].
+    #[must_use]
+    pub const fn is_reported( &self ) -> bool { self.is_reported }
+
+    /// The policy of a page, allowing its inline scripts and styles with the `nonce`.
+    #[must_use]
+    pub fn header_value_with_nonce( &self, nonce: &str ) -> Option<HeaderValue> {
+        HeaderValue::from_str( &self.to_policy( Some( nonce ) ) ).ok()
+    }
+
+    fn to_policy( &self, nonce: Option<&str> ) -> String {
+        let nonce_source = nonce.map( |nonce| format!( "'nonce-{nonce}'" ) );
+        let mut directives = Vec::with_capacity( self.directives.len() + 2 );
+        for ( directive, sources ) in &self.directives {
+            let nonce_source = nonce_source.as_deref().filter( |_| NONCE_DIRECTIVES.contains( &directive.as_str() ) );
+            let directive = std::iter::once( directive.as_str() )
+                .chain( sources.iter().map( String::as_str ) )
+                .chain( nonce_source )
+                .collect::<Vec<_>>();
+            directives.push( directive.join( " " ) );
+        }
+
+        // `report-uri` is deprecated for `report-to`, but the browsers without the Reporting API only support it.
+        if self.is_reported {
+            directives.push( format!( "report-uri {CSP_REPORT_PATH}" ) );
+            directives.push( format!( "report-to {CSP_REPORT_ENDPOINT}" ) );
+        }
+
+        directives.join( "; " )
+    }
+}
+
+/// Inserts the header unless its value is empty.
+fn insert_header( headers: &mut HeaderMap, name: HeaderName, value: &str ) {
+    if let Some( value ) = Some( value ).filter( |value| !value.is_empty() ).and_then( |value| value.parse().ok() ) {
+        headers.insert( name, value );
+    }
+}
+
+/// Adds the security headers to the responses of the `router`. The headers already set are kept, such as the policy of
+/// the pages rendered with a nonce.
+pub fn middleware_security_headers<T>( router: Router<T>, security_headers: SecurityHeaders ) -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    router.layer( middleware::map_response_with_state( security_headers, add_security_headers ) )
+}
+
+async fn add_security_headers<B>(
+    State( security_headers ): State<SecurityHeaders>,
+    mut response: Response<B>,
+) -> Response<B> {
+    let headers = response.headers_mut();
+    for ( name, value ) in &security_headers.headers {
+        headers.entry( name ).or_insert_with( || value.clone() );
+    }
+
+    if let Some( csp ) = &security_headers.csp {
+        if let Some( header_value ) = &csp.header_value {
+            headers.entry( &csp.header_name ).or_insert_with( || header_value.clone() );
+        }
+    }
+
+    response
+}
+
+/// Creates the route collecting the violation reports of the Content-Security-Policy, which are logged.
+pub fn create_csp_report_route<T>() -> Router<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route( CSP_REPORT_PATH, post( log_csp_report ) )
+        .layer( DefaultBodyLimit::max( MAX_REPORT_BYTES ) )
+        .with_state( Arc::new( ReportRateLimit::default() ) )
+}
+
+/// Counts the reports of the current one second window, shared by all the clients.
+#[derive(Debug)]
+struct ReportRateLimit {
+    window: Mutex<( Instant, u32 )>,
+}
+
+impl Default for ReportRateLimit {
+    fn default() -> Self { Self { window: Mutex::new( ( Instant::now(), 0 ) ) } }
+}
+
+impl ReportRateLimit {
+    /// Counts a report, or returns `false` if the limit of the current window is reached.
+    fn try_acquire( &self ) -> bool {
+        let mut window = self.window.lock().unwrap_or_else( PoisonError::into_inner );
+        let now = Instant::now();
+        if now.duration_since( window.0 ) >= Duration::from_secs( 1 ) {
+            *window = ( now, 0 );
+        }
+        if window.1 >= MAX_REPORTS_PER_SECOND {
+            return false;
+        }
+
+        window.1 += 1;
+        true
+    }
+}
+
+/// Logs a report, sent by `report-uri` as `application/csp-report` or by `report-to` as `application/reports+json`.
+///
+/// The content type and the rate limit are checked before the body is read.
+async fn log_csp_report( State( rate_limit ): State<Arc<ReportRateLimit>>, request: Request<Body> ) -> Response {
+    if !is_report_content_type( request.headers() ) {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    }
+    if !rate_limit.try_acquire() {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    let body = match Bytes::from_request( request, &() ).await {
+        Ok( body ) => body,
+        Err( rejection ) => return rejection.into_response(),
+    };
+    match serde_json::from_slice::<serde_json::Value>( &body ) {
+        Ok( report ) => tracing::warn!( "Content-Security-Policy violation: {report}" ),
+        Err( err ) => tracing::debug!( "Invalid Content-Security-Policy report: {err}" ),
+    }
+
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Whether the media type of the request, without its parameters, is one of the [`REPORT_CONTENT_TYPES`].
+fn is_report_content_type( headers: &HeaderMap ) -> bool {
+    headers
+        .get( header::CONTENT_TYPE )
+        .and_then( |content_type| content_type.to_str().ok() )
+        .and_then( |content_type| content_type.split( ';' ).next() )
+        .is_some_and( |media_type| {
+            REPORT_CONTENT_TYPES.iter().any( |report_type| media_type.trim().eq_ignore_ascii_case( report_type ) )
+        } )
+}
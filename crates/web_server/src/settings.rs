@@ -1,32 +1,35 @@
 //! The settings module.
 //!
 //! This module is responsible for importing the settings from the configuration files, the environment variables and the command line arguments. The latter overwrites the former.
-#![allow( unused )]
 
 use std::{
+    collections::BTreeMap,
     env,
-    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
-    ops::Deref,
+    net::{Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::{Arc, Mutex, OnceLock},
+    sync::Arc,
 };
 
 use axum::http;
-use clap::{Args, Parser};
-use error_stack::{FutureExt, Report, ResultExt};
-use futures::TryFutureExt;
-use hyper::Uri;
+use clap::{Parser, Subcommand};
+use error_stack::{Report, ResultExt};
+use monitoring::rolling_file::Rotation;
 use serde::{Deserialize, Serialize};
-pub use settings::get_configs_dir_path;
-use settings::{validators, FigmentExtractor, RuntimeEnvironment};
+pub use settings::{
+    configs::{CliArgsCors, CompressionSubconfig, CorsConfigs, LoggerTracesExportedSubconfig, TlsSubconfig},
+    get_configs_dir_path, Secret,
+};
+use settings::{
+    validators, ConfigReport, ConfigWatcher, LayeredSettings, Layers, Profiles, RuntimeEnvironment, WatchError,
+};
 use thiserror::Error;
 use url::Url;
 
 use crate::logger;
 
 /// Command line arguments interface.
-#[derive(Parser, Debug, Serialize, Deserialize)]
+#[derive(Parser, Debug, Clone, Serialize, Deserialize)]
 pub struct CliArgs {
     /// Configs directory.
     /// The directory where the configuration files are located. There is also the env variable FRONTEND_CONFIGS_DIR.
@@ -41,6 +44,20 @@ pub struct CliArgs {
     #[serde( skip_serializing_if = "Option::is_none" )]
     pub env_prefix: Option<String>,
 
+    /// Secrets directory.
+    /// The directory with the secret files, named like the environment variables in lowercase, e.g.
+    /// frontend_server_admin_token. There is also the env variable FRONTEND_SECRETS_DIR.
+    /// Default: /run/secrets/
+    #[arg( long = "secrets-dir", value_parser )]
+    #[serde( skip_serializing_if = "Option::is_none" )]
+    pub secrets_dir: Option<PathBuf>,
+
+    /// Interactive.
+    /// Ask for a new path when a directory in the settings is invalid, instead of failing. Requires a terminal.
+    #[arg( long = "interactive" )]
+    #[serde( skip )]
+    pub is_interactive: bool,
+
     #[command( flatten )]
     pub general: CliArgsGeneral,
 
@@ -49,53 +66,31 @@ pub struct CliArgs {
 
     #[command( flatten )]
     pub logger: CliArgsLogger,
-}
-
-#[derive(Args, Debug, Serialize, Deserialize)]
-pub struct CliArgsGeneral {
-    /// Set runtime environment.
-    #[arg(short = 'e', long = "run-env", value_parser = ["development", "production"])]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    run_env: Option<String>,
-}
-
-#[derive(Args, Debug, Serialize, Deserialize)]
-pub struct CliArgsServer {
-    /// Set the listen addr.
-    #[arg( short = 'a', long = "addr", value_parser )]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    addr: Option<Ipv4Addr>,
-
-    /// Set the listen port.
-    #[arg( short = 'p', long = "port", value_parser = clap::value_parser! (u16).range(1024..) )]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    port: Option<u16>,
 
-    /// Set the proxy url for calls to the backend.
-    /// Example: http://localhost:5555
-    #[arg( long = "proxy-url", value_parser )]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    proxy_url: Option<String>,
+    #[command( flatten )]
+    pub cors: CliArgsCors,
 
-    /// Set the static files directory.
-    #[arg( short = 's', long = "static-dir", value_parser )]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    static_dir: Option<PathBuf>,
+    #[command( flatten )]
+    pub security_headers: CliArgsSecurityHeaders,
 
-    /// Set the assets files directory.
-    #[arg( long = "assets-dir", value_parser )]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    assets_dir: Option<PathBuf>,
+    /// Command to run instead of starting the server.
+    #[command( subcommand )]
+    #[serde( skip )]
+    pub command: Option<Command>,
 }
 
-#[derive(Args, Debug, Serialize, Deserialize)]
-pub struct CliArgsLogger {
-    /// Set the log level.
-    #[arg( short = 'l', long = "log-level", value_parser = ["trace", "debug", "info", "warn", "error"])]
-    #[serde( skip_serializing_if = "Option::is_none" )]
-    log_level: Option<String>,
+/// Commands that run instead of starting the server.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Validate the settings and exit, with a non-zero code if they are invalid.
+    CheckConfig,
+    /// Print the merged settings as TOML, with the secrets masked and the origin of each value.
+    PrintConfig,
 }
 
+/// Directory of the secret files when it is not set, where Docker mounts the secrets.
+const DEFAULT_SECRETS_DIR: &str = "/run/secrets/";
+
 /// Error type for the [`init`] function.
 #[derive(Error, Debug)]
 #[error( "Failed to import the configs of {0}." )]
@@ -119,55 +114,145 @@ pub fn init(
     env_prefix: &str,
     cli_args: &CliArgs,
 ) -> Result<AllConfigs, Report<InitImportConfigError>> {
-    // General settings
-    let mut general_configs = GeneralConfigs::extract()
-        .file( configs_dir.join( "general.toml" ) )
-        .env_prefix( &[env_prefix, "_GENERAL_"].concat() )
-        .cli( &cli_args.general )
-        .call()
-        .change_context( InitImportConfigError( "GENERAL" ) )?;
-
-    // Get runtime environment from general settings.
-    let runtime_env: RuntimeEnvironment = env::var( [env_prefix, "_GENERAL_RUN_ENV"].concat() ).map_or_else(
-        |_| general_configs.run_env.clone(),
-        |env| RuntimeEnvironment::from( &*env ),
-    );
-
-    // Server settings.
-    let server_configs = ServerConfigs::extract()
-        .env( &runtime_env )
-        .env_prefix( &[env_prefix, "_SERVER_"].concat() )
-        .file( configs_dir.join( "server.toml" ) )
-        .cli( &cli_args.server )
-        .call()
-        .change_context( InitImportConfigError( "SERVER" ) )?;
-
-    // Logger settings.
-    let logger_configs = LoggerConfigs::extract()
-        .env( &runtime_env )
-        .env_prefix( &[env_prefix, "_LOGGER_"].concat() )
-        .file( configs_dir.join( "logger.toml" ) )
-        .cli( &cli_args.logger )
-        .call()
-        .change_context( InitImportConfigError( "SERVER" ) )?;
+    // Secret files directory.
+    let secrets_dir = cli_args
+        .secrets_dir
+        .clone()
+        .or_else( || env::var_os( [env_prefix, "_SECRETS_DIR"].concat() ).map( PathBuf::from ) )
+        .unwrap_or_else( || PathBuf::from( DEFAULT_SECRETS_DIR ) );
+
+    let profiles = Profiles::load( configs_dir ).change_context( InitImportConfigError( "profiles" ) )?;
+
+    let mut report = ConfigReport::default();
+    let mut layers = Layers {
+        configs_dir,
+        env_prefix,
+        secrets_dir: &secrets_dir,
+        profiles: &profiles,
+        run_env: None,
+    };
+
+    // The general settings select the runtime environment of the other ones, which must be a known profile.
+    let general = load::<GeneralConfigs>( &layers, &cli_args.general, &mut report )?;
+    profiles
+        .resolve( &general.run_env )
+        .change_context( InitImportConfigError( GeneralConfigs::SECTION ) )?;
+    layers.run_env = Some( &general.run_env );
+
+    let server = load::<ServerConfigs>( &layers, &cli_args.server, &mut report )?;
+    server.compression.validate().change_context( InitImportConfigError( ServerConfigs::SECTION ) )?;
+    server.cache.validate().change_context( InitImportConfigError( ServerConfigs::SECTION ) )?;
+    let logger = load::<LoggerConfigs>( &layers, &cli_args.logger, &mut report )?;
+    logger.files_emitted.validate().change_context( InitImportConfigError( LoggerConfigs::SECTION ) )?;
+    let cors = load::<CorsConfigs>( &layers, &cli_args.cors, &mut report )?;
+    cors.validate().change_context( InitImportConfigError( CorsConfigs::SECTION ) )?;
+    let security_headers = load::<SecurityHeadersConfigs>( &layers, &cli_args.security_headers, &mut report )?;
+    security_headers
+        .validate()
+        .change_context( InitImportConfigError( SecurityHeadersConfigs::SECTION ) )?;
 
     Ok( AllConfigs {
-        general: general_configs,
-        server:  server_configs,
-        logger:  logger_configs,
+        general,
+        server,
+        logger,
+        cors,
+        security_headers,
+        report,
+    } )
+}
+
+/// Loads a section of the settings and adds the origin of its values to the `report`.
+fn load<S: LayeredSettings>(
+    layers: &Layers<'_>,
+    cli_args: &S::CliArgs,
+    report: &mut ConfigReport,
+) -> Result<S, Report<InitImportConfigError>> {
+    let ( configs, section_report ) =
+        S::load( layers, cli_args ).change_context( InitImportConfigError( S::SECTION ) )?;
+    report.extend( S::SECTION, section_report );
+
+    Ok( configs )
+}
+
+/// Keys of the settings applied when the configuration files change. The other ones need a restart.
+pub const RELOADABLE_KEYS: &[&str] = &["logger.log_level", "cors.allowed_origins"];
+
+/// Receiver of the settings, updated when the configuration files change.
+pub type ConfigsReceiver = tokio::sync::watch::Receiver<Arc<AllConfigs>>;
+
+/// Watches the configuration files and reloads the settings, see [`ConfigWatcher`].
+///
+/// # Errors
+///
+/// If the configs directory can't be watched.
+pub fn watch(
+    configs_dir: &Path,
+    env_prefix: &str,
+    cli_args: CliArgs,
+    configs: AllConfigs,
+) -> Result<ConfigWatcher<AllConfigs>, WatchError> {
+    let reload_configs_dir = configs_dir.to_path_buf();
+    let env_prefix = env_prefix.to_string();
+
+    ConfigWatcher::spawn( configs_dir, configs, RELOADABLE_KEYS, move || {
+        init( &reload_configs_dir, &env_prefix, &cli_args )
     } )
 }
 
 /// All the settings imported.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AllConfigs {
-    pub general: GeneralConfigs,
-    pub server:  ServerConfigs,
-    pub logger:  LoggerConfigs,
+    pub general:          GeneralConfigs,
+    pub server:           ServerConfigs,
+    pub logger:           LoggerConfigs,
+    pub cors:             CorsConfigs,
+    pub security_headers: SecurityHeadersConfigs,
+    /// The origin of each setting value.
+    #[serde( skip )]
+    pub report:           ConfigReport,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Error type for the [`AllConfigs::ensure_dirs`] function.
+#[derive(Error, Debug)]
+#[error( "The directory of the {0} setting is invalid." )]
+pub struct InvalidDirError( &'static str );
+
+impl AllConfigs {
+    /// Validates the directories of the settings, creating the missing writable ones. Loading the settings does not
+    /// access them, so checking and printing the settings have no side effects.
+    ///
+    /// When `is_interactive`, an invalid directory is asked to the user instead of failing, which requires a terminal.
+    ///
+    /// # Errors
+    ///
+    /// If a directory is invalid and no valid one is inserted by the user.
+    pub fn ensure_dirs( &mut self, is_interactive: bool ) -> Result<(), Report<InvalidDirError>> {
+        self.server
+            .static_dir
+            .ensure( is_interactive )
+            .change_context( InvalidDirError( "server.static_dir" ) )?;
+        self.server
+            .assets_dir
+            .ensure( is_interactive )
+            .change_context( InvalidDirError( "server.assets_dir" ) )?;
+        if self.logger.files_emitted.is_emitted {
+            self.logger
+                .files_emitted
+                .dir
+                .ensure( is_interactive )
+                .change_context( InvalidDirError( "logger.files_emitted.dir" ) )?;
+        }
+
+        Ok( () )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, LayeredSettings)]
 pub struct GeneralConfigs {
     pub app_name: String,
+    /// The runtime environment, which selects the profile of the configuration files: development, staging,
+    /// production, test or a profile declared in the profiles.toml file.
+    #[settings( cli( short = 'e', long = "run-env", ty = String ) )]
     pub run_env:  RuntimeEnvironment,
 }
 
@@ -180,46 +265,169 @@ impl Default for GeneralConfigs {
     }
 }
 
-impl FigmentExtractor<'_, Self> for GeneralConfigs {}
-
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, LayeredSettings)]
 pub struct ServerConfigs {
-    pub sock_addr_v4: SocketAddrV4,
-    pub proxy_url:    Url,
-    pub static_dir:   validators::DirectoryPath,
-    pub assets_dir:   validators::DirectoryPath,
+    /// The listen addresses, separated by commas: IPv4, IPv6 or Unix domain sockets, e.g.
+    /// `127.0.0.1:5556,[::1]:5556,unix:/run/web_server.sock`.
+    #[settings( cli( short = 'a', long = "addr" ) )]
+    pub listen_addrs:   validators::ListenAddrs,
+    /// The URL of the backend, where the API calls are proxied, e.g. `http://localhost:5555`. See also
+    /// [`ProxySubconfig::unix_socket`].
+    #[settings( cli( long = "proxy-url" ) )]
+    pub proxy_url:      Url,
+    /// The static files directory.
+    #[settings( cli( short = 's', long = "static-dir", ty = PathBuf ) )]
+    pub static_dir:     validators::DirectoryPath,
+    /// The assets files directory.
+    #[settings( cli( long = "assets-dir", ty = PathBuf ) )]
+    pub assets_dir:     validators::DirectoryPath,
+    /// Token required by the admin routes. The admin routes are disabled if it is not set.
+    pub admin_token:    Option<Secret<String>>,
+    /// How long the listeners keep accepting connections after the shutdown started, while the readiness probe
+    /// fails, so the load balancers stop routing to the server first, e.g. `5s`.
+    #[settings( cli( long = "pre-stop-delay" ) )]
+    pub pre_stop_delay: validators::Duration,
+    /// How long the in-flight requests can take to finish on shutdown before being dropped, e.g. `30s`.
+    #[settings( cli( long = "drain-timeout" ) )]
+    pub drain_timeout:  validators::Duration,
+    /// Whether the plain listeners serve HTTP/2 with prior knowledge (h2c) besides HTTP/1.1.
+    #[settings( cli( long = "h2c", value_parser ) )]
+    pub is_h2c_enabled: bool,
+    pub metrics:        MetricsSubconfig,
+    pub compression:    CompressionSubconfig,
+    pub cache:          CacheSubconfig,
+    /// The HTTPS listeners. The plain ones are still served, e.g. for the internal traffic.
+    pub tls:            Option<TlsSubconfig>,
+    pub proxy:          ProxySubconfig,
 }
 
 impl Default for ServerConfigs {
     fn default() -> Self {
         Self {
-            sock_addr_v4: SocketAddrV4::new( Ipv4Addr::new( 127, 0, 0, 1 ), 5556 ),
-            proxy_url:    Url::parse( "http://127.0.0.1:5555" ).unwrap(),
-            static_dir:   PathBuf::from( "./build/static" ).try_into().unwrap_or_else( |err| {
-                println!(
-                    "Failed to parse the default value for the server.static_dir. Error: {}",
-                    err
-                );
-                validators::DirectoryPath::prompt()
-            } ),
-            assets_dir:   PathBuf::from( "./assets" ).try_into().unwrap_or_else( |err| {
-                println!(
-                    "Failed to parse the default value for the server.assets_dir. Error: {}",
-                    err
-                );
-                validators::DirectoryPath::prompt()
-            } ),
+            listen_addrs:   validators::ListenAddr::Tcp( ( Ipv4Addr::LOCALHOST, 5556 ).into() ).into(),
+            proxy_url:      Url::parse( "http://127.0.0.1:5555" ).unwrap(),
+            static_dir:     validators::DirectoryPath::new_unchecked( "./build/static" ),
+            assets_dir:     validators::DirectoryPath::new_unchecked( "./assets" ),
+            admin_token:    None,
+            pre_stop_delay: validators::Duration::from_secs( 0 ),
+            drain_timeout:  validators::Duration::from_secs( 30 ),
+            is_h2c_enabled: true,
+            metrics:        MetricsSubconfig::default(),
+            compression:    CompressionSubconfig::default(),
+            cache:          CacheSubconfig::default(),
+            tls:            None,
+            proxy:          ProxySubconfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetricsSubconfig {
+    /// Whether the prometheus metrics are served at `/metrics`.
+    pub is_exposed: bool,
+    /// Address of a dedicated metrics listener, IPv4 or IPv6. If it is not set, the main router serves the metrics.
+    pub sock_addr:  Option<SocketAddr>,
+}
+
+impl Default for MetricsSubconfig {
+    fn default() -> Self {
+        Self {
+            is_exposed: true,
+            sock_addr:  None,
+        }
+    }
+}
+
+/// Error type for the [`CacheSubconfig::validate`] function.
+#[derive(Error, Debug)]
+#[error( "The Cache-Control header of the files that are not fingerprinted is not valid: {0:?}" )]
+pub struct CacheControlError( String );
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CacheSubconfig {
+    /// How long the browsers cache the static files and the assets that are not fingerprinted, before revalidating
+    /// them with their `ETag`, e.g. `5m`. The fingerprinted files are cached as immutable.
+    pub revalidated_max_age: validators::Duration,
+}
+
+impl CacheSubconfig {
+    /// The `Cache-Control` of the static files and the assets that are not fingerprinted.
+    #[must_use]
+    pub fn revalidated_cache_control( &self ) -> String {
+        format!( "public, max-age={}", self.revalidated_max_age.as_std().as_secs() )
+    }
+
+    /// Checks the `Cache-Control` header made from the settings, so it can be sent as is.
+    ///
+    /// # Errors
+    ///
+    /// If the header value is not valid.
+    pub fn validate( &self ) -> Result<(), CacheControlError> {
+        let cache_control = self.revalidated_cache_control();
+        match http::HeaderValue::from_str( &cache_control ) {
+            Ok( _ ) => Ok( () ),
+            Err( _ ) => Err( CacheControlError( cache_control ) ),
+        }
+    }
+}
+
+impl Default for CacheSubconfig {
+    fn default() -> Self {
+        Self {
+            revalidated_max_age: validators::Duration::from_secs( 300 ),
         }
     }
 }
 
-impl FigmentExtractor<'_, Self> for ServerConfigs {}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxySubconfig {
+    /// How long to wait for a connection to the backend, e.g. `2s`.
+    pub connect_timeout:        validators::Duration,
+    /// How long to wait for the backend to start responding, then between two chunks of its response body, e.g.
+    /// `30s`.
+    pub read_timeout:           validators::Duration,
+    /// Maximum size of the request bodies proxied to the backend, and of the responses read whole for the server
+    /// functions, e.g. `10MiB`.
+    pub max_body_size:          validators::ByteSize,
+    /// Maximum size of the response bodies proxied from the backend, which are streamed, e.g. `1GiB`. It bounds the
+    /// backup archives downloaded through the proxy.
+    pub max_response_size:      validators::ByteSize,
+    /// How long an idle connection to the backend is kept in the pool, e.g. `90s`.
+    pub pool_idle_timeout:      validators::Duration,
+    pub pool_max_idle_per_host: usize,
+    /// Unix domain socket of the backend, when both run on the same host. The requests are then sent through it
+    /// instead of a TCP connection to the proxy URL, which still sets their host.
+    pub unix_socket:            Option<PathBuf>,
+}
+
+impl Default for ProxySubconfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout:        validators::Duration::from_secs( 2 ),
+            read_timeout:           validators::Duration::from_secs( 30 ),
+            max_body_size:          validators::ByteSize::new( 10 * 1024 * 1024 ),
+            max_response_size:      validators::ByteSize::new( 1024 * 1024 * 1024 ),
+            pool_idle_timeout:      validators::Duration::from_secs( 90 ),
+            pool_max_idle_per_host: 32,
+            unix_socket:            None,
+        }
+    }
+}
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, LayeredSettings)]
 pub struct LoggerConfigs {
+    /// The minimum level of the logs.
+    #[settings( cli(
+        short = 'l',
+        long = "log-level",
+        ty = String,
+        value_parser = ["trace", "debug", "info", "warn", "error"]
+    ) )]
     pub log_level:         logger::Level,
     pub is_stdout_emitted: bool,
+    pub stdout_format:     logger::StdoutFormat,
     pub files_emitted:     LoggerFilesEmittedSubconfig,
+    pub traces_exported:   LoggerTracesExportedSubconfig,
 }
 
 impl Default for LoggerConfigs {
@@ -227,32 +435,202 @@ impl Default for LoggerConfigs {
         Self {
             log_level:         logger::Level::from_str( "debug" ).unwrap(),
             is_stdout_emitted: true,
+            stdout_format:     logger::StdoutFormat::Pretty,
             files_emitted:     LoggerFilesEmittedSubconfig::default(),
+            traces_exported:   LoggerTracesExportedSubconfig::new( "photo-story-web-server" ),
         }
     }
 }
 
-impl FigmentExtractor<'_, Self> for LoggerConfigs {}
+/// Error type for the [`LoggerFilesEmittedSubconfig::validate`] function.
+#[derive(Error, Debug)]
+#[error( "The maximum size of the log files rotated by size must be greater than zero." )]
+pub struct FileRotationError;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct LoggerFilesEmittedSubconfig {
-    pub is_emitted:   bool,
-    pub dir:          validators::DirectoryPath,
-    pub files_prefix: String,
+    pub is_emitted:    bool,
+    /// Created if it is missing.
+    pub dir:           validators::DirectoryPath<validators::Writable>,
+    pub files_prefix:  String,
+    pub rotation:      Rotation,
+    /// Size a file can reach before being rotated, e.g. `100MiB`, only used by the `size` rotation.
+    pub max_file_size: validators::ByteSize,
+    /// Maximum number of rotated files kept. They are kept forever if neither this nor the maximum age is set.
+    pub max_files:     Option<usize>,
+    /// Maximum age of the rotated files kept, e.g. `14d`.
+    pub max_age:       Option<validators::Duration>,
+    /// Whether the rotated files are compressed with gzip.
+    pub is_compressed: bool,
+}
+
+impl LoggerFilesEmittedSubconfig {
+    /// Checks that the files rotated by size have a maximum size, or every log line would get its own file.
+    ///
+    /// # Errors
+    ///
+    /// If the rotation is by size and the maximum size is zero.
+    pub fn validate( &self ) -> Result<(), FileRotationError> {
+        if self.rotation == Rotation::Size && self.max_file_size.as_u64() == 0 {
+            return Err( FileRotationError );
+        }
+
+        Ok( () )
+    }
 }
 
 impl Default for LoggerFilesEmittedSubconfig {
     fn default() -> Self {
         Self {
-            is_emitted:   true,
-            dir:          PathBuf::from( "./logs" ).try_into().unwrap_or_else( |err| {
-                panic!(
-                    "Default settings used for logger but emitting to files is enabled and the failed to convert logs \
-                     directory: {}",
-                    err
-                )
-            } ),
-            files_prefix: "frontend.dev".to_string(),
+            is_emitted:    true,
+            dir:           validators::DirectoryPath::new_unchecked( "./logs" ),
+            files_prefix:  "frontend.dev".to_string(),
+            rotation:      Rotation::Hourly,
+            max_file_size: validators::ByteSize::new( 100 * 1024 * 1024 ),
+            max_files:     Some( 72 ),
+            max_age:       None,
+            is_compressed: true,
+        }
+    }
+}
+
+/// Error type for the [`SecurityHeadersConfigs::validate`] function.
+#[derive(Error, Debug)]
+pub enum SecurityHeadersError {
+    /// The name of a directive of the Content-Security-Policy is not valid.
+    #[error( "The Content-Security-Policy directive is not valid: {0}" )]
+    InvalidDirective( String ),
+    /// A source of a directive is empty, or has a separator or a character not allowed in a header.
+    #[error( "The Content-Security-Policy source of {0} is not valid: {1:?}" )]
+    InvalidSource( String, String ),
+    /// The value of a header has a character not allowed in a header.
+    #[error( "The {0} header is not valid: {1:?}" )]
+    InvalidHeader( &'static str, String ),
+}
+
+/// The security headers of the responses.
+#[derive(Debug, Serialize, Deserialize, Clone, LayeredSettings)]
+pub struct SecurityHeadersConfigs {
+    /// Whether the Content-Security-Policy is enforced, only reported or not sent.
+    #[settings( cli( long = "csp-mode", ty = String, value_parser = ["enforced", "report-only", "disabled"] ) )]
+    pub csp_mode:                     CspMode,
+    /// Whether the browsers report the violations of the policy to `/csp-report`, where they are logged.
+    pub is_csp_reported:              bool,
+    /// The directives of the policy and their sources. The nonce of the pages is added to `script-src` and
+    /// `style-src`.
+    pub csp_directives:               BTreeMap<String, Vec<String>>,
+    /// How long the browsers only use HTTPS, e.g. `365d`. `Strict-Transport-Security` is not sent if it is not set.
+    pub hsts_max_age:                 Option<validators::Duration>,
+    /// Whether HSTS also applies to the subdomains.
+    pub is_hsts_including_subdomains: bool,
+    /// Whether the pages can be framed by the pages of the same origin, with `X-Frame-Options`.
+    pub frame_options:                FrameOptions,
+    /// The `Referrer-Policy` header, not sent if it is empty.
+    pub referrer_policy:              String,
+    /// The `Permissions-Policy` header, not sent if it is empty.
+    pub permissions_policy:           String,
+}
+
+impl SecurityHeadersConfigs {
+    /// Checks that the directives and the headers can be sent.
+    ///
+    /// # Errors
+    ///
+    /// If a directive name, a source or a header value is not valid.
+    pub fn validate( &self ) -> Result<(), SecurityHeadersError> {
+        for ( directive, sources ) in &self.csp_directives {
+            let is_directive =
+                !directive.is_empty() && directive.chars().all( |c| c.is_ascii_alphanumeric() || c == '-' );
+            if !is_directive {
+                return Err( SecurityHeadersError::InvalidDirective( directive.clone() ) );
+            }
+
+            let invalid_source = sources.iter().find( |source| {
+                source.is_empty() || !source.chars().all( |c| c.is_ascii_graphic() && c != ';' && c != ',' )
+            } );
+            if let Some( source ) = invalid_source {
+                return Err( SecurityHeadersError::InvalidSource( directive.clone(), source.clone() ) );
+            }
+        }
+
+        let headers = [
+            ( "Referrer-Policy", &self.referrer_policy ),
+            ( "Permissions-Policy", &self.permissions_policy ),
+        ];
+        for ( name, value ) in headers {
+            if http::HeaderValue::from_str( value ).is_err() {
+                return Err( SecurityHeadersError::InvalidHeader( name, value.clone() ) );
+            }
+        }
+
+        Ok( () )
+    }
+}
+
+/// The directives of the default policy. The WebAssembly of the hydration needs `'wasm-unsafe-eval'`.
+const DEFAULT_CSP_DIRECTIVES: [( &str, &[&str] ); 9] = [
+    ( "default-src", &["'self'"] ),
+    ( "script-src", &["'self'", "'wasm-unsafe-eval'"] ),
+    ( "style-src", &["'self'"] ),
+    ( "img-src", &["'self'", "data:", "https:"] ),
+    ( "connect-src", &["'self'"] ),
+    ( "object-src", &["'none'"] ),
+    ( "base-uri", &["'self'"] ),
+    ( "form-action", &["'self'"] ),
+    ( "frame-ancestors", &["'none'"] ),
+];
+
+impl Default for SecurityHeadersConfigs {
+    fn default() -> Self {
+        let csp_directives = DEFAULT_CSP_DIRECTIVES
+            .into_iter()
+            .map( |( directive, sources )| {
+                ( directive.to_string(), sources.iter().map( ToString::to_string ).collect() )
+            } )
+            .collect();
+
+        Self {
+            csp_mode: CspMode::Enforced,
+            is_csp_reported: true,
+            csp_directives,
+            hsts_max_age: None,
+            is_hsts_including_subdomains: false,
+            frame_options: FrameOptions::Deny,
+            referrer_policy: "strict-origin-when-cross-origin".to_string(),
+            permissions_policy: "camera=(), microphone=(), geolocation=(), payment=()".to_string(),
+        }
+    }
+}
+
+/// How the Content-Security-Policy is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde( rename_all = "kebab-case" )]
+pub enum CspMode {
+    /// The policy is enforced, with `Content-Security-Policy`.
+    Enforced,
+    /// The violations are only reported, with `Content-Security-Policy-Report-Only`.
+    ReportOnly,
+    /// The policy is not sent.
+    Disabled,
+}
+
+/// The framing of the pages allowed by `X-Frame-Options`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde( rename_all = "lowercase" )]
+pub enum FrameOptions {
+    /// The pages can't be framed.
+    Deny,
+    /// The pages can only be framed by the pages of the same origin.
+    SameOrigin,
+}
+
+impl FrameOptions {
+    /// The value of the `X-Frame-Options` header.
+    #[must_use]
+    pub const fn as_header_value( self ) -> &'static str {
+        match self {
+            Self::Deny => "DENY",
+            Self::SameOrigin => "SAMEORIGIN",
         }
     }
 }
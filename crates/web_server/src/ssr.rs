@@ -1,30 +1,79 @@
+//! Server side rendering of the pages and the server functions with Leptos.
+
 use axum::{
     body::{boxed, Body, BoxBody},
-    extract::State,
-    http::{Request, Response, StatusCode, Uri},
+    extract::{Path, RawQuery, State},
+    http::{HeaderMap, Request, Response, StatusCode, Uri},
     response::IntoResponse,
     routing::post,
     Router,
 };
-use frontend::presentation::AppComponent;
-use leptos::{view, LeptosOptions};
-use leptos_axum::{generate_route_list, LeptosRoutes};
+use frontend::{infrastructure::backend::BackendApi, presentation::AppComponent};
+use leptos::{nonce::use_nonce, provide_context, use_context, view, LeptosOptions};
+use leptos_axum::{generate_route_list, LeptosRoutes, ResponseOptions};
 use tower::ServiceExt;
-use tower_http::services::ServeDir;
+use tower_http::{request_id::RequestId, services::ServeDir};
+
+use crate::{assets::AssetManifest, proxy, security_headers::ContentSecurityPolicy};
 
-pub async fn leptos_routes( app: Router<LeptosOptions>, leptos_options: LeptosOptions ) -> Router {
-    let routes = generate_route_list( |cx| view! {cx, <AppComponent/>} ).await;
-    app.leptos_routes( &leptos_options, routes, |cx| view! {cx, <AppComponent/>} )
-        .route( "/leptos/*path", post( leptos_axum::handle_server_fns ) )
-        .fallback( file_and_error_handler )
-        .with_state( leptos_options )
+/// The context provided to the rendered pages.
+#[derive(Clone)]
+pub struct PageContext {
+    pub csp:            Option<ContentSecurityPolicy>,
+    pub asset_manifest: AssetManifest,
+    pub backend_api:    BackendApi,
+}
+
+pub async fn leptos_routes(
+    app: Router<LeptosOptions>,
+    leptos_options: LeptosOptions,
+    page_context: PageContext,
+) -> Router {
+    let routes = generate_route_list( || view! { <AppComponent/> } ).await;
+    let route_page_context = page_context.clone();
+    let backend_api = page_context.backend_api.clone();
+    app.leptos_routes_with_handler(
+        routes,
+        move |State( options ): State<LeptosOptions>, req: Request<Body>| {
+            render_page( options, req, route_page_context.clone() )
+        },
+    )
+    .route(
+        "/leptos/*path",
+        post( move |path: Path<String>, headers: HeaderMap, raw_query: RawQuery, req: Request<Body>| {
+            let backend_api = backend_api.with_request_id( request_id( &req ) );
+            leptos_axum::handle_server_fns_with_context(
+                path,
+                headers,
+                raw_query,
+                move || provide_context( backend_api.clone() ),
+                req,
+            )
+        } ),
+    )
+    .fallback( move |uri: Uri, state: State<LeptosOptions>, req: Request<Body>| {
+        file_and_error_handler( uri, state, req, page_context.clone() )
+    } )
+    .with_state( leptos_options )
+}
+
+/// The backend API of the server functions, reached over HTTP with the pool of connections of the proxy.
+pub fn create_backend_api( proxy_state: proxy::ProxyState ) -> BackendApi {
+    BackendApi::new( move |path_query, request_id| {
+        let proxy_state = proxy_state.clone();
+        Box::pin( async move {
+            let response =
+                proxy_state.get( &path_query, request_id.as_deref() ).await.map_err( |err| err.to_string() )?;
+            Ok( response.map( |body| body.to_vec() ) )
+        } )
+    } )
 }
 
-#[axum::debug_handler]
 pub async fn file_and_error_handler(
     uri: Uri,
     State( options ): State<LeptosOptions>,
     req: Request<Body>,
+    page_context: PageContext,
 ) -> axum::response::Response {
     let root = &options.site_root;
     let result = get_static_file( uri, root ).await.unwrap();
@@ -32,8 +81,51 @@ pub async fn file_and_error_handler(
     if result.status() == StatusCode::OK {
         result.into_response()
     } else {
-        let handler = leptos_axum::render_app_to_stream( options, move |cx| view! {cx, <AppComponent/>} );
-        handler( req ).await.into_response()
+        render_page( options, req, page_context ).await
+    }
+}
+
+/// Renders the page of the request, whose id the backend API forwards.
+async fn render_page(
+    options: LeptosOptions,
+    req: Request<Body>,
+    mut page_context: PageContext,
+) -> axum::response::Response {
+    page_context.backend_api = page_context.backend_api.with_request_id( request_id( &req ) );
+    let handler = leptos_axum::render_app_to_stream_with_context(
+        options,
+        move || provide_page_context( &page_context ),
+        || view! { <AppComponent/> },
+    );
+    handler( req ).await.into_response()
+}
+
+/// The id of the request, set by the tracing middleware.
+fn request_id<B>( req: &Request<B> ) -> Option<String> {
+    req.extensions()
+        .get::<RequestId>()
+        .and_then( |request_id| request_id.header_value().to_str().ok() )
+        .map( ToString::to_string )
+}
+
+/// Provides the fingerprinted URLs of the static files and the backend API to the rendered page, and sends its
+/// Content-Security-Policy.
+fn provide_page_context( page_context: &PageContext ) {
+    provide_context( page_context.asset_manifest.clone() );
+    provide_context( page_context.backend_api.clone() );
+    provide_csp_header( page_context.csp.as_ref() );
+}
+
+/// Sends the Content-Security-Policy of the rendered page, allowing the nonce Leptos generated for it. Leptos adds the
+/// nonce to its hydration scripts, and the components to their inline styles with [`use_nonce`].
+fn provide_csp_header( csp: Option<&ContentSecurityPolicy> ) {
+    let Some( csp ) = csp else {
+        return;
+    };
+
+    let header_value = use_nonce().and_then( |nonce| csp.header_value_with_nonce( &nonce.to_string() ) );
+    if let ( Some( header_value ), Some( response_options ) ) = ( header_value, use_context::<ResponseOptions>() ) {
+        response_options.insert_header( csp.header_name().clone(), header_value );
     }
 }
 
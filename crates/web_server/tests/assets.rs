@@ -0,0 +1,89 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    routing::get_service,
+    Router,
+};
+use tower::util::ServiceExt;
+use tower_http::services::ServeDir;
+use web_server::{assets, settings};
+
+fn create_static_dir( name: &str ) -> PathBuf {
+    let dir = std::env::temp_dir().join( format!( "web_server-tests-{name}-{}", std::process::id() ) );
+    let _ = fs::remove_dir_all( &dir );
+    fs::create_dir_all( &dir ).unwrap();
+    fs::write( dir.join( "frontend.0123456789abcdef.css" ), "body { margin: 0; }" ).unwrap();
+    fs::write( dir.join( "frontend.0123456789abcdef.js" ), "export default function init() {}" ).unwrap();
+    fs::write( dir.join( "logo.svg" ), "<svg></svg>" ).unwrap();
+    let manifest = r#"{"frontend.css":"frontend.0123456789abcdef.css","frontend.js":"frontend.0123456789abcdef.js"}"#;
+    fs::write( dir.join( assets::MANIFEST_FILE_NAME ), manifest ).unwrap();
+    dir
+}
+
+fn create_app( static_dir: &Path ) -> Router {
+    let asset_manifest = assets::load_manifest( static_dir ).unwrap();
+    let app = Router::new().nest_service( assets::STATIC_URL_PATH, get_service( ServeDir::new( static_dir ) ) );
+    assets::middleware_cache_control( app, &asset_manifest, &settings::CacheSubconfig::default() )
+}
+
+#[test]
+fn manifest_maps_the_urls_to_the_fingerprinted_ones() {
+    let static_dir = create_static_dir( "manifest" );
+    let asset_manifest = assets::load_manifest( &static_dir ).unwrap();
+
+    assert_eq!( asset_manifest.url( "/static/frontend.css" ), "/static/frontend.0123456789abcdef.css" );
+    assert_eq!( asset_manifest.url( "/static/logo.svg" ), "/static/logo.svg" );
+    assert!( asset_manifest.is_fingerprinted( "/static/frontend.0123456789abcdef.css" ) );
+    assert!( !asset_manifest.is_fingerprinted( "/static/frontend.css" ) );
+    assert_eq!( assets::fingerprinted_output_name( &asset_manifest, "frontend" ), "frontend.0123456789abcdef" );
+
+    let without_manifest = assets::load_manifest( &std::env::temp_dir() ).unwrap();
+    assert_eq!( assets::fingerprinted_output_name( &without_manifest, "frontend" ), "frontend" );
+}
+
+#[tokio::test]
+async fn fingerprinted_files_are_immutable() {
+    let app = create_app( &create_static_dir( "immutable" ) );
+
+    let request = Request::get( "/static/frontend.0123456789abcdef.css" ).body( Body::empty() ).unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), StatusCode::OK );
+    assert_eq!( response.headers()[header::CACHE_CONTROL], "public, max-age=31536000, immutable" );
+    assert!( !response.headers().contains_key( header::ETAG ) );
+}
+
+#[tokio::test]
+async fn other_files_are_revalidated_with_their_etag() {
+    let app = create_app( &create_static_dir( "revalidated" ) );
+
+    let request = Request::get( "/static/logo.svg" ).body( Body::empty() ).unwrap();
+    let response = app.clone().oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), StatusCode::OK );
+    assert_eq!( response.headers()[header::CACHE_CONTROL], "public, max-age=300" );
+    let etag = response.headers()[header::ETAG].clone();
+    assert!( etag.to_str().unwrap().starts_with( "W/\"" ) );
+
+    let request = Request::get( "/static/logo.svg" ).header( header::IF_NONE_MATCH, etag.clone() );
+    let response = app.oneshot( request.body( Body::empty() ).unwrap() ).await.unwrap();
+
+    assert_eq!( response.status(), StatusCode::NOT_MODIFIED );
+    assert_eq!( response.headers()[header::ETAG], etag );
+    assert_eq!( response.headers()[header::CACHE_CONTROL], "public, max-age=300" );
+}
+
+#[test]
+fn revalidated_cache_control_is_validated_with_the_settings() {
+    let cache_settings = settings::CacheSubconfig {
+        revalidated_max_age: "1h".parse().unwrap(),
+    };
+
+    assert_eq!( cache_settings.revalidated_cache_control(), "public, max-age=3600" );
+    assert!( cache_settings.validate().is_ok() );
+}
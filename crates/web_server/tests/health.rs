@@ -0,0 +1,108 @@
+use std::net::SocketAddr;
+
+use axum::{
+    body::Body,
+    http::{self, Request},
+    routing::get,
+    Json, Router,
+};
+use serde_json::json;
+use tower::util::ServiceExt;
+use url::Url;
+use web_server::{health, proxy, settings, shutdown::Shutdown};
+
+/// Serves a stub of the backend readiness on a random local port, answering with `status`.
+fn spawn_stub_backend( status: http::StatusCode ) -> SocketAddr {
+    let report_status = if status.is_success() { "pass" } else { "fail" };
+    let report = json!( {
+        "status": report_status,
+        "checks": { "database": { "status": report_status, "latency_ms": 1.0 } },
+    } );
+    let ready = move || std::future::ready( ( status, Json( report.clone() ) ) );
+    let stub = Router::new().route( "/health/ready", get( ready ) );
+
+    let server = axum::Server::bind( &"127.0.0.1:0".parse().unwrap() ).serve( stub.into_make_service() );
+    let addr = server.local_addr();
+    tokio::spawn( server );
+
+    addr
+}
+
+fn create_health( backend_addr: SocketAddr, shutdown: Shutdown ) -> Router {
+    let api_url = Url::parse( &format!( "http://{backend_addr}" ) ).unwrap();
+    let client = proxy::ProxyState::new( api_url.clone(), &settings::ProxySubconfig::default() ).client;
+
+    health::create_route( health::HealthState {
+        client,
+        api_url,
+        shutdown,
+    } )
+}
+
+async fn get_report( app: Router, uri: &str ) -> ( http::StatusCode, serde_json::Value ) {
+    let request = Request::builder().uri( uri ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    let status = response.status();
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    ( status, serde_json::from_slice( &response_body ).unwrap() )
+}
+
+#[tokio::test]
+async fn health_live_does_not_depend_on_the_backend() {
+    // Nothing listens on the port of the discard protocol.
+    let app = create_health( "127.0.0.1:9".parse().unwrap(), Shutdown::default() );
+
+    let ( status, report ) = get_report( app, "/live" ).await;
+
+    assert_eq!( status, http::StatusCode::OK );
+    assert_eq!( report["status"], "pass" );
+}
+
+#[tokio::test]
+async fn health_ready_includes_the_backend_checks() {
+    let app = create_health( spawn_stub_backend( http::StatusCode::OK ), Shutdown::default() );
+
+    let ( status, report ) = get_report( app, "/ready" ).await;
+
+    assert_eq!( status, http::StatusCode::OK );
+    assert_eq!( report["checks"]["shutdown"]["status"], "pass" );
+    assert_eq!( report["checks"]["backend"]["status"], "pass" );
+    assert_eq!( report["checks"]["backend.database"]["status"], "pass" );
+}
+
+#[tokio::test]
+async fn health_ready_fails_when_the_backend_is_not_ready() {
+    let app = create_health( spawn_stub_backend( http::StatusCode::SERVICE_UNAVAILABLE ), Shutdown::default() );
+
+    let ( status, report ) = get_report( app, "/ready" ).await;
+
+    assert_eq!( status, http::StatusCode::SERVICE_UNAVAILABLE );
+    assert_eq!( report["checks"]["backend"]["status"], "fail" );
+    assert_eq!( report["checks"]["backend.database"]["status"], "fail" );
+}
+
+#[tokio::test]
+async fn health_ready_fails_when_the_backend_is_unreachable() {
+    let app = create_health( "127.0.0.1:9".parse().unwrap(), Shutdown::default() );
+
+    let ( status, report ) = get_report( app, "/ready" ).await;
+
+    assert_eq!( status, http::StatusCode::SERVICE_UNAVAILABLE );
+    assert_eq!( report["checks"]["backend"]["status"], "fail" );
+    assert!( report["checks"]["backend"]["message"].is_string() );
+}
+
+#[tokio::test]
+async fn health_ready_fails_while_draining() {
+    let shutdown = Shutdown::default();
+    let app = create_health( spawn_stub_backend( http::StatusCode::OK ), shutdown.clone() );
+    shutdown.trigger();
+
+    let ( status, report ) = get_report( app, "/ready" ).await;
+
+    assert_eq!( status, http::StatusCode::SERVICE_UNAVAILABLE );
+    assert_eq!( report["checks"]["shutdown"]["status"], "fail" );
+    assert_eq!( report["checks"]["backend"]["status"], "pass" );
+}
@@ -0,0 +1,364 @@
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    net::{SocketAddr, TcpListener, UdpSocket},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{Body, Bytes, StreamBody},
+    extract::ConnectInfo,
+    http::{self, uri::Scheme, HeaderMap, Request, Uri},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::Stream;
+use hyper::server::accept;
+use monitoring::logger::{self, EnableLayer, JaegerExportOptions, Level};
+use serde_json::json;
+use tokio::net::UnixListener;
+use tower::util::ServiceExt;
+use tracing::Instrument;
+use url::Url;
+use web_server::{proxy, settings};
+
+/// Serves a stub of the backend on a random local port.
+fn spawn_stub_backend() -> SocketAddr {
+    let stub = Router::new()
+        .route( "/api/v1/echo", get( echo ) )
+        .route( "/api/v1/upload", post( |body: String| async move { body.len().to_string() } ) )
+        .route( "/api/v1/large", get( || async { vec![b'a'; 2048] } ) )
+        .route( "/api/v1/failing", get( || async { http::StatusCode::SERVICE_UNAVAILABLE } ) )
+        .route(
+            "/api/v1/slow",
+            get( || async {
+                tokio::time::sleep( Duration::from_secs( 5 ) ).await;
+                "slow"
+            } ),
+        )
+        .route( "/api/v1/stalled", get( || async { stalled_body() } ) )
+        .route(
+            "/api/v1/large-stream",
+            get( || async { StreamBody::new( futures::stream::iter( vec![chunk( 1024 ), chunk( 1024 )] ) ) } ),
+        );
+
+    let server = axum::Server::bind( &"127.0.0.1:0".parse().unwrap() ).serve( stub.into_make_service() );
+    let addr = server.local_addr();
+    tokio::spawn( server );
+
+    addr
+}
+
+/// Sends the first chunk of the body, then stalls before the last one.
+fn stalled_body() -> StreamBody<impl Stream<Item = Result<Bytes, Infallible>>> {
+    StreamBody::new( futures::stream::unfold( 0, |sent_chunks| async move {
+        match sent_chunks {
+            0 => Some( ( chunk( 16 ), 1 ) ),
+            1 => {
+                tokio::time::sleep( Duration::from_secs( 5 ) ).await;
+                Some( ( chunk( 16 ), 2 ) )
+            }
+            _ => None,
+        }
+    } ) )
+}
+
+fn chunk( size: usize ) -> Result<Bytes, Infallible> { Ok( Bytes::from( vec![b'a'; size] ) ) }
+
+/// Answers with the path and headers received.
+async fn echo( uri: Uri, headers: HeaderMap ) -> Json<serde_json::Value> {
+    let headers: BTreeMap<String, String> = headers
+        .iter()
+        .map( |( name, value )| ( name.to_string(), value.to_str().unwrap().to_string() ) )
+        .collect();
+
+    Json( json!( { "path": uri.to_string(), "headers": headers } ) )
+}
+
+/// Serves a stub of the backend on a Unix domain socket of the temporary directory.
+fn spawn_stub_backend_on_unix_socket( name: &str ) -> PathBuf {
+    let path = std::env::temp_dir().join( format!( "web-server-tests-{name}-{}.sock", std::process::id() ) );
+    let _ = std::fs::remove_file( &path );
+
+    let listener = UnixListener::bind( &path ).unwrap();
+    let incoming = futures::stream::unfold( listener, |listener| async move {
+        let connection = listener.accept().await.map( |( stream, _ )| stream );
+        Some( ( connection, listener ) )
+    } );
+
+    let stub = Router::new().route( "/api/v1/echo", get( echo ) );
+    tokio::spawn( axum::Server::builder( accept::from_stream( incoming ) ).serve( stub.into_make_service() ) );
+
+    path
+}
+
+fn create_proxy_state( backend_addr: SocketAddr, proxy_settings: &settings::ProxySubconfig ) -> proxy::ProxyState {
+    let api_url = Url::parse( &format!( "http://{backend_addr}" ) ).unwrap();
+    proxy::ProxyState::new( api_url, proxy_settings )
+}
+
+fn create_proxy( backend_addr: SocketAddr, proxy_settings: &settings::ProxySubconfig ) -> Router {
+    Router::new().nest( "/api", proxy::create_route( create_proxy_state( backend_addr, proxy_settings ) ) )
+}
+
+#[tokio::test]
+async fn proxy_forwards_path_and_forwarding_headers() {
+    let app = create_proxy( spawn_stub_backend(), &settings::ProxySubconfig::default() );
+
+    let mut request = Request::builder()
+        .uri( "/api/v1/echo?page=2" )
+        .header( "host", "photos.example.com" )
+        .header( "connection", "keep-alive, x-hop-secret" )
+        .header( "x-hop-secret", "do-not-forward" )
+        .header( "x-request-id", "frontend-request-id" )
+        .body( Body::empty() )
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert( ConnectInfo( "10.0.0.7:40000".parse::<SocketAddr>().unwrap() ) );
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let echo: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( echo["path"], "/api/v1/echo?page=2" );
+    assert_eq!( echo["headers"]["x-forwarded-for"], "10.0.0.7" );
+    assert_eq!( echo["headers"]["x-forwarded-proto"], "http" );
+    assert_eq!( echo["headers"]["x-forwarded-host"], "photos.example.com" );
+    assert_eq!( echo["headers"]["x-request-id"], "frontend-request-id" );
+    assert!( echo["headers"].get( "x-hop-secret" ).is_none() );
+}
+
+#[tokio::test]
+async fn proxy_replaces_forged_forwarding_headers() {
+    let app = create_proxy( spawn_stub_backend(), &settings::ProxySubconfig::default() );
+
+    let mut request = Request::builder()
+        .uri( "/api/v1/echo" )
+        .header( "host", "photos.example.com" )
+        .header( "x-forwarded-for", "127.0.0.1" )
+        .header( "x-forwarded-proto", "https" )
+        .header( "x-forwarded-host", "admin.example.com" )
+        .body( Body::empty() )
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert( ConnectInfo( "10.0.0.7:40000".parse::<SocketAddr>().unwrap() ) );
+    request.extensions_mut().insert( Scheme::HTTP );
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let echo: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( echo["headers"]["x-forwarded-for"], "10.0.0.7" );
+    assert_eq!( echo["headers"]["x-forwarded-proto"], "http" );
+    assert_eq!( echo["headers"]["x-forwarded-host"], "photos.example.com" );
+}
+
+#[tokio::test]
+async fn proxy_drops_forged_forwarding_headers_it_cant_set() {
+    let app = create_proxy( spawn_stub_backend(), &settings::ProxySubconfig::default() );
+
+    // Neither the client address nor the host are known, e.g. over a Unix domain socket.
+    let request = Request::builder()
+        .uri( "/api/v1/echo" )
+        .header( "x-forwarded-for", "127.0.0.1" )
+        .header( "x-forwarded-host", "admin.example.com" )
+        .body( Body::empty() )
+        .unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let echo: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert!( echo["headers"].get( "x-forwarded-for" ).is_none() );
+    assert!( echo["headers"].get( "x-forwarded-host" ).is_none() );
+}
+
+#[tokio::test]
+async fn proxy_reaches_backend_over_unix_socket() {
+    let socket_path = spawn_stub_backend_on_unix_socket( "proxy" );
+    let proxy_settings = settings::ProxySubconfig {
+        unix_socket: Some( socket_path.clone() ),
+        ..Default::default()
+    };
+    // Nothing listens on the port, the host is only set in the requests.
+    let backend_addr = TcpListener::bind( "127.0.0.1:0" ).unwrap().local_addr().unwrap();
+    let app = create_proxy( backend_addr, &proxy_settings );
+
+    let request = Request::builder().uri( "/api/v1/echo" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let response_body = hyper::body::to_bytes( response.into_body() ).await.unwrap();
+    let echo: serde_json::Value = serde_json::from_slice( &response_body ).unwrap();
+    assert_eq!( echo["path"], "/api/v1/echo" );
+    assert_eq!( echo["headers"]["host"], backend_addr.to_string() );
+
+    std::fs::remove_file( socket_path ).unwrap();
+}
+
+#[tokio::test]
+async fn proxy_answers_bad_gateway_when_backend_is_down() {
+    // Bind and release a port, so nothing listens on it.
+    let backend_addr = TcpListener::bind( "127.0.0.1:0" ).unwrap().local_addr().unwrap();
+    let app = create_proxy( backend_addr, &settings::ProxySubconfig::default() );
+
+    let request = Request::builder().uri( "/api/v1/echo" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::BAD_GATEWAY );
+}
+
+#[tokio::test]
+async fn proxy_answers_gateway_timeout_when_backend_is_slow() {
+    let proxy_settings = settings::ProxySubconfig {
+        read_timeout: "1s".parse().unwrap(),
+        ..Default::default()
+    };
+    let app = create_proxy( spawn_stub_backend(), &proxy_settings );
+
+    let request = Request::builder().uri( "/api/v1/slow" ).body( Body::empty() ).unwrap();
+
+    let response = app.oneshot( request ).await.unwrap();
+
+    assert_eq!( response.status(), http::StatusCode::GATEWAY_TIMEOUT );
+}
+
+#[tokio::test]
+async fn proxy_aborts_the_response_when_backend_stalls_mid_body() {
+    let proxy_settings = settings::ProxySubconfig {
+        read_timeout: "1s".parse().unwrap(),
+        ..Default::default()
+    };
+    let app = create_proxy( spawn_stub_backend(), &proxy_settings );
+
+    let request = Request::builder().uri( "/api/v1/stalled" ).body( Body::empty() ).unwrap();
+
+    let started = Instant::now();
+    let response = app.oneshot( request ).await.unwrap();
+
+    // The response has started, so the body is aborted instead of waiting for the rest.
+    assert_eq!( response.status(), http::StatusCode::OK );
+    assert!( hyper::body::to_bytes( response.into_body() ).await.is_err() );
+    assert!( started.elapsed() < Duration::from_secs( 4 ) );
+}
+
+#[tokio::test]
+async fn proxy_rejects_responses_over_the_limit() {
+    let proxy_settings = settings::ProxySubconfig {
+        max_response_size: "1KiB".parse().unwrap(),
+        ..Default::default()
+    };
+    let app = create_proxy( spawn_stub_backend(), &proxy_settings );
+
+    let request = Request::builder().uri( "/api/v1/large" ).body( Body::empty() ).unwrap();
+    let response = app.clone().oneshot( request ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::BAD_GATEWAY );
+
+    // Without a `Content-Length` header, the body is aborted once over the limit.
+    let request = Request::builder().uri( "/api/v1/large-stream" ).body( Body::empty() ).unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::OK );
+    assert!( hyper::body::to_bytes( response.into_body() ).await.is_err() );
+}
+
+#[tokio::test]
+async fn proxy_rejects_bodies_over_the_limit() {
+    let proxy_settings = settings::ProxySubconfig {
+        max_body_size: "1KiB".parse().unwrap(),
+        ..Default::default()
+    };
+    let app = create_proxy( spawn_stub_backend(), &proxy_settings );
+
+    let request = Request::builder()
+        .method( http::Method::POST )
+        .uri( "/api/v1/upload" )
+        .header( "content-length", "2048" )
+        .body( Body::from( vec![b'a'; 2048] ) )
+        .unwrap();
+    let response = app.clone().oneshot( request ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::PAYLOAD_TOO_LARGE );
+
+    let request = Request::builder()
+        .method( http::Method::POST )
+        .uri( "/api/v1/upload" )
+        .body( Body::from( vec![b'a'; 512] ) )
+        .unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::OK );
+}
+
+#[tokio::test]
+async fn get_answers_with_the_status_and_body_of_the_backend() {
+    let proxy_state = create_proxy_state( spawn_stub_backend(), &settings::ProxySubconfig::default() );
+
+    let response = proxy_state.get( "/api/v1/echo?page=2", None ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::OK );
+    let echo: serde_json::Value = serde_json::from_slice( response.body() ).unwrap();
+    assert_eq!( echo["path"], "/api/v1/echo?page=2" );
+
+    // The id of the request being handled is forwarded.
+    let response = proxy_state.get( "/api/v1/echo", Some( "frontend-request-id" ) ).await.unwrap();
+    let echo: serde_json::Value = serde_json::from_slice( response.body() ).unwrap();
+    assert_eq!( echo["headers"]["x-request-id"], "frontend-request-id" );
+
+    // The statuses are kept for the server functions, which answer `404 Not Found` with none and the others with an
+    // error.
+    let response = proxy_state.get( "/api/v1/missing", None ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::NOT_FOUND );
+    let response = proxy_state.get( "/api/v1/failing", None ).await.unwrap();
+    assert_eq!( response.status(), http::StatusCode::SERVICE_UNAVAILABLE );
+}
+
+#[tokio::test]
+async fn get_fails_when_the_backend_is_down_slow_or_too_large() {
+    // Bind and release a port, so nothing listens on it.
+    let backend_addr = TcpListener::bind( "127.0.0.1:0" ).unwrap().local_addr().unwrap();
+    let proxy_state = create_proxy_state( backend_addr, &settings::ProxySubconfig::default() );
+    assert!( matches!( proxy_state.get( "/api/v1/echo", None ).await, Err( proxy::Error::BackendUnreachable( _ ) ) ) );
+
+    let proxy_settings = settings::ProxySubconfig {
+        read_timeout:  "1s".parse().unwrap(),
+        max_body_size: "1KiB".parse().unwrap(),
+        ..Default::default()
+    };
+    let proxy_state = create_proxy_state( spawn_stub_backend(), &proxy_settings );
+    assert!( matches!( proxy_state.get( "/api/v1/slow", None ).await, Err( proxy::Error::BackendTimeout( _ ) ) ) );
+    assert!( matches!( proxy_state.get( "/api/v1/stalled", None ).await, Err( proxy::Error::BackendTimeout( _ ) ) ) );
+    assert!( matches!(
+        proxy_state.get( "/api/v1/large", None ).await,
+        Err( proxy::Error::ResponseTooLarge( 1024 ) )
+    ) );
+}
+
+#[tokio::test( flavor = "multi_thread" )]
+async fn get_continues_the_trace_of_the_current_span() {
+    // The spans are only exported to a socket nobody reads, the trace context exists as soon as they are sampled.
+    let agent = UdpSocket::bind( "127.0.0.1:0" ).unwrap();
+    let tracer = logger::init_jaeger_tracer( &JaegerExportOptions {
+        service_name:   "web-server-proxy-tests",
+        agent_endpoint: &agent.local_addr().unwrap().to_string(),
+        sampling_ratio: 1.0,
+    } )
+    .unwrap();
+    let _guards = logger::init( &"info".parse::<Level>().unwrap(), &vec![EnableLayer::Jaeger( tracer )] );
+    let proxy_state = create_proxy_state( spawn_stub_backend(), &settings::ProxySubconfig::default() );
+
+    let response = proxy_state
+        .get( "/api/v1/echo", None )
+        .instrument( tracing::info_span!( "server-fn" ) )
+        .await
+        .unwrap();
+
+    let echo: serde_json::Value = serde_json::from_slice( response.body() ).unwrap();
+    let traceparent = echo["headers"]["traceparent"].as_str().expect( "The trace context was not injected." );
+    // `version-trace_id-parent_id-flags`, sampled.
+    let fields: Vec<&str> = traceparent.split( '-' ).collect();
+    assert_eq!( fields.len(), 4 );
+    assert_eq!( ( fields[0], fields[3] ), ( "00", "01" ) );
+}
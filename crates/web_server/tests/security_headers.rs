@@ -0,0 +1,140 @@
+use axum::{
+    body::Body,
+    http::{self, header, Request},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use tower::util::ServiceExt;
+use web_server::{
+    security_headers::{self, SecurityHeaders},
+    settings,
+};
+
+fn create_app( security_headers: SecurityHeaders ) -> Router {
+    let app = Router::new()
+        .route( "/", get( || async { "page" } ) )
+        .merge( security_headers::create_csp_report_route() );
+    security_headers::middleware_security_headers( app, security_headers )
+}
+
+#[tokio::test]
+async fn responses_get_the_security_headers() {
+    let security_headers_settings = settings::SecurityHeadersConfigs {
+        hsts_max_age: Some( "365d".parse().unwrap() ),
+        is_hsts_including_subdomains: true,
+        ..Default::default()
+    };
+    let app = create_app( SecurityHeaders::new( &security_headers_settings ) );
+
+    let response = app.oneshot( Request::get( "/" ).body( Body::empty() ).unwrap() ).await.unwrap();
+
+    let headers = response.headers();
+    assert_eq!( headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff" );
+    assert_eq!( headers[header::X_FRAME_OPTIONS], "DENY" );
+    assert_eq!( headers[header::REFERRER_POLICY], "strict-origin-when-cross-origin" );
+    assert_eq!( headers[header::STRICT_TRANSPORT_SECURITY], "max-age=31536000; includeSubDomains" );
+    assert!( headers.contains_key( "permissions-policy" ) );
+    assert!( headers.contains_key( "reporting-endpoints" ) );
+    let csp = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+    assert!( csp.contains( "default-src 'self'" ) );
+    assert!( csp.contains( "report-uri /csp-report" ) );
+    assert!( !csp.contains( "'nonce-" ) );
+}
+
+#[tokio::test]
+async fn report_only_policy_of_a_page_keeps_its_nonce() {
+    let security_headers_settings = settings::SecurityHeadersConfigs {
+        csp_mode: settings::CspMode::ReportOnly,
+        ..Default::default()
+    };
+    let security_headers = SecurityHeaders::new( &security_headers_settings );
+    let csp = security_headers.content_security_policy().unwrap().clone();
+    let page = move || async move {
+        let header_value = csp.header_value_with_nonce( "rAnd0m" ).unwrap();
+        ( [( csp.header_name().clone(), header_value )], "page" ).into_response()
+    };
+    let app = Router::new().route( "/", get( page ) );
+    let app = security_headers::middleware_security_headers( app, security_headers );
+
+    let response = app.oneshot( Request::get( "/" ).body( Body::empty() ).unwrap() ).await.unwrap();
+
+    let headers = response.headers();
+    assert!( !headers.contains_key( header::CONTENT_SECURITY_POLICY ) );
+    let csp = headers[header::CONTENT_SECURITY_POLICY_REPORT_ONLY].to_str().unwrap();
+    assert!( csp.contains( "script-src 'self' 'wasm-unsafe-eval' 'nonce-rAnd0m'" ) );
+    assert!( csp.contains( "style-src 'self' 'nonce-rAnd0m'" ) );
+    assert!( !csp.contains( "default-src 'self' 'nonce-" ) );
+}
+
+async fn send_report( app: Router, content_type: &str, report: impl Into<Body> ) -> http::StatusCode {
+    let request = Request::post( security_headers::CSP_REPORT_PATH )
+        .header( header::CONTENT_TYPE, content_type )
+        .body( report.into() )
+        .unwrap();
+
+    app.oneshot( request ).await.unwrap().status()
+}
+
+const REPORT: &str =
+    r#"{"csp-report":{"document-uri":"https://photos.example.com/","violated-directive":"script-src"}}"#;
+
+#[tokio::test]
+async fn csp_reports_are_collected() {
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ) );
+    let reports = r#"[{"type":"csp-violation","body":{"effectiveDirective":"script-src"}}]"#;
+
+    assert_eq!( send_report( app.clone(), "application/csp-report", REPORT ).await, http::StatusCode::NO_CONTENT );
+    assert_eq!(
+        send_report( app, "application/reports+json; charset=utf-8", reports ).await,
+        http::StatusCode::NO_CONTENT
+    );
+}
+
+#[tokio::test]
+async fn csp_reports_with_other_content_types_are_rejected() {
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ) );
+
+    for content_type in ["application/json", "text/plain", "application/csp-report-extra"] {
+        assert_eq!(
+            send_report( app.clone(), content_type, REPORT ).await,
+            http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "{content_type}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn large_csp_reports_are_rejected() {
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ) );
+    let report = format!( r#"{{"csp-report":{{"script-sample":"{}"}}}}"#, "a".repeat( 16 * 1024 ) );
+
+    assert_eq!(
+        send_report( app, "application/csp-report", report ).await,
+        http::StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
+
+#[tokio::test]
+async fn csp_reports_are_rate_limited() {
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ) );
+
+    let mut statuses = Vec::new();
+    for _ in 0..20 {
+        statuses.push( send_report( app.clone(), "application/csp-report", REPORT ).await );
+    }
+
+    assert!( statuses.contains( &http::StatusCode::NO_CONTENT ) );
+    assert_eq!( statuses.last(), Some( &http::StatusCode::TOO_MANY_REQUESTS ) );
+}
+
+#[test]
+fn invalid_csp_directives_are_rejected() {
+    let mut security_headers_settings = settings::SecurityHeadersConfigs::default();
+    security_headers_settings
+        .csp_directives
+        .insert( "script-src".to_string(), vec!["'self'; object-src *".to_string()] );
+
+    assert!( security_headers_settings.validate().is_err() );
+    assert!( settings::SecurityHeadersConfigs::default().validate().is_ok() );
+}
@@ -0,0 +1,167 @@
+#![cfg( feature = "ssr" )]
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::{self, Body},
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use common::api::photos::{get_photo::Output as Photo, list_photos};
+use frontend::infrastructure::{backend::BackendApi, by_features::photos::GetPhoto};
+use hyper::Response;
+use leptos::{server_fn::ServerFn, LeptosOptions};
+use tower::util::ServiceExt;
+use web_server::{
+    assets::AssetManifest,
+    logger,
+    security_headers::{self, SecurityHeaders},
+    settings,
+    ssr::{self, PageContext},
+};
+
+/// The paths the backend was asked for, with the id of the request they were sent for.
+type BackendRequests = Arc<Mutex<Vec<( String, Option<String> )>>>;
+
+/// A backend API listing the `photos` in a single page and answering `404 Not Found` to the other requests, and the
+/// requests it received.
+fn create_backend_api( photos: Vec<Photo> ) -> ( BackendApi, BackendRequests ) {
+    let page = list_photos::Output { photos, next_cursor: None };
+    let page = rkyv::to_bytes::<_, 1024>( &page ).unwrap().to_vec();
+    let backend_requests = BackendRequests::default();
+    let received_requests = backend_requests.clone();
+    let backend_api = BackendApi::new( move |path_query, request_id| {
+        let response = if path_query == "/api/v1/photos" {
+            Response::builder().status( StatusCode::OK ).body( page.clone() )
+        } else {
+            Response::builder().status( StatusCode::NOT_FOUND ).body( Vec::new() )
+        };
+        received_requests.lock().unwrap().push( ( path_query, request_id ) );
+        Box::pin( async move { Ok( response.unwrap() ) } )
+    } );
+
+    ( backend_api, backend_requests )
+}
+
+async fn render( app: Router, uri: &str ) -> ( StatusCode, HeaderMap, String ) {
+    let response = app.oneshot( Request::get( uri ).body( Body::empty() ).unwrap() ).await.unwrap();
+    let ( parts, page ) = response.into_parts();
+    let page = body::to_bytes( page ).await.unwrap();
+
+    ( parts.status, parts.headers, String::from_utf8( page.to_vec() ).unwrap() )
+}
+
+async fn create_app( security_headers: SecurityHeaders, backend_api: BackendApi ) -> Router {
+    let site_root = std::env::temp_dir().join( format!( "web_server-tests-ssr-{}", std::process::id() ) );
+    let leptos_options = LeptosOptions::builder()
+        .output_name( "frontend" )
+        .site_root( site_root.to_string_lossy().to_string() )
+        .build();
+    let page_context = PageContext {
+        csp:            security_headers.content_security_policy().cloned(),
+        asset_manifest: AssetManifest::default(),
+        backend_api,
+    };
+
+    let app = ssr::leptos_routes( Router::new(), leptos_options, page_context ).await;
+    security_headers::middleware_security_headers( app, security_headers )
+}
+
+#[tokio::test]
+async fn rendered_pages_allow_their_scripts_with_the_nonce_of_the_policy() {
+    let ( backend_api, _ ) = create_backend_api( Vec::new() );
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ), backend_api ).await;
+
+    let ( status, headers, page ) = render( app, "/" ).await;
+
+    assert_eq!( status, StatusCode::OK );
+    let csp = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+    let nonce = csp
+        .split_whitespace()
+        .find_map( |source| source.strip_prefix( "'nonce-" ) )
+        .and_then( |source| source.strip_suffix( "';" ).or_else( || source.strip_suffix( '\'' ) ) )
+        .expect( "The policy of the page has no nonce." );
+
+    // Every inline script and style of the page is allowed by the policy.
+    assert!( page.contains( &format!( "nonce=\"{nonce}\"" ) ) );
+    for tag in ["<script", "<style"] {
+        for element in page.split( tag ).skip( 1 ) {
+            let start_tag = &element[..element.find( '>' ).unwrap()];
+            let is_external = start_tag.contains( " src=" );
+            assert!( is_external || start_tag.contains( &format!( "nonce=\"{nonce}\"" ) ), "{tag}{start_tag}>" );
+        }
+    }
+}
+
+#[tokio::test]
+async fn server_functions_are_provided_the_backend_api() {
+    let ( backend_api, backend_requests ) = create_backend_api( Vec::new() );
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ), backend_api ).await;
+    let app = logger::middleware_http_tracing( app );
+
+    // Called by the browsers with the arguments encoded with CBOR.
+    let mut args = Vec::new();
+    ciborium::ser::into_writer( &GetPhoto { id: 7 }, &mut args ).unwrap();
+    let request = Request::builder()
+        .method( Method::POST )
+        .uri( format!( "/leptos/{}", <GetPhoto as ServerFn<()>>::url() ) )
+        .header( header::CONTENT_TYPE, "application/cbor" )
+        .header( header::ACCEPT, "application/cbor" )
+        .header( "x-request-id", "frontend-request-id" )
+        .body( Body::from( args ) )
+        .unwrap();
+    let response = app.oneshot( request ).await.unwrap();
+
+    // Without the backend API, the server function would answer with an error. The id of the request is forwarded.
+    assert_eq!( response.status(), StatusCode::OK );
+    assert_eq!(
+        *backend_requests.lock().unwrap(),
+        [( "/api/v1/photos/7".to_string(), Some( "frontend-request-id".to_string() ) )]
+    );
+    let output = body::to_bytes( response.into_body() ).await.unwrap();
+    let photo: Option<Photo> = ciborium::de::from_reader( output.as_ref() ).unwrap();
+    assert_eq!( photo, None );
+}
+
+#[tokio::test]
+async fn gallery_is_laid_out_without_inline_styles() {
+    let photo = |id, width, height| Photo {
+        id,
+        created_at:  1_700_000_000,
+        url:         format!( "/photos/{id}.jpg" ),
+        title:       format!( "Photo {id}" ),
+        description: None,
+        width,
+        height,
+    };
+    let ( backend_api, _ ) = create_backend_api( vec![photo( 2, Some( 3000 ), Some( 2000 ) ), photo( 1, None, None )] );
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ), backend_api ).await;
+
+    let ( status, headers, page ) = render( app, "/photos" ).await;
+
+    assert_eq!( status, StatusCode::OK );
+    // The policy blocks the style attributes, the space of the photos is reserved by the attributes of their image.
+    let csp = headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+    assert!( !csp.contains( "'unsafe-inline'" ) );
+    assert!( !page.contains( " style=" ) );
+    assert!( page.contains( "src=\"/photos/2.jpg\"" ) );
+    assert_eq!( page.matches( "width=\"360\" height=\"240\"" ).count(), 2 );
+}
+
+#[tokio::test]
+async fn rendered_pages_forward_their_request_id_to_the_backend() {
+    let ( backend_api, backend_requests ) = create_backend_api( Vec::new() );
+    let app = create_app( SecurityHeaders::new( &settings::SecurityHeadersConfigs::default() ), backend_api ).await;
+    let app = logger::middleware_http_tracing( app );
+
+    let request = Request::get( "/photos" ).header( "x-request-id", "frontend-request-id" ).body( Body::empty() );
+    let response = app.oneshot( request.unwrap() ).await.unwrap();
+    assert_eq!( response.status(), StatusCode::OK );
+    body::to_bytes( response.into_body() ).await.unwrap();
+
+    let backend_requests = backend_requests.lock().unwrap();
+    assert!( !backend_requests.is_empty() );
+    assert!( backend_requests
+        .iter()
+        .all( |( _, request_id )| request_id.as_deref() == Some( "frontend-request-id" ) ) );
+}